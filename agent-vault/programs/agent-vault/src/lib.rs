@@ -1,51 +1,1133 @@
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_instruction_at_checked, ID as INSTRUCTIONS_SYSVAR_ID,
+};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
 
 declare_id!("2RaQkqGn8wyMfLEWBRjbz76ZwqrXUJyxvgiKrmMjUtn7");
 
+// =========================
+// WITHDRAWAL AUTHORIZATION
+// =========================
+//
+// A withdrawal authorization is a signature (produced off-chain, e.g. by the
+// MXE's distributed signing key) over the SHA-256 hash of the canonical
+// encoding:
+//
+//   vault: Pubkey      (32 bytes)
+//   recipient: Pubkey  (32 bytes)
+//   amount: u64        (8 bytes, little-endian)
+//   nonce: u64         (8 bytes, little-endian)
+//   expiry: i64        (8 bytes, little-endian)
+//
+// `gated_withdraw` expects the Ed25519 program signature-verification
+// instruction immediately preceding it in the same transaction (checked via
+// the instructions sysvar) to have verified exactly this message against
+// `AgentState.signing_authority`.
+fn hash_withdrawal_authorization(
+    vault: &Pubkey,
+    recipient: &Pubkey,
+    amount: u64,
+    nonce: u64,
+    expiry: i64,
+) -> [u8; 32] {
+    let mut preimage = Vec::with_capacity(32 + 32 + 8 + 8 + 8);
+    preimage.extend_from_slice(vault.as_ref());
+    preimage.extend_from_slice(recipient.as_ref());
+    preimage.extend_from_slice(&amount.to_le_bytes());
+    preimage.extend_from_slice(&nonce.to_le_bytes());
+    preimage.extend_from_slice(&expiry.to_le_bytes());
+    solana_program::hash::hash(&preimage).to_bytes()
+}
+
+fn withdraw_lamports(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+
+    require!(
+        vault.owner == ctx.accounts.owner.key(),
+        ErrorCode::Unauthorized
+    );
+
+    require!(vault.balance >= amount, ErrorCode::InsufficientFunds);
+    require_rent_exemption_preserved(&vault.to_account_info(), amount)?;
+
+    **vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **ctx.accounts.owner.to_account_info().try_borrow_mut_lamports()? += amount;
+
+    vault.balance = vault
+        .balance
+        .checked_sub(amount)
+        .ok_or(ErrorCode::Underflow)?;
+
+    emit!(WithdrawnEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.owner.key(),
+        amount,
+        new_balance: vault.balance,
+    });
+
+    invoke_vault_hook(vault, amount, HOOK_DIRECTION_WITHDRAW, ctx.remaining_accounts)?;
+
+    Ok(())
+}
+
+/// Guards against `withdraw`/`gated_withdraw` pushing a `Vault` account's
+/// actual lamports below its rent-exempt minimum, which would leave it
+/// eligible for purge and `balance` meaningless regardless of how
+/// carefully that field was tracked. Checked against live lamports, not
+/// `vault.balance`, so it still catches the case where `balance` has
+/// desynced ahead of what's actually in the account — see `reconcile`'s
+/// doc comment for how that desync happens.
+fn require_rent_exemption_preserved(vault_info: &AccountInfo, amount: u64) -> Result<()> {
+    let rent_exempt_min = Rent::get()?.minimum_balance(vault_info.data_len());
+    let remaining = vault_info
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ErrorCode::WouldBreakRentExemption)?;
+    require!(remaining >= rent_exempt_min, ErrorCode::WouldBreakRentExemption);
+    Ok(())
+}
+
+/// Applies a just-landed `amount` transfer to `vault.balance`. When
+/// `vault.auto_reconcile` is set, resyncs `balance` to the vault's actual
+/// lamports (minus the rent-exempt minimum) instead of strictly
+/// incrementing by `amount`, absorbing any direct transfers that bypassed
+/// `deposit`/`withdraw`. Returns the reconciled delta beyond the plain
+/// `amount` increment (`0` when auto-reconcile didn't trigger), for the
+/// caller to report in `DepositedEvent`.
+fn apply_deposit_balance(vault: &mut Account<Vault>, amount: u64) -> Result<i64> {
+    if vault.auto_reconcile {
+        let vault_info = vault.to_account_info();
+        let rent_exempt_min = Rent::get()?.minimum_balance(vault_info.data_len());
+        let actual_available = vault_info.lamports().saturating_sub(rent_exempt_min);
+        let expected = vault.balance.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        let delta = actual_available as i64 - expected as i64;
+        vault.balance = actual_available;
+        Ok(delta)
+    } else {
+        vault.balance = vault.balance.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        Ok(0)
+    }
+}
+
+/// Lamports per micro-SOL: 1 SOL = 1_000_000_000 lamports = 1_000_000
+/// micro-SOL, so 1 micro-SOL = exactly 1_000 lamports. Used by the
+/// `_micro_sol` instruction variants (`deposit_micro_sol`,
+/// `withdraw_micro_sol`) so clients that think in SOL-denominated amounts
+/// don't have to hand-roll the lamport conversion themselves.
+pub const LAMPORTS_PER_MICRO_SOL: u64 = 1_000;
+
+/// Converts a micro-SOL amount to lamports with an explicit overflow check.
+/// See [`LAMPORTS_PER_MICRO_SOL`] for the exact conversion factor.
+fn micro_sol_to_lamports(amount_micro_sol: u64) -> Result<u64> {
+    amount_micro_sol
+        .checked_mul(LAMPORTS_PER_MICRO_SOL)
+        .ok_or_else(|| ErrorCode::Overflow.into())
+}
+
+/// Deterministic-clock override for integration tests, read by [`now`]
+/// instead of the real `Clock` sysvar — set via `set_test_clock`. Only
+/// ever consulted by a binary compiled with the `test-clock` feature; see
+/// [`now`]'s doc comment for why a production build must never enable it.
+#[cfg(feature = "test-clock")]
+#[account]
+pub struct TestClock {
+    pub authority: Pubkey,
+    pub unix_timestamp: i64,
+    pub bump: u8,
+}
+
+#[cfg(feature = "test-clock")]
+pub const TEST_CLOCK_SEED: &[u8] = b"test_clock";
+
+/// Reads the current Unix timestamp, centralizing every time-based gate
+/// in this program (epochs, velocity, cooldowns, timeouts, nonces) behind
+/// one call so the whole suite of them can be made deterministic in
+/// tests the same way, instead of each gate calling `Clock::get()`
+/// directly and tests having to wait on real time to exercise rollovers.
+///
+/// A production build (the default — no `test-clock` feature) always
+/// resolves to the real sysvar via `Clock::get()?.unix_timestamp` and
+/// ignores `test_clock` entirely, even if a caller supplies one. This
+/// isn't just a default: the branch that would ever trust `test_clock`
+/// is compiled out of that binary altogether, so there is no way to use
+/// it to spoof a cooldown, timeout, or nonce window against a production
+/// deployment.
+///
+/// A binary compiled with `test-clock` instead reads `test_clock.
+/// unix_timestamp` whenever `test_clock` is `Some` and actually
+/// deserializes as a [`TestClock`]; passing `None`, or an account that
+/// isn't a `TestClock`, still falls back to the real sysvar.
+fn now(test_clock: Option<&UncheckedAccount>) -> Result<i64> {
+    #[cfg(feature = "test-clock")]
+    {
+        if let Some(account) = test_clock {
+            if let Ok(clock) = Account::<TestClock>::try_from(&account.to_account_info()) {
+                return Ok(clock.unix_timestamp);
+            }
+        }
+    }
+    let _ = test_clock;
+    Ok(Clock::get()?.unix_timestamp)
+}
+
+/// Self-contained price record — see `set_price_feed`'s doc comment for
+/// why this program defines its own rather than parsing a real Pyth/
+/// Switchboard account. `price_usd_cents_per_sol` is USD cents per whole
+/// SOL (1e9 lamports), matching how `daily_limit_usd_cents` and
+/// `spent_today_usd_cents` are denominated in `UsdSpendingLimit`.
+#[account]
+pub struct PriceFeed {
+    pub authority: Pubkey,
+    pub price_usd_cents_per_sol: u64,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+/// Seed (with `authority` appended) for a [`PriceFeed`] PDA.
+pub const PRICE_FEED_SEED: &[u8] = b"price_feed";
+
+/// Maximum entries in `Vault::hook_allowlist`.
+pub const MAX_HOOK_ALLOWLIST: usize = 4;
+
+/// Seed (with `vault` and `message` appended) for a
+/// [`VerifiedWithdrawalAuthorization`] PDA.
+pub const VERIFIED_WITHDRAWAL_SEED: &[u8] = b"verified_withdrawal";
+
+/// How long a [`VerifiedWithdrawalAuthorization`] stays redeemable after
+/// `receive_plaintext_verification_result` records it, checked by
+/// `gated_withdraw_verified` against `record.recorded_at`. This is separate
+/// from `amount`/`nonce`/`expiry`'s own `max_staleness`/`expiry` handling on
+/// the withdrawal itself — it bounds how long the MXE's verdict can sit on
+/// an unconsumed record before a withdrawal can no longer redeem it, so a
+/// verification that landed but was never spent can't authorize a
+/// withdrawal arbitrarily far in the future.
+pub const VERIFIED_WITHDRAWAL_MAX_AGE_SECS: i64 = 300;
+
+/// `direction` passed to a hook's `on_vault_funds_moved`: a deposit credited
+/// `vault`.
+const HOOK_DIRECTION_DEPOSIT: u8 = 0;
+
+/// `direction` passed to a hook's `on_vault_funds_moved`: a withdrawal
+/// debited `vault`.
+const HOOK_DIRECTION_WITHDRAW: u8 = 1;
+
+/// 8-byte discriminator prefixed to the CPI data `deposit`/`withdraw`/
+/// `gated_withdraw`/`evaluate_and_withdraw` send to an allowlisted
+/// `hook_program`, computed the same way Anchor derives instruction
+/// discriminators (`sha256("global:<name>")[..8]`) so a downstream Anchor
+/// program can declare a matching `#[program]` method named
+/// `on_vault_funds_moved` and decode the rest positionally.
+fn vault_hook_discriminator() -> [u8; 8] {
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&solana_program::hash::hash(b"global:on_vault_funds_moved").to_bytes()[..8]);
+    out
+}
+
+/// Calls an owner-allowlisted `hook_program` after `deposit`/`withdraw`/
+/// `gated_withdraw`/`evaluate_and_withdraw` move lamports, so integrators
+/// can react (e.g. update an external accounting ledger) without forking
+/// this program. A caller requests a hook by passing the hook program id as
+/// `remaining_accounts[0]` and any accounts that program's instruction
+/// needs as `remaining_accounts[1..]`; passing no remaining accounts means
+/// no hook runs.
+///
+/// The hook program must already be in `Vault::hook_allowlist` (set by the
+/// vault's owner via `set_hook_allowlist`), or this returns
+/// `HookProgramNotAllowlisted` before ever invoking it — this program never
+/// hands lamports-in-flight control to an owner-unapproved program.
+///
+/// ## Hook interface
+///
+/// The hook program must expose an Anchor instruction named
+/// `on_vault_funds_moved` (the CPI's 8-byte discriminator is computed the
+/// same way Anchor would — see [`vault_hook_discriminator`]) whose accounts
+/// are exactly `remaining_accounts[1..]`, in the order the caller supplied
+/// them, and whose instruction data after the discriminator is:
+///
+///   vault: Pubkey     (32 bytes)
+///   direction: u8     (`HOOK_DIRECTION_DEPOSIT` or `HOOK_DIRECTION_WITHDRAW`)
+///   amount: u64       (8 bytes, little-endian, in lamports)
+///
+/// A hook that returns an error aborts the whole deposit/withdraw
+/// atomically — the lamport transfer that already landed is rolled back
+/// along with it, since everything runs in the same instruction.
+fn invoke_vault_hook(
+    vault: &Account<Vault>,
+    amount: u64,
+    direction: u8,
+    remaining_accounts: &[AccountInfo],
+) -> Result<()> {
+    if remaining_accounts.is_empty() {
+        return Ok(());
+    }
+    let hook_program = remaining_accounts[0].key();
+    require!(
+        vault.hook_allowlist[..vault.hook_allowlist_count as usize].contains(&hook_program),
+        ErrorCode::HookProgramNotAllowlisted
+    );
+
+    let mut data = vault_hook_discriminator().to_vec();
+    data.extend_from_slice(vault.key().as_ref());
+    data.push(direction);
+    data.extend_from_slice(&amount.to_le_bytes());
+    let account_metas = remaining_accounts[1..]
+        .iter()
+        .map(|info| solana_program::instruction::AccountMeta::new(info.key(), false))
+        .collect::<Vec<_>>();
+    let ix = solana_program::instruction::Instruction {
+        program_id: hook_program,
+        accounts: account_metas,
+        data,
+    };
+    solana_program::program::invoke(&ix, remaining_accounts)?;
+    Ok(())
+}
+
+/// Shared body behind `deposit` and `deposit_micro_sol` — `amount` is
+/// already in lamports by the time it reaches here.
+fn deposit_lamports(
+    ctx: Context<Deposit>,
+    amount: u64,
+    idempotency_key: [u8; 16],
+) -> Result<()> {
+    let vault_owner = ctx.accounts.vault.owner;
+    require!(
+        vault_owner == ctx.accounts.owner.key(),
+        ErrorCode::Unauthorized
+    );
+
+    let clock_ts = now(ctx.accounts.test_clock.as_ref())?;
+    let log = &mut ctx.accounts.deposit_log;
+    if log.vault == Pubkey::default() {
+        log.vault = ctx.accounts.vault.key();
+    }
+    log.reject_if_duplicate(&idempotency_key, clock_ts)?;
+
+    let transfer_ix = solana_program::system_instruction::transfer(
+        &ctx.accounts.owner.key(),
+        &ctx.accounts.vault.key(),
+        amount,
+    );
+
+    solana_program::program::invoke(
+        &transfer_ix,
+        &[
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+        ],
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    let reconciled_delta = apply_deposit_balance(vault, amount)?;
+
+    log.record(&idempotency_key, clock_ts);
+    emit!(DepositedEvent {
+        vault: vault.key(),
+        owner: vault_owner,
+        amount,
+        reconciled_delta,
+        new_balance: vault.balance,
+    });
+
+    invoke_vault_hook(vault, amount, HOOK_DIRECTION_DEPOSIT, ctx.remaining_accounts)?;
+
+    Ok(())
+}
+
+fn apply_risk_score(
+    state: &mut Account<AgentState>,
+    owner: &Pubkey,
+    risk_score: u8,
+    test_clock: Option<&UncheckedAccount>,
+) -> Result<()> {
+    require!(risk_score <= 100, ErrorCode::InvalidRiskScore);
+    require!(state.owner == *owner, ErrorCode::Unauthorized);
+
+    let current_ts = now(test_clock)?;
+    if state.min_eval_interval_secs > 0 {
+        require!(
+            current_ts - state.last_eval_timestamp >= state.min_eval_interval_secs,
+            ErrorCode::EvaluationTooSoon
+        );
+        state.last_eval_timestamp = current_ts;
+    }
+
+    state.risk_score = risk_score;
+    state.risk_updated_at = current_ts;
+    state.execution_enabled = risk_score <= state.max_risk_score;
+
+    // `high_risk_streak`/`freeze_after` catch an agent that's repeatedly
+    // high-risk but never badly enough on any single reading for the
+    // owner to notice and freeze it by hand — see `AgentState::
+    // freeze_after`'s doc comment. A qualifying reading accumulates the
+    // streak; anything else resets it, so only *consecutive* high-risk
+    // readings count. `frozen` is sticky once set here — unlike
+    // `execution_enabled`, which `apply_risk_score` recomputes every call,
+    // only `unfreeze` (or `set_agent_frozen(false)`) clears it, even once
+    // a later reading comes back low.
+    if risk_score > state.max_risk_score {
+        state.high_risk_streak = state.high_risk_streak.saturating_add(1);
+        if state.freeze_after > 0 && state.high_risk_streak >= state.freeze_after && !state.frozen {
+            state.frozen = true;
+            emit!(AgentFrozenEvent {
+                agent_state: state.key(),
+                frozen: true,
+            });
+        }
+    } else {
+        state.high_risk_streak = 0;
+    }
+
+    Ok(())
+}
+
+/// Fails fast with `IncompatibleVersion` when `program_config.version` is
+/// below `min_version`, and with `ProgramConfigRequired` when a caller
+/// supplied `min_version` but not the `program_config` needed to check it
+/// against — a caller that omits `min_version` entirely skips this check,
+/// regardless of whether `program_config` was passed.
+fn require_min_version(program_config: Option<&ProgramConfig>, min_version: Option<u16>) -> Result<()> {
+    if let Some(min_version) = min_version {
+        let program_config = program_config.ok_or(ErrorCode::ProgramConfigRequired)?;
+        require!(
+            program_config.version >= min_version,
+            ErrorCode::IncompatibleVersion
+        );
+    }
+    Ok(())
+}
+
+/// Checks `destination` against `state.destination_list` under
+/// `state.destination_list_mode`, per [`DestinationListMode`]'s doc
+/// comment. Called from `execute_gated_withdraw` for `service_account`
+/// and for any `recipient` other than `owner` — see `AgentState::destination_list`'s
+/// doc comment for why `owner` itself isn't subject to this check.
+fn check_destination_allowed(state: &AgentState, destination: &Pubkey) -> Result<()> {
+    let listed = state.destination_list[..state.destination_list_count as usize].contains(destination);
+    match state.destination_list_mode {
+        DestinationListMode::Denylist => require!(!listed, ErrorCode::DestinationDenied),
+        DestinationListMode::Allowlist => require!(listed, ErrorCode::DestinationDenied),
+    }
+    Ok(())
+}
+
+/// Shared core of `gated_withdraw`, also used by `evaluate_and_withdraw` so
+/// both entry points enforce exactly the same gates.
+///
+/// `use_actual_lamports` controls which balance the `InsufficientFunds`
+/// check is measured against:
+///
+/// - `false` (default, strict): checked against `vault.balance`, the
+///   tracked balance as of the last `deposit`/`withdraw` processed by this
+///   program. This is the safe default — it never admits a withdrawal the
+///   vault's own bookkeeping hasn't caught up to yet.
+/// - `true`: checked against the vault's actual lamports minus the
+///   rent-exempt minimum, which already reflects a same-slot `deposit`
+///   that landed earlier in the same bundle even though `vault.balance`
+///   hasn't been updated from this instruction's point of view. Only safe
+///   when the caller controls instruction ordering within the bundle (e.g.
+///   a single searcher-submitted bundle) and can guarantee the deposit is
+///   irrevocably applied before this withdrawal executes — a withdrawal
+///   that races a deposit across separate, independently-landed
+///   transactions could still observe lamports that a concurrent
+///   transaction is also relying on.
+#[allow(clippy::too_many_arguments)]
+fn execute_gated_withdraw<'info>(
+    vault: &mut Account<'info, Vault>,
+    state: &mut Account<'info, AgentState>,
+    owner: &Signer<'info>,
+    instructions_sysvar: &AccountInfo<'info>,
+    mut destination_policy: Option<&mut Account<'info, DestinationMemoPolicy>>,
+    service_account: Option<AccountInfo<'info>>,
+    recipient: Option<AccountInfo<'info>>,
+    recipient_allowlist: Option<&Account<'info, RecipientAllowlist>>,
+    program_config: Option<&Account<'info, ProgramConfig>>,
+    price_feed: Option<&Account<'info, PriceFeed>>,
+    usd_spending_limit: Option<&mut Account<'info, UsdSpendingLimit>>,
+    amount: u64,
+    nonce: u64,
+    expiry: i64,
+    max_staleness_override: Option<i64>,
+    memo: [u8; 32],
+    use_actual_lamports: bool,
+    min_version: Option<u16>,
+    test_clock: Option<&UncheckedAccount<'info>>,
+    remaining_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    require_min_version(program_config.map(|c| &**c), min_version)?;
+    require!(state.owner == owner.key(), ErrorCode::Unauthorized);
+    // `vault` and `agent_state` are independent accounts (`vault` is a PDA
+    // seeded by its owner key, `agent_state` is `init`-created by its own
+    // `Keypair`, and neither is derived from the other — see
+    // `Vault::bump`/`AgentState::bump`'s doc comments), so there is no seeds
+    // constraint `#[derive(Accounts)]` could enforce to tie a specific vault
+    // to a specific agent_state. This explicit check is the full extent of
+    // that binding until a PDA-keyed migration links them structurally:
+    // without it, a caller could pair their own `agent_state` with someone
+    // else's `vault` as long as `vault.owner == owner.key()` held on its own.
+    require!(vault.owner == state.owner, ErrorCode::OwnerMismatch);
+
+    require!(!state.frozen, ErrorCode::AgentFrozen);
+    require!(state.execution_enabled, ErrorCode::ExecutionBlocked);
+    require!(state.risk_score <= state.max_risk_score, ErrorCode::HighRisk);
+
+    // A caller may voluntarily demand a tighter freshness bound than the
+    // agent's configured timeout, but never a looser one.
+    let max_staleness = match max_staleness_override {
+        Some(override_secs) => {
+            require!(
+                override_secs <= state.action_timeout_secs,
+                ErrorCode::OverrideTooLoose
+            );
+            override_secs
+        }
+        None => state.action_timeout_secs,
+    };
+
+    let clock_ts = now(test_clock)?;
+    require!(
+        clock_ts - state.last_action_timestamp < max_staleness,
+        ErrorCode::ExecutionTimeout
+    );
+
+    // A stale `risk_score` (no recent `evaluate_agent_action`/
+    // `evaluate_and_withdraw`) could be an outdated "safe" reading from a
+    // dead risk feed. `0` (the default) trusts `risk_score` regardless of
+    // age, same convention as every other optional check on `AgentState`;
+    // once set, this is a fail-closed default — a halted risk feed blocks
+    // withdrawals rather than letting them through on old data.
+    if state.max_risk_staleness_secs > 0 {
+        require!(
+            clock_ts - state.risk_updated_at < state.max_risk_staleness_secs,
+            ErrorCode::RiskScoreStale
+        );
+    }
+
+    // `min_cooldown_secs` rejects a withdrawal fired too soon after the
+    // last one — the opposite bound from the staleness check just above.
+    // `0` disables it.
+    if state.min_cooldown_secs > 0 {
+        require!(
+            clock_ts - state.last_action_timestamp >= state.min_cooldown_secs,
+            ErrorCode::CooldownNotElapsed
+        );
+    }
+
+    if state.max_velocity_lamports_per_sec > 0 {
+        let elapsed = (clock_ts - state.last_action_timestamp).max(0) as u64;
+        let allowed = state.max_velocity_lamports_per_sec.saturating_mul(elapsed);
+        require!(amount <= allowed, ErrorCode::VelocityExceeded);
+    }
+
+    // `daily_limit` bounds total outflow within a rolling 24h window,
+    // complementing the per-action amount and the continuous
+    // `max_velocity_lamports_per_sec` rate check above. `window_start`
+    // doesn't just advance by one day at a time — an agent idle for
+    // several days shouldn't need several calls to catch its window up,
+    // so this jumps straight to the start of the current window by
+    // flooring `clock_ts` to the most recent multiple of the window
+    // length since `window_start`, covering any number of elapsed
+    // windows (including zero, when nothing has elapsed) in one step.
+    // `0` disables the check entirely, same convention as `max_velocity_
+    // lamports_per_sec`/`min_eval_interval_secs`.
+    if state.daily_limit > 0 {
+        let elapsed_since_window_start = (clock_ts - state.window_start).max(0);
+        if elapsed_since_window_start >= DAILY_LIMIT_WINDOW_SECS {
+            let elapsed_windows = elapsed_since_window_start / DAILY_LIMIT_WINDOW_SECS;
+            state.window_start += elapsed_windows * DAILY_LIMIT_WINDOW_SECS;
+            state.spent_today = 0;
+        }
+        let new_spent_today = state
+            .spent_today
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(
+            new_spent_today <= state.daily_limit,
+            ErrorCode::DailyLimitExceeded
+        );
+        state.spent_today = new_spent_today;
+    }
+
+    // USD-denominated ceiling, layered on top of (not instead of)
+    // `daily_limit` above — see `set_usd_spending_limit`'s doc comment.
+    // Omitting `usd_spending_limit` (the common case) skips this entirely,
+    // same convention as `destination_policy`/`program_config`.
+    if let Some(limit) = usd_spending_limit {
+        let feed = price_feed.ok_or(ErrorCode::PriceFeedRequired)?;
+        require!(feed.key() == limit.price_feed, ErrorCode::PriceFeedMismatch);
+        require!(
+            clock_ts - feed.updated_at < limit.max_price_staleness_secs,
+            ErrorCode::StalePriceFeed
+        );
+
+        // amount (lamports) -> USD cents: amount / LAMPORTS_PER_SOL SOL,
+        // priced at feed.price_usd_cents_per_sol cents/SOL. Widened to
+        // u128 so the multiply can't overflow before the divide.
+        let amount_usd_cents = (amount as u128 * feed.price_usd_cents_per_sol as u128
+            / anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL as u128)
+            as u64;
+
+        let elapsed_since_window_start = (clock_ts - limit.window_start).max(0);
+        if elapsed_since_window_start >= DAILY_LIMIT_WINDOW_SECS {
+            let elapsed_windows = elapsed_since_window_start / DAILY_LIMIT_WINDOW_SECS;
+            limit.window_start += elapsed_windows * DAILY_LIMIT_WINDOW_SECS;
+            limit.spent_today_usd_cents = 0;
+        }
+        let new_spent_today_usd_cents = limit
+            .spent_today_usd_cents
+            .checked_add(amount_usd_cents)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(
+            new_spent_today_usd_cents <= limit.daily_limit_usd_cents,
+            ErrorCode::UsdDailyLimitExceeded
+        );
+        limit.spent_today_usd_cents = new_spent_today_usd_cents;
+    }
+
+    let available = if use_actual_lamports {
+        let vault_info = vault.to_account_info();
+        let rent_exempt_min = Rent::get()?.minimum_balance(vault_info.data_len());
+        vault_info.lamports().saturating_sub(rent_exempt_min)
+    } else {
+        vault.balance
+    };
+    require!(available >= amount, ErrorCode::InsufficientFunds);
+    require_rent_exemption_preserved(&vault.to_account_info(), amount)?;
+
+    require!(clock_ts < expiry, ErrorCode::AuthorizationExpired);
+    require!(nonce > state.last_withdrawal_nonce, ErrorCode::NonceReused);
+
+    // `recipient` lets `gated_withdraw`/`gated_transfer` pay a third party
+    // directly instead of always routing through `owner`'s own wallet.
+    // `owner` remains the signer authorizing the whole instruction (and
+    // therefore whichever `recipient` account it included), so this is
+    // gated the same way `service_account` already is: checked against
+    // `AgentState::destination_list` under `destination_list_mode` rather
+    // than trusted unconditionally. Resolved before the signature check
+    // below so the signed message binds to the actual payout destination —
+    // otherwise an owner could redirect an MXE-signed "withdraw to self"
+    // authorization to any owner-approved recipient without the signing
+    // authority ever having approved that destination.
+    let recipient_account = recipient.unwrap_or_else(|| owner.to_account_info());
+    if recipient_account.key() != owner.key() {
+        check_destination_allowed(&*state, &recipient_account.key())?;
+        // `recipient_allowlist` is a separate, vault-owner-controlled list
+        // layered on top of `AgentState::destination_list` above — see
+        // `RecipientAllowlist`'s doc comment for how this program carries
+        // both. Omitting the account (the common case, until the owner
+        // opts in by calling `add_recipient`) skips this check entirely,
+        // same convention as `destination_policy`/`program_config` above.
+        if let Some(allowlist) = recipient_allowlist {
+            let count = allowlist.count as usize;
+            require!(
+                allowlist.recipients[..count].contains(&recipient_account.key()),
+                ErrorCode::RecipientNotAllowed
+            );
+        }
+    }
+
+    let message =
+        hash_withdrawal_authorization(&vault.key(), &recipient_account.key(), amount, nonce, expiry);
+    verify_preceding_ed25519_signature(instructions_sysvar, &state.signing_authority, &message)?;
+
+    // `lifetime_sent`, unlike the window-resetting `daily_limit` check above,
+    // never resets — it accumulates across every `gated_withdraw` /
+    // `evaluate_and_withdraw` call this (vault, destination) pair has ever
+    // gone through, so `lifetime_cap` bounds a hard total rather than a
+    // rate.
+    let mut lifetime_sent_total = 0u64;
+    if let Some(policy) = destination_policy.as_deref_mut() {
+        require!(memo == policy.required_memo, ErrorCode::MemoMismatch);
+        let new_total = policy
+            .lifetime_sent
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+        if let Some(cap) = policy.lifetime_cap {
+            require!(new_total <= cap, ErrorCode::LifetimeCapExceeded);
+        }
+        policy.lifetime_sent = new_total;
+        lifetime_sent_total = new_total;
+    }
+
+    // `service_bps` splits `amount` between the owner and a configured
+    // service account instead of sending all of it to the owner — the
+    // performance-fee cut for managed-agent products. `0` (the default)
+    // keeps the whole amount with the owner and never requires
+    // `service_account` to be present at all.
+    let service_amount = if state.service_bps > 0 {
+        let service_account = service_account.ok_or(ErrorCode::ServiceAccountRequired)?;
+        require!(
+            service_account.key() == state.service_account,
+            ErrorCode::ServiceAccountMismatch
+        );
+        check_destination_allowed(&*state, &service_account.key())?;
+        let cut = (amount as u128 * state.service_bps as u128 / 10_000) as u64;
+        **service_account.try_borrow_mut_lamports()? += cut;
+        cut
+    } else {
+        0
+    };
+    let owner_amount = amount - service_amount;
+
+    **vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **recipient_account.try_borrow_mut_lamports()? += owner_amount;
+
+    // Derived from `available`, not `vault.balance` directly: when
+    // `use_actual_lamports` resolved `available` from actual lamports
+    // rather than the tracked balance, this also resyncs the tracked
+    // balance to what was actually just observed, instead of risking an
+    // underflow against a tracked balance this withdrawal deliberately
+    // looked past.
+    vault.balance = available.checked_sub(amount).ok_or(ErrorCode::Underflow)?;
+
+    state.last_action_timestamp = clock_ts;
+    state.last_withdrawal_nonce = nonce;
+    state.record_action(ActionHistoryEntry {
+        timestamp: clock_ts,
+        amount,
+        risk_score: state.risk_score,
+        action_kind: ACTION_KIND_GATED_WITHDRAW,
+    });
+
+    emit!(GatedWithdrawnEvent {
+        vault: vault.key(),
+        owner: owner.key(),
+        recipient: recipient_account.key(),
+        amount,
+        lifetime_sent: lifetime_sent_total,
+        owner_amount,
+        service_amount,
+        risk_score: state.risk_score,
+        timestamp: clock_ts,
+    });
+
+    invoke_vault_hook(vault, amount, HOOK_DIRECTION_WITHDRAW, remaining_accounts)?;
+
+    Ok(())
+}
+
+/// Verifies that the Ed25519 signature-verification instruction directly
+/// preceding the current instruction in this transaction attests to
+/// `expected_signer` signing `expected_message`.
+fn verify_preceding_ed25519_signature(
+    instructions_sysvar: &AccountInfo,
+    expected_signer: &Pubkey,
+    expected_message: &[u8; 32],
+) -> Result<()> {
+    let current_index = solana_program::sysvar::instructions::load_current_index_checked(
+        instructions_sysvar,
+    )?;
+    require!(current_index > 0, ErrorCode::MissingSignatureInstruction);
+
+    let ix = load_instruction_at_checked(
+        (current_index - 1) as usize,
+        instructions_sysvar,
+    )?;
+
+    require!(
+        ix.program_id == ed25519_program::ID,
+        ErrorCode::MissingSignatureInstruction
+    );
+
+    // Layout of an Ed25519SigVerify instruction with a single signature,
+    // following the standard `Ed25519Program.createInstructionWithPublicKey`
+    // format: a 16-byte header followed by pubkey (32), signature (64) and
+    // message bytes at the offsets the header encodes.
+    let data = &ix.data;
+    require!(data.len() >= 16 + 32 + 64 + 32, ErrorCode::InvalidAuthorizationSignature);
+
+    let pubkey_offset = u16::from_le_bytes([data[6], data[7]]) as usize;
+    let message_offset = u16::from_le_bytes([data[10], data[11]]) as usize;
+    let message_size = u16::from_le_bytes([data[12], data[13]]) as usize;
+
+    let signer_bytes = data
+        .get(pubkey_offset..pubkey_offset + 32)
+        .ok_or(ErrorCode::InvalidAuthorizationSignature)?;
+    require!(
+        signer_bytes == expected_signer.as_ref(),
+        ErrorCode::InvalidAuthorizationSignature
+    );
+
+    let message_bytes = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(ErrorCode::InvalidAuthorizationSignature)?;
+    require!(
+        message_bytes == expected_message,
+        ErrorCode::InvalidAuthorizationSignature
+    );
+
+    Ok(())
+}
+
 #[program]
 pub mod agent_vault {
     use super::*;
 
+    // =========================
+    // PROGRAM CONFIG / VERSIONING
+    // =========================
+    //
+    // See `ProgramConfig`'s doc comment. `initialize_program_config` is
+    // meant to run once, right after deployment; every later instruction
+    // that wants a `min_version` check just reads the resulting PDA.
+    pub fn initialize_program_config(ctx: Context<InitializeProgramConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.program_config;
+        config.version = INITIAL_PROGRAM_VERSION;
+        config.upgrade_authority = ctx.accounts.payer.key();
+        config.bump = ctx.bumps.program_config;
+        Ok(())
+    }
+
+    pub fn bump_version(ctx: Context<BumpVersion>, new_version: u16) -> Result<()> {
+        require!(
+            ctx.accounts.program_config.upgrade_authority == ctx.accounts.upgrade_authority.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            new_version > ctx.accounts.program_config.version,
+            ErrorCode::VersionNotIncreasing
+        );
+
+        let old_version = ctx.accounts.program_config.version;
+        ctx.accounts.program_config.version = new_version;
+
+        emit!(VersionBumpedEvent {
+            old_version,
+            new_version,
+        });
+        Ok(())
+    }
+
     // =========================
     // VAULT INITIALIZATION
     // =========================
     pub fn initialize_vault(ctx: Context<InitializeVault>) -> Result<()> {
+        let bump = ctx.bumps.vault;
         let vault = &mut ctx.accounts.vault;
         vault.owner = ctx.accounts.owner.key();
+        vault.owner_kind = OwnerKind::Direct;
         vault.balance = 0;
+        vault.bump = bump;
+        vault.auto_reconcile = false;
+        vault.hook_allowlist = [Pubkey::default(); MAX_HOOK_ALLOWLIST];
+        vault.hook_allowlist_count = 0;
+        vault.verifier_program = Pubkey::default();
+        vault.pending_owner = None;
+        vault.guardian = None;
+        vault.recovery_delay_secs = 0;
+        vault.recovery_initiated_at = 0;
+        vault.pending_recovery_owner = None;
         Ok(())
     }
 
     // =========================
-    // DEPOSIT SOL
+    // AUTO-RECONCILE TOGGLE
+    // =========================
+    //
+    // Lets the owner opt a vault into `deposit` resyncing `balance` from
+    // actual lamports on every call (see `apply_deposit_balance`'s doc comment), for
+    // vaults that receive funds through channels besides `deposit` itself.
+    pub fn set_vault_auto_reconcile(ctx: Context<UpdateVaultAutoReconcile>, auto_reconcile: bool) -> Result<()> {
+        require!(
+            ctx.accounts.vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.vault.auto_reconcile = auto_reconcile;
+        Ok(())
+    }
+
+    // =========================
+    // VAULT OWNERSHIP MODE
+    // =========================
+    //
+    // Lets the current owner switch `Vault.owner` between a plain signer
+    // key and a PDA controlled by another program (e.g. a Squads-style
+    // multisig), without changing `owner` itself in the same instruction —
+    // callers that want both should call `set_vault_owner_kind` from
+    // whichever key/PDA currently authorizes as owner, then separately
+    // transfer `owner` under the new mode's rules.
+    pub fn set_vault_owner_kind(ctx: Context<UpdateVaultOwnerKind>, owner_kind: OwnerKind) -> Result<()> {
+        require!(
+            ctx.accounts.vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.vault.owner_kind = owner_kind;
+        Ok(())
+    }
+
+    // =========================
+    // VAULT OWNERSHIP TRANSFER (TWO-STEP)
     // =========================
-    pub fn deposit(ctx: Context<Deposit>, amount: u64) -> Result<()> {
-        let vault_owner = ctx.accounts.vault.owner;
+    //
+    // `set_vault_owner_kind`'s doc comment above calls this out as the
+    // "separately transfer owner" step. Two-step rather than a direct
+    // `set_owner(new_owner: Pubkey)` so a typo'd or unreachable
+    // `new_owner` can't strand the vault without any key able to act as
+    // `owner` — the transfer sits pending until whoever holds
+    // `new_owner`'s key shows up to `accept_owner` it, and `owner` can
+    // `cancel_owner_transfer` in the meantime.
+    pub fn propose_owner(ctx: Context<ProposeOwner>, new_owner: Pubkey) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(vault.owner == ctx.accounts.owner.key(), ErrorCode::Unauthorized);
+        vault.pending_owner = Some(new_owner);
+        Ok(())
+    }
+
+    pub fn cancel_owner_transfer(ctx: Context<CancelOwnerTransfer>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(vault.owner == ctx.accounts.owner.key(), ErrorCode::Unauthorized);
+        vault.pending_owner = None;
+        Ok(())
+    }
+
+    pub fn accept_owner(ctx: Context<AcceptOwner>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            vault.pending_owner == Some(ctx.accounts.new_owner.key()),
+            ErrorCode::Unauthorized
+        );
+
+        let old_owner = vault.owner;
+        vault.owner = ctx.accounts.new_owner.key();
+        vault.pending_owner = None;
+
+        emit!(VaultOwnershipTransferredEvent {
+            vault: vault.key(),
+            old_owner,
+            new_owner: vault.owner,
+        });
+
+        Ok(())
+    }
+
+    // =========================
+    // GUARDIAN RECOVERY
+    // =========================
+    //
+    // A second, guardian-driven path to the same `owner` field
+    // `propose_owner`/`accept_owner` rotate, for the case that pair can't
+    // cover: the current `owner` key is lost outright, so there's no one
+    // left to sign `propose_owner` in the first place. `guardian` being
+    // unset (the default) disables all three instructions below entirely —
+    // opting in is a deliberate, separate step via `set_guardian`, not a
+    // standing capability every vault carries.
+    pub fn set_guardian(
+        ctx: Context<SetGuardian>,
+        guardian: Option<Pubkey>,
+        recovery_delay_secs: i64,
+    ) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(vault.owner == ctx.accounts.owner.key(), ErrorCode::Unauthorized);
+
+        if guardian.is_some() {
+            require!(
+                recovery_delay_secs >= MIN_RECOVERY_DELAY_SECS,
+                ErrorCode::RecoveryDelayTooShort
+            );
+        }
+
+        vault.guardian = guardian;
+        vault.recovery_delay_secs = recovery_delay_secs;
+        // Changing (or clearing) the guardian invalidates whatever
+        // recovery was in flight under the old configuration.
+        vault.recovery_initiated_at = 0;
+        vault.pending_recovery_owner = None;
+
+        Ok(())
+    }
+
+    /// Starts the clock on a guardian-driven recovery. `recover` can't
+    /// actually rotate `owner` until `recovery_delay_secs` has elapsed
+    /// since this call — see that instruction's doc comment — which gives
+    /// `owner`, if the key isn't really lost, a window to notice and
+    /// `cancel_recovery`.
+    pub fn initiate_recovery(ctx: Context<InitiateRecovery>, new_owner: Pubkey) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            vault.guardian == Some(ctx.accounts.guardian.key()),
+            ErrorCode::Unauthorized
+        );
+
+        let clock_ts = now(ctx.accounts.test_clock.as_ref())?;
+        vault.recovery_initiated_at = clock_ts;
+        vault.pending_recovery_owner = Some(new_owner);
+
+        emit!(RecoveryInitiatedEvent {
+            vault: vault.key(),
+            guardian: ctx.accounts.guardian.key(),
+            new_owner,
+            recovery_initiated_at: clock_ts,
+        });
+
+        Ok(())
+    }
+
+    /// `owner`'s veto over an in-flight `initiate_recovery` — proof the key
+    /// wasn't actually lost after all. Unconditional like
+    /// `cancel_owner_transfer`: calling this with no recovery in flight is
+    /// just a no-op, not an error.
+    pub fn cancel_recovery(ctx: Context<CancelRecovery>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(vault.owner == ctx.accounts.owner.key(), ErrorCode::Unauthorized);
+
+        vault.recovery_initiated_at = 0;
+        vault.pending_recovery_owner = None;
+
+        emit!(RecoveryCancelledEvent { vault: vault.key() });
+
+        Ok(())
+    }
+
+    /// Finalizes a recovery `initiate_recovery` started at least
+    /// `recovery_delay_secs` ago. `new_owner` must match the target
+    /// `initiate_recovery` recorded, so a guardian can't initiate toward
+    /// one key and land the rotation on a different one once the delay is
+    /// up.
+    pub fn recover(ctx: Context<Recover>, new_owner: Pubkey) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+        require!(
+            vault.guardian == Some(ctx.accounts.guardian.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(vault.recovery_initiated_at != 0, ErrorCode::RecoveryNotInitiated);
         require!(
-            vault_owner == ctx.accounts.owner.key(),
+            vault.pending_recovery_owner == Some(new_owner),
             ErrorCode::Unauthorized
         );
 
+        let clock_ts = now(ctx.accounts.test_clock.as_ref())?;
+        require!(
+            clock_ts - vault.recovery_initiated_at >= vault.recovery_delay_secs,
+            ErrorCode::RecoveryDelayNotElapsed
+        );
+
+        let old_owner = vault.owner;
+        vault.owner = new_owner;
+        vault.recovery_initiated_at = 0;
+        vault.pending_recovery_owner = None;
+
+        emit!(RecoveryCompletedEvent {
+            vault: vault.key(),
+            old_owner,
+            new_owner,
+        });
+
+        Ok(())
+    }
+
+    // =========================
+    // DEPOSIT SOL
+    // =========================
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        amount: u64,
+        idempotency_key: [u8; 16],
+    ) -> Result<()> {
+        deposit_lamports(ctx, amount, idempotency_key)
+    }
+
+    // =========================
+    // DEPOSIT SOL (MICRO-SOL UNIT)
+    // =========================
+    //
+    // Thin unit-conversion wrapper around `deposit`: `amount_micro_sol` is
+    // converted to lamports (see `LAMPORTS_PER_MICRO_SOL`) with an explicit
+    // overflow check, then runs the exact same deposit logic as the
+    // lamport-native instruction above. `deposit` remains the canonical
+    // low-level API; this exists only so clients that think in
+    // SOL-denominated amounts stop hand-rolling (and occasionally getting
+    // wrong) the lamport conversion themselves.
+    pub fn deposit_micro_sol(
+        ctx: Context<Deposit>,
+        amount_micro_sol: u64,
+        idempotency_key: [u8; 16],
+    ) -> Result<()> {
+        let amount = micro_sol_to_lamports(amount_micro_sol)?;
+        deposit_lamports(ctx, amount, idempotency_key)
+    }
+
+    // =========================
+    // DEPOSIT WITH AUTO-CREATE
+    // =========================
+    //
+    // `deposit` above requires a `Vault` that already exists (created by a
+    // prior `initialize_vault`). This variant derives the same owner-seeded
+    // PDA and creates it on first use, collapsing "create and fund" into one instruction. Only a
+    // self-deposit (`depositor == owner`) may perform that creation — a
+    // third party depositing to an `owner` PDA that doesn't exist yet is
+    // rejected rather than silently becoming that vault's owner. Once the
+    // PDA exists, later deposits follow the same owner check as `deposit`.
+    pub fn deposit_with_auto_create(
+        ctx: Context<DepositWithAutoCreate>,
+        owner: Pubkey,
+        amount: u64,
+        idempotency_key: [u8; 16],
+    ) -> Result<()> {
+        let vault_key = ctx.accounts.vault.key();
+        let vault = &mut ctx.accounts.vault;
+        if vault.owner == Pubkey::default() {
+            require!(
+                ctx.accounts.depositor.key() == owner,
+                ErrorCode::Unauthorized
+            );
+            vault.owner = owner;
+            vault.owner_kind = OwnerKind::Direct;
+            vault.balance = 0;
+            vault.bump = ctx.bumps.vault;
+            vault.auto_reconcile = false;
+            vault.hook_allowlist = [Pubkey::default(); MAX_HOOK_ALLOWLIST];
+            vault.hook_allowlist_count = 0;
+            vault.verifier_program = Pubkey::default();
+            vault.pending_owner = None;
+            vault.guardian = None;
+            vault.recovery_delay_secs = 0;
+            vault.recovery_initiated_at = 0;
+            vault.pending_recovery_owner = None;
+            emit!(VaultCreatedEvent {
+                vault: vault_key,
+                owner,
+            });
+        } else {
+            require!(vault.owner == owner, ErrorCode::Unauthorized);
+        }
+
+        let clock_ts = now(ctx.accounts.test_clock.as_ref())?;
+        let log = &mut ctx.accounts.deposit_log;
+        if log.vault == Pubkey::default() {
+            log.vault = vault_key;
+        }
+        log.reject_if_duplicate(&idempotency_key, clock_ts)?;
+
         let transfer_ix = solana_program::system_instruction::transfer(
-            &ctx.accounts.owner.key(),
-            &ctx.accounts.vault.key(),
+            &ctx.accounts.depositor.key(),
+            &vault_key,
             amount,
         );
-
         solana_program::program::invoke(
             &transfer_ix,
             &[
-                ctx.accounts.owner.to_account_info(),
+                ctx.accounts.depositor.to_account_info(),
                 ctx.accounts.vault.to_account_info(),
             ],
         )?;
 
         let vault = &mut ctx.accounts.vault;
-        vault.balance = vault
-            .balance
-            .checked_add(amount)
-            .ok_or(ErrorCode::Overflow)?;
+        let reconciled_delta = apply_deposit_balance(vault, amount)?;
+
+        log.record(&idempotency_key, clock_ts);
+        emit!(DepositedEvent {
+            vault: vault_key,
+            owner,
+            amount,
+            reconciled_delta,
+            new_balance: vault.balance,
+        });
+
+        invoke_vault_hook(vault, amount, HOOK_DIRECTION_DEPOSIT, ctx.remaining_accounts)?;
 
         Ok(())
     }
@@ -54,169 +1136,2866 @@ pub mod agent_vault {
     // NORMAL WITHDRAW
     // =========================
     pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
-        let vault = &mut ctx.accounts.vault;
+        withdraw_lamports(ctx, amount)
+    }
 
-        require!(
-            vault.owner == ctx.accounts.owner.key(),
-            ErrorCode::Unauthorized
-        );
+    // =========================
+    // NORMAL WITHDRAW (MICRO-SOL UNIT)
+    // =========================
+    //
+    // Same unit-conversion relationship to `withdraw` as
+    // `deposit_micro_sol` has to `deposit` — see that instruction's doc
+    // comment. `withdraw` remains the canonical lamport-native API.
+    pub fn withdraw_micro_sol(ctx: Context<Withdraw>, amount_micro_sol: u64) -> Result<()> {
+        let amount = micro_sol_to_lamports(amount_micro_sol)?;
+        withdraw_lamports(ctx, amount)
+    }
 
+    // =========================
+    // SPL TOKEN DEPOSIT / WITHDRAW
+    // =========================
+    //
+    // Mirrors `deposit`/`withdraw` for SPL tokens instead of native SOL.
+    // Custody lives in `vault_token_account`, an associated token account
+    // owned by the `Vault` PDA itself (so `withdraw_spl`'s outbound
+    // transfer can be authorized with `invoke_signed` over that PDA's own
+    // seeds, the same way a future SOL-transfer-via-CPI would per
+    // `Vault::bump`'s doc comment) — `TokenVault` alongside it is the
+    // tracked-balance record for one `(vault, mint)` pair, exactly the
+    // same "explicit accounting field, not just trusting the token
+    // account's own balance" design `Vault::balance` already uses for
+    // lamports (see `apply_deposit_balance`'s `auto_reconcile` doc
+    // comment for why that distinction matters there too).
+    pub fn deposit_spl(ctx: Context<DepositSpl>, amount: u64) -> Result<()> {
         require!(
-            vault.balance >= amount,
-            ErrorCode::InsufficientFunds
+            ctx.accounts.vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
         );
 
-        **vault.to_account_info().try_borrow_mut_lamports()? -= amount;
-        **ctx.accounts.owner.to_account_info().try_borrow_mut_lamports()? += amount;
+        let token_vault = &mut ctx.accounts.token_vault;
+        if token_vault.vault == Pubkey::default() {
+            token_vault.vault = ctx.accounts.vault.key();
+            token_vault.mint = ctx.accounts.mint.key();
+            token_vault.balance = 0;
+            token_vault.bump = ctx.bumps.token_vault;
+        }
+        token_vault.balance = token_vault
+            .balance
+            .checked_add(amount)
+            .ok_or(ErrorCode::Overflow)?;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.owner_token_account.to_account_info(),
+                    to: ctx.accounts.vault_token_account.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        emit!(TokenDepositedEvent {
+            vault: ctx.accounts.vault.key(),
+            mint: ctx.accounts.mint.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    pub fn withdraw_spl(ctx: Context<WithdrawSpl>, amount: u64) -> Result<()> {
+        require!(
+            ctx.accounts.vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+
+        let token_vault = &mut ctx.accounts.token_vault;
+        require!(token_vault.balance >= amount, ErrorCode::InsufficientFunds);
+        token_vault.balance = token_vault
+            .balance
+            .checked_sub(amount)
+            .ok_or(ErrorCode::Underflow)?;
+
+        let owner_key = ctx.accounts.vault.owner;
+        let vault_bump = ctx.accounts.vault.bump;
+        let vault_seeds: &[&[u8]] = &[b"vault", owner_key.as_ref(), &[vault_bump]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault_token_account.to_account_info(),
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    authority: ctx.accounts.vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            amount,
+        )?;
+
+        emit!(TokenWithdrawnEvent {
+            vault: ctx.accounts.vault.key(),
+            mint: ctx.accounts.mint.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // =========================
+    // EMERGENCY SWEEP
+    // =========================
+    //
+    // `withdraw`/`withdraw_micro_sol` above are already unaffected by
+    // `AgentState::frozen` — that flag only ever gates `gated_withdraw`
+    // and its variants (see `execute_gated_withdraw`'s `require!` on it),
+    // never the owner's own `Withdraw` accounts. This instruction exists
+    // anyway, as the dedicated "get everything out right now" escape
+    // hatch the request actually asked for: it never touches `agent_state`
+    // at all (so there's no agent-signed path into it, frozen or not),
+    // sweeps the vault's *entire* `balance` in one call rather than a
+    // caller-chosen `amount`, and reports a distinct `EmergencySweptEvent`
+    // so off-chain monitoring can tell a full sweep apart from an ordinary
+    // partial withdrawal.
+    pub fn emergency_sweep(ctx: Context<EmergencySweep>) -> Result<()> {
+        let vault = &mut ctx.accounts.vault;
+
+        require!(
+            vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+
+        let amount = vault.balance;
+
+        **vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx
+            .accounts
+            .destination
+            .to_account_info()
+            .try_borrow_mut_lamports()? += amount;
+
+        vault.balance = 0;
+
+        emit!(EmergencySweptEvent {
+            vault: vault.key(),
+            owner: vault.owner,
+            destination: ctx.accounts.destination.key(),
+            amount,
+        });
+
+        Ok(())
+    }
+
+    // =========================
+    // CLOSE VAULT
+    // =========================
+    //
+    // Reclaims `vault`'s rent once it's fully drained — there was
+    // previously no way to recover that ~0.002 SOL. `agent_state` is
+    // optional (not every vault has one) but, when supplied, must be the
+    // vault's own linked agent (`agent_state.owner == vault.owner`, the
+    // same consistency check `get_vault_summary` and `can_act` make) so a
+    // caller can't use an unrelated `AgentState` to dodge the
+    // still-executing check below.
+    pub fn close_vault(ctx: Context<CloseVault>) -> Result<()> {
+        let vault = &ctx.accounts.vault;
+
+        require!(
+            vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(vault.balance == 0, ErrorCode::VaultNotEmpty);
+
+        if let Some(agent_state) = &ctx.accounts.agent_state {
+            require!(
+                agent_state.owner == vault.owner,
+                ErrorCode::OwnerMismatch
+            );
+            require!(
+                !agent_state.execution_enabled,
+                ErrorCode::AgentStillExecuting
+            );
+        }
+
+        Ok(())
+    }
+
+    // =========================
+    // INITIALIZE AGENT STATE
+    // =========================
+    pub fn initialize_agent(
+        ctx: Context<InitializeAgent>,
+        signing_authority: Pubkey,
+        action_timeout_secs: i64,
+    ) -> Result<()> {
+        require!(action_timeout_secs > 0, ErrorCode::InvalidActionTimeout);
+
+        let clock_ts = now(ctx.accounts.test_clock.as_ref())?;
+        let state = &mut ctx.accounts.agent_state;
+
+        state.owner = ctx.accounts.owner.key();
+        state.risk_score = 50;
+        state.execution_enabled = true;
+        state.last_action_timestamp = clock_ts;
+        state.signing_authority = signing_authority;
+        state.last_withdrawal_nonce = 0;
+        state.action_timeout_secs = action_timeout_secs;
+        state.frozen = false;
+        state.bump = 0;
+        state.min_eval_interval_secs = 0;
+        state.last_eval_timestamp = 0;
+        state.max_velocity_lamports_per_sec = 0;
+        state.agent_id = 0;
+        state.agent_id_registered = false;
+        state.service_account = Pubkey::default();
+        state.service_bps = 0;
+        state.destination_list_mode = DestinationListMode::Denylist;
+        state.destination_list = [Pubkey::default(); MAX_DESTINATION_LIST];
+        state.destination_list_count = 0;
+        state.daily_limit = 0;
+        state.spent_today = 0;
+        state.window_start = clock_ts;
+        state.max_risk_score = DEFAULT_MAX_RISK_SCORE;
+        state.action_history = [ActionHistoryEntry::default(); ACTION_HISTORY_CAPACITY];
+        state.action_history_len = 0;
+        state.action_history_next_index = 0;
+        state.high_risk_streak = 0;
+        state.freeze_after = 0;
+        state.min_cooldown_secs = 0;
+        state.max_risk_staleness_secs = 0;
+        state.risk_updated_at = clock_ts;
+
+        Ok(())
+    }
+
+    // =========================
+    // AGENT STATE MIGRATION (action_history, risk staleness)
+    // =========================
+    //
+    // `action_history` grew `AgentState` past `AGENT_STATE_SPACE_V1`, and
+    // `max_risk_staleness_secs`/`risk_updated_at` later grew it again past
+    // `AGENT_STATE_SPACE_V2`. Every account created before one of those
+    // migrations was `init`-ed at the size current at the time, but
+    // Anchor's typed `Account<AgentState>` deserialization expects the
+    // account to already be `AGENT_STATE_SPACE` bytes, so a pre-migration
+    // account can't be passed into any instruction that takes
+    // `agent_state: Account<'info, AgentState>` at all — including this
+    // one, which is why `MigrateAgentState` takes it as a raw
+    // `UncheckedAccount` and reads/reallocs the buffer by hand instead.
+    // Callable any number of times: a no-op once the account is already
+    // `AGENT_STATE_SPACE`, and handles accounts stuck at either older size.
+    pub fn migrate_agent_state(ctx: Context<MigrateAgentState>) -> Result<()> {
+        let account_info = ctx.accounts.agent_state.to_account_info();
+        let current_len = account_info.data_len();
+        if current_len == AGENT_STATE_SPACE {
+            return Ok(());
+        }
+        require!(
+            current_len == AGENT_STATE_SPACE_V1 || current_len == AGENT_STATE_SPACE_V2,
+            ErrorCode::UnexpectedAgentStateSize
+        );
+
+        {
+            let data = account_info.try_borrow_data()?;
+            let mut owner_bytes = [0u8; 32];
+            owner_bytes.copy_from_slice(&data[8..40]);
+            require!(Pubkey::from(owner_bytes) == ctx.accounts.owner.key(), ErrorCode::Unauthorized);
+        }
+
+        let rent = Rent::get()?;
+        let new_minimum_balance = rent.minimum_balance(AGENT_STATE_SPACE);
+        let lamports_diff = new_minimum_balance.saturating_sub(account_info.lamports());
+        if lamports_diff > 0 {
+            solana_program::program::invoke(
+                &solana_program::system_instruction::transfer(
+                    &ctx.accounts.owner.key(),
+                    &account_info.key(),
+                    lamports_diff,
+                ),
+                &[
+                    ctx.accounts.owner.to_account_info(),
+                    account_info.clone(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+
+        account_info.realloc(AGENT_STATE_SPACE, false)?;
+        // The bytes `realloc` just added are uninitialized, not
+        // zero-filled — `action_history_len`/`action_history_next_index`
+        // in particular must start at `0`, not garbage, or `record_action`
+        // would index past `ACTION_HISTORY_CAPACITY`. Zero-filling from
+        // `current_len` (rather than a hardcoded size) covers accounts
+        // coming from either older layout.
+        let mut data = account_info.try_borrow_mut_data()?;
+        for byte in data[current_len..].iter_mut() {
+            *byte = 0;
+        }
+
+        Ok(())
+    }
+
+    // =========================
+    // SERVICE FEE SPLIT
+    // =========================
+    //
+    // Lets the owner route a configured cut of every `gated_withdraw`/
+    // `evaluate_and_withdraw`/`gated_withdraw_verified` to a service
+    // account instead of the owner receiving the full `amount` — the
+    // revenue mechanism for managed-agent products that take a
+    // performance fee on the agent's successful actions.
+    pub fn set_service_split(
+        ctx: Context<UpdateAgent>,
+        service_account: Pubkey,
+        service_bps: u16,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.agent_state.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(service_bps <= 10_000, ErrorCode::InvalidServiceBps);
+
+        let state = &mut ctx.accounts.agent_state;
+        state.service_account = service_account;
+        state.service_bps = service_bps;
+
+        Ok(())
+    }
+
+    // =========================
+    // DESTINATION ALLOW/DENY LIST
+    // =========================
+    //
+    // Gates `service_account` and any non-owner `recipient` (see `AgentState::destination_list`'s doc
+    // comment for why `owner` itself is out of scope) against an
+    // owner-managed list, under either denylist or allowlist semantics.
+    // See [`DestinationListMode`].
+    pub fn set_destination_list_mode(
+        ctx: Context<UpdateAgent>,
+        mode: DestinationListMode,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.agent_state.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.agent_state.destination_list_mode = mode;
+        Ok(())
+    }
+
+    pub fn add_denied_destination(ctx: Context<UpdateAgent>, destination: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.agent_state;
+        require!(state.owner == ctx.accounts.owner.key(), ErrorCode::Unauthorized);
+
+        let count = state.destination_list_count as usize;
+        require!(
+            !state.destination_list[..count].contains(&destination),
+            ErrorCode::DuplicateDestinationListEntry
+        );
+        require!(count < MAX_DESTINATION_LIST, ErrorCode::TooManyDestinationListEntries);
+
+        state.destination_list[count] = destination;
+        state.destination_list_count += 1;
+        Ok(())
+    }
+
+    pub fn remove_denied_destination(ctx: Context<UpdateAgent>, destination: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.agent_state;
+        require!(state.owner == ctx.accounts.owner.key(), ErrorCode::Unauthorized);
+
+        let count = state.destination_list_count as usize;
+        let index = state.destination_list[..count]
+            .iter()
+            .position(|entry| *entry == destination)
+            .ok_or(ErrorCode::DestinationNotInList)?;
+
+        let last = count - 1;
+        state.destination_list[index] = state.destination_list[last];
+        state.destination_list[last] = Pubkey::default();
+        state.destination_list_count -= 1;
+        Ok(())
+    }
+
+    // =========================
+    // UPDATE RISK SCORE
+    // =========================
+    pub fn evaluate_agent_action(
+        ctx: Context<UpdateAgent>,
+        risk_score: u8,
+    ) -> Result<()> {
+        apply_risk_score(
+            &mut ctx.accounts.agent_state,
+            &ctx.accounts.owner.key(),
+            risk_score,
+            ctx.accounts.test_clock.as_ref(),
+        )?;
+
+        let timestamp = now(ctx.accounts.test_clock.as_ref())?;
+        ctx.accounts.agent_state.record_action(ActionHistoryEntry {
+            timestamp,
+            amount: 0,
+            risk_score,
+            action_kind: ACTION_KIND_EVALUATE,
+        });
+        Ok(())
+    }
+
+    // =========================
+    // EVALUATION RATE LIMIT
+    // =========================
+    //
+    // Lets the owner bound how often `evaluate_agent_action`/
+    // `evaluate_and_withdraw` may actually update the risk score, so a
+    // misbehaving cranker can't flap `execution_enabled` or spam events.
+    // Zero disables the limit.
+    pub fn set_min_eval_interval(ctx: Context<UpdateAgent>, min_eval_interval_secs: i64) -> Result<()> {
+        require!(
+            ctx.accounts.agent_state.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(min_eval_interval_secs >= 0, ErrorCode::InvalidEvalInterval);
+        ctx.accounts.agent_state.min_eval_interval_secs = min_eval_interval_secs;
+        Ok(())
+    }
+
+    // =========================
+    // WITHDRAWAL COOLDOWN
+    // =========================
+    //
+    // Lets the owner bound how soon one `gated_withdraw` may follow the
+    // last, independent of `action_timeout_secs` (which bounds the
+    // opposite: how stale `last_action_timestamp` may get). Zero disables
+    // the cooldown.
+    pub fn set_min_cooldown(ctx: Context<UpdateAgent>, min_cooldown_secs: i64) -> Result<()> {
+        require!(
+            ctx.accounts.agent_state.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(min_cooldown_secs >= 0, ErrorCode::InvalidCooldown);
+        ctx.accounts.agent_state.min_cooldown_secs = min_cooldown_secs;
+        Ok(())
+    }
+
+    // =========================
+    // RISK SCORE STALENESS
+    // =========================
+    //
+    // Lets the owner demand a fresh `risk_score`: once `risk_updated_at`
+    // falls more than this many seconds behind `clock_ts`,
+    // `execute_gated_withdraw` rejects with `RiskScoreStale` instead of
+    // trusting a possibly-outdated reading. Zero (the default) disables
+    // the check.
+    pub fn set_max_risk_staleness(
+        ctx: Context<UpdateAgent>,
+        max_risk_staleness_secs: i64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.agent_state.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(max_risk_staleness_secs >= 0, ErrorCode::InvalidRiskStaleness);
+        ctx.accounts.agent_state.max_risk_staleness_secs = max_risk_staleness_secs;
+        Ok(())
+    }
+
+    // =========================
+    // AGENT ID REGISTRY
+    // =========================
+    //
+    // Assigns each `AgentState` a collision-free `agent_id` from a single
+    // program-wide counter, so the confidential circuits and any other
+    // agent-id-keyed feature have a deterministic identity scheme to rely
+    // on instead of each caller picking its own (possibly colliding) id.
+    //
+    // `evaluate_agent_action`/`gated_withdraw` and the MXE signing flows
+    // don't reference `AgentState::agent_id` yet — they identify an agent
+    // entirely through the `AgentState` account itself (as `Unauthorized`
+    // checks throughout this file already do), and `circuits::SigningRequest`
+    // takes its own caller-supplied `agent_id` with no on-chain account to
+    // cross-check against. Wiring those call sites to require
+    // `agent_id_registered` and thread the assigned id through is later,
+    // larger-scoped work than standing up the registry itself.
+    pub fn register_agent(ctx: Context<RegisterAgent>) -> Result<()> {
+        require!(
+            ctx.accounts.agent_state.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            !ctx.accounts.agent_state.agent_id_registered,
+            ErrorCode::DuplicateAgentRegistration
+        );
+
+        let registry = &mut ctx.accounts.registry;
+        let agent_id = registry.next_agent_id;
+        registry.next_agent_id = registry
+            .next_agent_id
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let state = &mut ctx.accounts.agent_state;
+        state.agent_id = agent_id;
+        state.agent_id_registered = true;
+
+        emit!(AgentRegisteredEvent {
+            agent_state: state.key(),
+            agent_id,
+        });
+
+        Ok(())
+    }
+
+    // =========================
+    // ANTI-DRAIN VELOCITY LIMIT
+    // =========================
+    //
+    // Lets the owner bound the outflow rate `gated_withdraw`/
+    // `evaluate_and_withdraw` will honor, beyond the fixed per-action
+    // amount they already gate on. Zero disables the check.
+    pub fn set_max_velocity(ctx: Context<UpdateAgent>, max_velocity_lamports_per_sec: u64) -> Result<()> {
+        require!(
+            ctx.accounts.agent_state.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.agent_state.max_velocity_lamports_per_sec = max_velocity_lamports_per_sec;
+        Ok(())
+    }
+
+    // =========================
+    // DAILY SPENDING LIMIT
+    // =========================
+    //
+    // Lets the owner bound total `gated_withdraw`/`evaluate_and_withdraw`
+    // outflow within a rolling `DAILY_LIMIT_WINDOW_SECS` window, so a
+    // low-risk-scored agent still can't drain the vault in one run. `0`
+    // disables the check. Doesn't touch `spent_today`/`window_start`
+    // directly — lowering `limit` below the current window's
+    // `spent_today` simply makes the very next `gated_withdraw` reject
+    // until the window rolls over, rather than retroactively failing
+    // anything already sent.
+    pub fn set_daily_limit(ctx: Context<UpdateAgent>, daily_limit: u64) -> Result<()> {
+        require!(
+            ctx.accounts.agent_state.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.agent_state.daily_limit = daily_limit;
+        Ok(())
+    }
+
+    // =========================
+    // RISK/TIMEOUT POLICY CONFIGURATION
+    // =========================
+    //
+    // Lets the owner tune the two thresholds `apply_risk_score`/
+    // `execute_gated_withdraw`/`can_act` enforce — `max_risk_score` (how
+    // high `risk_score` may climb before `execution_enabled` flips false
+    // and gated withdrawals are rejected with `ExecutionBlocked`) and
+    // `action_timeout_secs` (how stale `last_action_timestamp` may get
+    // before `ExecutionTimeout`) — without recompiling. Both were fixed
+    // at `initialize_agent` time before this instruction existed
+    // (`max_risk_score` was actually hardcoded to `DEFAULT_MAX_RISK_SCORE`
+    // everywhere it was checked; `action_timeout_secs` was already a
+    // caller-chosen `initialize_agent` parameter, just with no way to
+    // change it afterward). Each parameter is independently optional —
+    // `None` leaves that field unchanged — so a caller can tighten one
+    // without having to already know or resend the other's current value.
+    pub fn configure_policy(
+        ctx: Context<UpdateAgent>,
+        max_risk_score: Option<u8>,
+        action_timeout_secs: Option<i64>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.agent_state.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+
+        let state = &mut ctx.accounts.agent_state;
+        if let Some(max_risk_score) = max_risk_score {
+            require!(max_risk_score <= 100, ErrorCode::InvalidRiskScore);
+            state.max_risk_score = max_risk_score;
+            // Re-derive the gate against the new ceiling right away, the
+            // same way `apply_risk_score` does for a new reading.
+            state.execution_enabled = state.risk_score <= max_risk_score;
+        }
+        if let Some(action_timeout_secs) = action_timeout_secs {
+            require!(action_timeout_secs > 0, ErrorCode::InvalidActionTimeout);
+            state.action_timeout_secs = action_timeout_secs;
+        }
+
+        Ok(())
+    }
+
+    // =========================
+    // BALANCE CONSISTENCY CHECK (test/ops utility)
+    // =========================
+    //
+    // Fails loudly if `vault.balance` has desynced from the vault's actual
+    // lamports (minus the rent-exempt minimum), e.g. from a direct transfer
+    // that bypassed `deposit`/`withdraw`. Emits the two values so CI/devnet
+    // tooling can see the mismatch without re-deriving it.
+    pub fn assert_balance_consistent(ctx: Context<AssertBalanceConsistent>) -> Result<()> {
+        let vault_info = ctx.accounts.vault.to_account_info();
+        let rent_exempt_min = Rent::get()?.minimum_balance(vault_info.data_len());
+        let actual_available = vault_info.lamports().saturating_sub(rent_exempt_min);
+        let tracked_balance = ctx.accounts.vault.balance;
+
+        if actual_available != tracked_balance {
+            emit!(BalanceInconsistentEvent {
+                vault: ctx.accounts.vault.key(),
+                tracked_balance,
+                actual_available,
+            });
+            return Err(ErrorCode::BalanceInconsistent.into());
+        }
+
+        Ok(())
+    }
+
+    // =========================
+    // BALANCE RECONCILIATION
+    // =========================
+    //
+    // `vault.balance` is an explicit accounting field, not a live read of
+    // the vault PDA's actual lamports — see `apply_deposit_balance`'s
+    // `auto_reconcile` doc comment for why that distinction is deliberate.
+    // The invariant it normally holds is `vault.balance == actual lamports
+    // - rent_exempt_min`, maintained incrementally by `deposit`/`withdraw`/
+    // `gated_withdraw`. A direct system-program transfer straight to the
+    // vault PDA (bypassing `deposit` entirely) breaks that invariant: the
+    // lamports arrive, but nothing increments `balance` to match, so a
+    // later `withdraw` can undercount what's actually spendable or, in the
+    // other direction, `assert_balance_consistent` starts failing on an
+    // honest surplus. `gated_withdraw`'s `use_actual_lamports` flag already
+    // lets a single withdrawal look past `balance` and resync it as a side
+    // effect (see `execute_gated_withdraw`'s doc comment), but only as part
+    // of a withdrawal that opts into that flag. `reconcile` is the
+    // direct fix: owner-callable any time, it recomputes `balance` from
+    // actual lamports the same way `assert_balance_consistent` checks it,
+    // without requiring a withdrawal to ride along.
+    pub fn reconcile(ctx: Context<Reconcile>) -> Result<()> {
+        require!(
+            ctx.accounts.vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+
+        let vault = &mut ctx.accounts.vault;
+        let vault_info = vault.to_account_info();
+        let rent_exempt_min = Rent::get()?.minimum_balance(vault_info.data_len());
+        let actual_available = vault_info.lamports().saturating_sub(rent_exempt_min);
+        let old_balance = vault.balance;
+        vault.balance = actual_available;
+
+        emit!(VaultReconciledEvent {
+            vault: vault.key(),
+            old_balance,
+            new_balance: actual_available,
+        });
+
+        Ok(())
+    }
+
+    // =========================
+    // RECIPIENT ALLOWLIST
+    // =========================
+    //
+    // See [`RecipientAllowlist`]'s doc comment for how this differs from
+    // `AgentState::destination_list`. `add_recipient` creates the account
+    // on its first call (`init_if_needed`) rather than requiring a
+    // separate setup instruction, same convention as
+    // `set_destination_memo_policy`.
+    pub fn add_recipient(ctx: Context<AddRecipient>, recipient: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        let allowlist = &mut ctx.accounts.recipient_allowlist;
+        if allowlist.vault == Pubkey::default() {
+            allowlist.vault = ctx.accounts.vault.key();
+            allowlist.bump = ctx.bumps.recipient_allowlist;
+        }
+
+        let count = allowlist.count as usize;
+        require!(
+            !allowlist.recipients[..count].contains(&recipient),
+            ErrorCode::DuplicateRecipientAllowlistEntry
+        );
+        require!(
+            count < MAX_RECIPIENT_ALLOWLIST,
+            ErrorCode::TooManyRecipientAllowlistEntries
+        );
+
+        allowlist.recipients[count] = recipient;
+        allowlist.count += 1;
+        Ok(())
+    }
+
+    pub fn remove_recipient(ctx: Context<UpdateRecipientAllowlist>, recipient: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        let allowlist = &mut ctx.accounts.recipient_allowlist;
+
+        let count = allowlist.count as usize;
+        let index = allowlist.recipients[..count]
+            .iter()
+            .position(|entry| *entry == recipient)
+            .ok_or(ErrorCode::RecipientNotInAllowlist)?;
+
+        let last = count - 1;
+        allowlist.recipients[index] = allowlist.recipients[last];
+        allowlist.recipients[last] = Pubkey::default();
+        allowlist.count -= 1;
+        Ok(())
+    }
+
+    // =========================
+    // FREEZE / UNFREEZE AGENT
+    // =========================
+    //
+    // Lets the owner contain a single misbehaving agent on a multi-agent
+    // vault without pausing every agent's execution_enabled flag.
+    pub fn set_agent_frozen(ctx: Context<UpdateAgent>, frozen: bool) -> Result<()> {
+        let state = &mut ctx.accounts.agent_state;
+        require!(state.owner == ctx.accounts.owner.key(), ErrorCode::Unauthorized);
+
+        state.frozen = frozen;
+
+        emit!(AgentFrozenEvent {
+            agent_state: state.key(),
+            frozen,
+        });
+
+        Ok(())
+    }
+
+    /// Clears a `frozen` agent, whether it got there via `set_agent_frozen`
+    /// or `apply_risk_score`'s auto-freeze. Also resets `high_risk_streak`
+    /// to `0` — unlike `set_agent_frozen(false)`, which leaves the streak
+    /// wherever it was, so an agent unfrozen without a streak reset would
+    /// immediately re-freeze on its very next still-high-risk reading
+    /// rather than getting the clean slate `unfreeze` is meant to give it.
+    pub fn unfreeze(ctx: Context<UpdateAgent>) -> Result<()> {
+        let state = &mut ctx.accounts.agent_state;
+        require!(state.owner == ctx.accounts.owner.key(), ErrorCode::Unauthorized);
+
+        state.frozen = false;
+        state.high_risk_streak = 0;
+
+        emit!(AgentFrozenEvent {
+            agent_state: state.key(),
+            frozen: false,
+        });
+
+        Ok(())
+    }
+
+    /// Configures `AgentState::freeze_after` — see that field's doc
+    /// comment. `0` disables auto-freezing entirely, same convention as
+    /// `daily_limit`/`max_velocity_lamports_per_sec`.
+    pub fn set_freeze_after(ctx: Context<UpdateAgent>, freeze_after: u8) -> Result<()> {
+        require!(
+            ctx.accounts.agent_state.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.agent_state.freeze_after = freeze_after;
+        Ok(())
+    }
+
+    // =========================
+    // GATED WITHDRAW (FINAL BOSS)
+    // =========================
+    pub fn gated_withdraw(
+        ctx: Context<GatedWithdraw>,
+        amount: u64,
+        nonce: u64,
+        expiry: i64,
+        max_staleness_override: Option<i64>,
+        memo: [u8; 32],
+        use_actual_lamports: bool,
+        min_version: Option<u16>,
+    ) -> Result<()> {
+        execute_gated_withdraw(
+            &mut ctx.accounts.vault,
+            &mut ctx.accounts.agent_state,
+            &ctx.accounts.owner,
+            &ctx.accounts.instructions_sysvar,
+            ctx.accounts.destination_policy.as_mut(),
+            ctx.accounts.service_account.as_ref().map(|a| a.to_account_info()),
+            ctx.accounts.recipient.as_ref().map(|a| a.to_account_info()),
+            ctx.accounts.recipient_allowlist.as_ref(),
+            ctx.accounts.program_config.as_ref(),
+            ctx.accounts.price_feed.as_ref(),
+            ctx.accounts.usd_spending_limit.as_mut(),
+            amount,
+            nonce,
+            expiry,
+            max_staleness_override,
+            memo,
+            use_actual_lamports,
+            min_version,
+            ctx.accounts.test_clock.as_ref(),
+            ctx.remaining_accounts,
+        )
+    }
+
+    // =========================
+    // GATED TRANSFER (THIRD-PARTY RECIPIENT)
+    // =========================
+    //
+    // Identical to `gated_withdraw` in every check it runs — see
+    // `execute_gated_withdraw` — but `recipient` is mandatory here rather
+    // than optional, so a transaction clearly reads as "pay this other
+    // party" instead of silently falling back to `owner`. `owner` still
+    // signs and still authorizes the instruction; `recipient` is gated
+    // against `AgentState::destination_list` exactly as `gated_withdraw`'s
+    // optional recipient is.
+    pub fn gated_transfer(
+        ctx: Context<GatedTransfer>,
+        amount: u64,
+        nonce: u64,
+        expiry: i64,
+        max_staleness_override: Option<i64>,
+        memo: [u8; 32],
+        use_actual_lamports: bool,
+        min_version: Option<u16>,
+    ) -> Result<()> {
+        execute_gated_withdraw(
+            &mut ctx.accounts.vault,
+            &mut ctx.accounts.agent_state,
+            &ctx.accounts.owner,
+            &ctx.accounts.instructions_sysvar,
+            ctx.accounts.destination_policy.as_mut(),
+            ctx.accounts.service_account.as_ref().map(|a| a.to_account_info()),
+            Some(ctx.accounts.recipient.to_account_info()),
+            ctx.accounts.recipient_allowlist.as_ref(),
+            ctx.accounts.program_config.as_ref(),
+            ctx.accounts.price_feed.as_ref(),
+            ctx.accounts.usd_spending_limit.as_mut(),
+            amount,
+            nonce,
+            expiry,
+            max_staleness_override,
+            memo,
+            use_actual_lamports,
+            min_version,
+            ctx.accounts.test_clock.as_ref(),
+            ctx.remaining_accounts,
+        )
+    }
+
+    // =========================
+    // EVALUATE THEN WITHDRAW (ATOMIC)
+    // =========================
+    //
+    // Applies a fresh risk score and performs the gated withdrawal in the
+    // same instruction, so the withdrawal is always gated on the score it
+    // just set rather than whatever was left over from a prior transaction.
+    pub fn evaluate_and_withdraw(
+        ctx: Context<EvaluateAndWithdraw>,
+        risk_score: u8,
+        amount: u64,
+        nonce: u64,
+        expiry: i64,
+        max_staleness_override: Option<i64>,
+        memo: [u8; 32],
+        use_actual_lamports: bool,
+        min_version: Option<u16>,
+    ) -> Result<()> {
+        apply_risk_score(
+            &mut ctx.accounts.agent_state,
+            &ctx.accounts.owner.key(),
+            risk_score,
+            ctx.accounts.test_clock.as_ref(),
+        )?;
+
+        execute_gated_withdraw(
+            &mut ctx.accounts.vault,
+            &mut ctx.accounts.agent_state,
+            &ctx.accounts.owner,
+            &ctx.accounts.instructions_sysvar,
+            ctx.accounts.destination_policy.as_mut(),
+            ctx.accounts.service_account.as_ref().map(|a| a.to_account_info()),
+            None,
+            None,
+            ctx.accounts.program_config.as_ref(),
+            ctx.accounts.price_feed.as_ref(),
+            ctx.accounts.usd_spending_limit.as_mut(),
+            amount,
+            nonce,
+            expiry,
+            max_staleness_override,
+            memo,
+            use_actual_lamports,
+            min_version,
+            ctx.accounts.test_clock.as_ref(),
+            ctx.remaining_accounts,
+        )
+    }
+
+    // =========================
+    // GATED WITHDRAW, MXE-VERIFIED
+    // =========================
+    //
+    // Everything `gated_withdraw` already requires (signing-authority
+    // Ed25519 check via the instructions sysvar, freshness, velocity,
+    // memo/lifetime-cap, nonce) still applies — see `execute_gated_withdraw`.
+    // This additionally requires a `verified_authorization` record
+    // produced off-chain by `agentic_wallet_mxe`'s
+    // `verify_agent_signature_plaintext` / `_callback` /
+    // `receive_plaintext_verification_result` pipeline, whose `message`
+    // matches this exact withdrawal (same `hash_withdrawal_authorization`
+    // preimage as the signing-authority check) and whose `is_valid` is
+    // `true`. Consumed (marked `consumed = true`) on use so the same
+    // verification can't authorize a second withdrawal, and rejected with
+    // `VerificationExpired` once `VERIFIED_WITHDRAWAL_MAX_AGE_SECS` has
+    // passed since `receive_plaintext_verification_result` recorded it —
+    // a verdict that landed but was never spent shouldn't stay redeemable
+    // indefinitely.
+    //
+    // This is the realistic shape of "require a successful MXE
+    // verification before this withdrawal proceeds": `verify_agent_signature*`
+    // queues an MPC computation and only resolves later, via a callback
+    // transaction submitted by the cluster — there is no way for a
+    // *single* transaction to contain both a request for that computation
+    // and a completed result for the instructions sysvar to point at, the
+    // way `verify_preceding_ed25519_signature` can for a same-transaction
+    // native Ed25519Program check. `VerifiedWithdrawalAuthorization` is an
+    // on-chain record bridging that across transactions instead. See
+    // `receive_plaintext_verification_result`'s doc comment for why this
+    // is layered on top of, rather than instead of, the existing
+    // signing-authority check.
+    pub fn gated_withdraw_verified(
+        ctx: Context<GatedWithdrawVerified>,
+        amount: u64,
+        nonce: u64,
+        expiry: i64,
+        max_staleness_override: Option<i64>,
+        memo: [u8; 32],
+        use_actual_lamports: bool,
+        min_version: Option<u16>,
+    ) -> Result<()> {
+        let record = &mut ctx.accounts.verified_authorization;
+        require!(!record.consumed, ErrorCode::VerificationAlreadyConsumed);
+        require!(record.is_valid, ErrorCode::VerificationFailed);
+        let clock_ts = now(ctx.accounts.test_clock.as_ref())?;
+        require!(
+            clock_ts - record.recorded_at < VERIFIED_WITHDRAWAL_MAX_AGE_SECS,
+            ErrorCode::VerificationExpired
+        );
+        let expected_message = hash_withdrawal_authorization(
+            &ctx.accounts.vault.key(),
+            &ctx.accounts.owner.key(),
+            amount,
+            nonce,
+            expiry,
+        );
+        require!(
+            record.message == expected_message,
+            ErrorCode::VerificationResultMismatch
+        );
+        record.consumed = true;
+
+        execute_gated_withdraw(
+            &mut ctx.accounts.vault,
+            &mut ctx.accounts.agent_state,
+            &ctx.accounts.owner,
+            &ctx.accounts.instructions_sysvar,
+            ctx.accounts.destination_policy.as_mut(),
+            ctx.accounts.service_account.as_ref().map(|a| a.to_account_info()),
+            None,
+            None,
+            ctx.accounts.program_config.as_ref(),
+            ctx.accounts.price_feed.as_ref(),
+            ctx.accounts.usd_spending_limit.as_mut(),
+            amount,
+            nonce,
+            expiry,
+            max_staleness_override,
+            memo,
+            use_actual_lamports,
+            min_version,
+            ctx.accounts.test_clock.as_ref(),
+            ctx.remaining_accounts,
+        )
+    }
+
+    // =========================
+    // VAULT SUMMARY (READ CONVENIENCE)
+    // =========================
+    //
+    // Consolidates `Vault` and its linked `AgentState` into one return-data
+    // struct so a dashboard doesn't have to fetch and decode both accounts
+    // and re-derive anything. See `VaultSummary`'s doc comment for which
+    // requested fields this program doesn't actually track and why.
+    pub fn get_vault_summary(ctx: Context<GetVaultSummary>) -> Result<()> {
+        require!(
+            ctx.accounts.agent_state.owner == ctx.accounts.vault.owner,
+            ErrorCode::Unauthorized
+        );
+
+        let summary = VaultSummary {
+            balance: ctx.accounts.vault.balance,
+            owner: ctx.accounts.vault.owner,
+            risk_score: ctx.accounts.agent_state.risk_score,
+            execution_enabled: ctx.accounts.agent_state.execution_enabled,
+            paused: ctx.accounts.agent_state.frozen,
+            last_action_timestamp: ctx.accounts.agent_state.last_action_timestamp,
+        };
+        solana_program::program::set_return_data(&summary.try_to_vec()?);
+
+        Ok(())
+    }
+
+    // =========================
+    // REMAINING VELOCITY ALLOWANCE (READ CONVENIENCE)
+    // =========================
+    //
+    // The request behind this instruction asked for a
+    // `remaining_epoch_allowance` read that centralizes the
+    // `spent_this_epoch`/`epoch_start` rollover math agents would
+    // otherwise replicate off-chain. This program tracks neither field;
+    // its one fixed-window budget is `daily_limit`'s `spent_today`/
+    // `window_start` rollover in `execute_gated_withdraw`, which this
+    // instruction does not report. What it reports instead, for the same
+    // "don't make callers replicate on-chain math" problem, is the
+    // continuous velocity check in
+    // `execute_gated_withdraw` (`AgentState::max_velocity_lamports_per_sec`):
+    // like a rolling epoch, it naturally replenishes as time passes
+    // rather than resetting at fixed boundaries, but unlike an epoch it
+    // has no discrete boundary to report either. This reports that
+    // check's current ceiling — the largest `amount` a `gated_withdraw`
+    // submitted right now would pass it — via return data, the same
+    // convention `get_vault_summary` uses. See `VelocityAllowance`'s doc
+    // comment for what's deliberately left out and why.
+    pub fn remaining_velocity_allowance(
+        ctx: Context<GetRemainingVelocityAllowance>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.agent_state.owner == ctx.accounts.vault.owner,
+            ErrorCode::Unauthorized
+        );
+
+        let state = &ctx.accounts.agent_state;
+        let clock_ts = now(ctx.accounts.test_clock.as_ref())?;
+        let elapsed = (clock_ts - state.last_action_timestamp).max(0) as u64;
+        let max_amount_now = if state.max_velocity_lamports_per_sec == 0 {
+            u64::MAX
+        } else {
+            state
+                .max_velocity_lamports_per_sec
+                .saturating_mul(elapsed)
+        };
+
+        let allowance = VelocityAllowance {
+            max_amount_now,
+            last_action_timestamp: state.last_action_timestamp,
+        };
+        solana_program::program::set_return_data(&allowance.try_to_vec()?);
+
+        Ok(())
+    }
+
+    // =========================
+    // CAN ACT (READ CONVENIENCE)
+    // =========================
+    //
+    // Consolidates the gating checks `execute_gated_withdraw` itself
+    // enforces — frozen, execution_enabled, risk_score, staleness, and
+    // (when `amount` is supplied) velocity — into one read-only answer to
+    // "would a gated withdraw right now succeed", checked in the exact
+    // same priority order those `require!`s run in there, so this stays
+    // the authoritative answer rather than a second implementation that
+    // could drift from it. Two gates the request mentions that this
+    // program genuinely doesn't have — a separate "heartbeat" signal and
+    // an "activation" flag distinct from `execution_enabled` — aren't
+    // reported because nothing here tracks either; see `VaultSummary`'s
+    // doc comment for the same kind of gap. `amount` is optional because
+    // velocity is the only check that needs one: omitting it answers as
+    // if the caller were about to submit a `0`-lamport withdrawal, which
+    // never trips velocity on its own.
+    //
+    // Deliberately takes no `owner` signer — like `get_vault_summary` and
+    // `remaining_velocity_allowance`, this is a pure read, not an
+    // authorization check; anyone who can already fetch `vault`/
+    // `agent_state` off-chain could replicate this math themselves, this
+    // endpoint just saves them the trouble (and the risk of getting the
+    // replication wrong).
+    pub fn can_act(ctx: Context<CanAct>, amount: Option<u64>) -> Result<()> {
+        require!(
+            ctx.accounts.agent_state.owner == ctx.accounts.vault.owner,
+            ErrorCode::Unauthorized
+        );
+
+        let state = &ctx.accounts.agent_state;
+        let clock_ts = now(ctx.accounts.test_clock.as_ref())?;
+
+        let mut reason = CanActReason::Ok;
+        if state.frozen {
+            reason = CanActReason::Frozen;
+        } else if !state.execution_enabled {
+            reason = CanActReason::ExecutionBlocked;
+        } else if state.risk_score > state.max_risk_score {
+            reason = CanActReason::HighRisk;
+        } else if clock_ts - state.last_action_timestamp >= state.action_timeout_secs {
+            reason = CanActReason::Stale;
+        } else if state.max_risk_staleness_secs > 0
+            && clock_ts - state.risk_updated_at >= state.max_risk_staleness_secs
+        {
+            reason = CanActReason::RiskStale;
+        } else if let Some(amount) = amount {
+            if state.max_velocity_lamports_per_sec > 0 {
+                let elapsed = (clock_ts - state.last_action_timestamp).max(0) as u64;
+                let allowed = state
+                    .max_velocity_lamports_per_sec
+                    .saturating_mul(elapsed);
+                if amount > allowed {
+                    reason = CanActReason::VelocityExceeded;
+                }
+            }
+        }
+
+        let result = CanActResult {
+            can_act: reason == CanActReason::Ok,
+            reason,
+        };
+        solana_program::program::set_return_data(&result.try_to_vec()?);
+
+        Ok(())
+    }
+
+    // =========================
+    // DESTINATION MEMO POLICY
+    // =========================
+    pub fn set_destination_memo_policy(
+        ctx: Context<SetDestinationMemoPolicy>,
+        required_memo: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.destination_policy.required_memo = required_memo;
+        Ok(())
+    }
+
+    // =========================
+    // DESTINATION LIFETIME CAP
+    // =========================
+    //
+    // `lifetime_cap` bounds the running total `gated_withdraw` /
+    // `evaluate_and_withdraw` may ever pay to `destination` from `vault`;
+    // see `DestinationMemoPolicy::lifetime_sent`'s doc comment for how that
+    // differs from the epoch-resetting velocity check. Passing `None`
+    // removes any existing cap without touching `lifetime_sent` itself —
+    // raising or clearing the cap doesn't erase what's already been sent.
+    pub fn set_destination_lifetime_cap(
+        ctx: Context<SetDestinationLifetimeCap>,
+        lifetime_cap: Option<u64>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.destination_policy.lifetime_cap = lifetime_cap;
+        Ok(())
+    }
+
+    // =========================
+    // HOOK ALLOWLIST
+    // =========================
+    //
+    // Replaces `vault.hook_allowlist` wholesale rather than
+    // adding/removing one entry at a time — with only `MAX_HOOK_ALLOWLIST`
+    // slots, the owner is expected to pass the full desired set each call.
+    // See `invoke_vault_hook`'s doc comment for the hook interface this
+    // allowlist gates.
+    pub fn set_hook_allowlist(
+        ctx: Context<SetHookAllowlist>,
+        allowlist: [Pubkey; MAX_HOOK_ALLOWLIST],
+        count: u8,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            (count as usize) <= MAX_HOOK_ALLOWLIST,
+            ErrorCode::TooManyHookPrograms
+        );
+        ctx.accounts.vault.hook_allowlist = allowlist;
+        ctx.accounts.vault.hook_allowlist_count = count;
+        Ok(())
+    }
+
+    // =========================
+    // PRICE FEED
+    // =========================
+    //
+    // This program has no Pyth/Switchboard dependency, so `PriceFeed` is a
+    // self-contained on-chain record rather than a parsed external oracle
+    // account — same shape as `TestClock`: one PDA per `authority`,
+    // `init_if_needed` on first use, updatable only by that same
+    // `authority` thereafter. `set_usd_spending_limit` stores whichever
+    // `PriceFeed` pubkey a vault trusts; `execute_gated_withdraw` reads it
+    // (and checks `updated_at` against `UsdSpendingLimit.
+    // max_price_staleness_secs`) to convert a withdrawal's lamport amount
+    // into USD cents. Integrating a real price feed program means pointing
+    // `UsdSpendingLimit.price_feed` at that program's own account instead —
+    // this program never interprets `PriceFeed`'s layout as anything but
+    // its own.
+    pub fn set_price_feed(ctx: Context<SetPriceFeed>, price_usd_cents_per_sol: u64) -> Result<()> {
+        let feed = &mut ctx.accounts.price_feed;
+        require!(
+            feed.authority == Pubkey::default() || feed.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        feed.authority = ctx.accounts.authority.key();
+        feed.price_usd_cents_per_sol = price_usd_cents_per_sol;
+        feed.updated_at = now(ctx.accounts.test_clock.as_ref())?;
+        feed.bump = ctx.bumps.price_feed;
+        Ok(())
+    }
+
+    // =========================
+    // USD SPENDING LIMIT
+    // =========================
+    //
+    // Layers a USD-denominated ceiling on top of (not instead of)
+    // `AgentState::daily_limit`: that lamport-denominated limit drifts as
+    // SOL's price moves, so an owner who wants risk bounded in real terms
+    // can additionally set this. Omitting the account from a withdrawal
+    // (the common case, until this is called) skips the USD check
+    // entirely, same convention as `destination_policy`/`program_config`.
+    pub fn set_usd_spending_limit(
+        ctx: Context<SetUsdSpendingLimit>,
+        price_feed: Pubkey,
+        max_price_staleness_secs: i64,
+        daily_limit_usd_cents: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(max_price_staleness_secs > 0, ErrorCode::InvalidPriceStaleness);
+        let limit = &mut ctx.accounts.usd_spending_limit;
+        limit.price_feed = price_feed;
+        limit.max_price_staleness_secs = max_price_staleness_secs;
+        limit.daily_limit_usd_cents = daily_limit_usd_cents;
+        limit.bump = ctx.bumps.usd_spending_limit;
+        Ok(())
+    }
+
+    // =========================
+    // CROSS-PROGRAM SIGNATURE VERIFICATION (agentic_wallet_mxe)
+    // =========================
+    //
+    // See `receive_plaintext_verification_result`'s doc comment for the
+    // whole mechanism and its limits. Setting `verifier_program` back to
+    // `Pubkey::default()` disables `receive_plaintext_verification_result`
+    // for this vault again.
+    pub fn set_verifier_program(
+        ctx: Context<SetVerifierProgram>,
+        verifier_program: Pubkey,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.vault.owner == ctx.accounts.owner.key(),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.vault.verifier_program = verifier_program;
+        Ok(())
+    }
+
+    /// Pre-allocates the `VerifiedWithdrawalAuthorization` slot a later
+    /// `receive_plaintext_verification_result` CPI will write into. This
+    /// has to happen in its own ordinary (signer-bearing) transaction
+    /// rather than inside `receive_plaintext_verification_result` itself:
+    /// that instruction only ever runs as a CPI forwarded by
+    /// `agentic_wallet_mxe`'s callback, which relays every account it was
+    /// handed as non-signer (see that instruction's doc comment) — there
+    /// is no signer available in that call path to pay for `init`.
+    pub fn create_verified_withdrawal_slot(
+        ctx: Context<CreateVerifiedWithdrawalSlot>,
+        message: [u8; 32],
+    ) -> Result<()> {
+        let record = &mut ctx.accounts.verified_authorization;
+        record.message = message;
+        record.is_valid = false;
+        record.recorded_at = now(ctx.accounts.test_clock.as_ref())?;
+        record.consumed = false;
+        record.bump = ctx.bumps.verified_authorization;
+        Ok(())
+    }
+
+    /// CPI target for `agentic_wallet_mxe`'s
+    /// `verify_agent_signature_plaintext_callback`: that callback invokes
+    /// this instruction directly (see its doc comment over there) with the
+    /// plaintext outcome of an off-chain-authorized MPC signature check,
+    /// carrying `message` (the exact 32-byte hash `gated_withdraw_verified`
+    /// also recomputes via `hash_withdrawal_authorization`) and `is_valid`.
+    /// The `verified_authorization` slot must already exist (see
+    /// `create_verified_withdrawal_slot`) — this instruction only updates
+    /// it, never creates it.
+    ///
+    /// `require!(vault.verifier_program != Pubkey::default(), ...)` is the
+    /// only gate this instruction can actually enforce: Solana gives a
+    /// callee no generic way to learn which program actually issued an
+    /// incoming CPI (the same limitation `OwnerKind::ProgramControlled`
+    /// documents), so a caller who knows a vault's key and this
+    /// instruction's discriminator could call it directly with fabricated
+    /// `is_valid = true`, `verifier_program` or not. That's exactly why
+    /// `gated_withdraw_verified` treats a consumed, matching
+    /// `VerifiedWithdrawalAuthorization` as an *additional* requirement
+    /// layered on top of `execute_gated_withdraw`'s own
+    /// signing-authority check, never as a withdrawal's sole
+    /// authorization. `verifier_program` being unset is still a real
+    /// opt-in gate though: a vault owner who never calls
+    /// `set_verifier_program` keeps this entry point fully inert.
+    pub fn receive_plaintext_verification_result(
+        ctx: Context<ReceivePlaintextVerificationResult>,
+        message: [u8; 32],
+        is_valid: bool,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.vault.verifier_program != Pubkey::default(),
+            ErrorCode::VerifierNotConfigured
+        );
+        let record = &mut ctx.accounts.verified_authorization;
+        require!(record.message == message, ErrorCode::VerificationResultMismatch);
+        record.is_valid = is_valid;
+        record.recorded_at = now(ctx.accounts.test_clock.as_ref())?;
+        Ok(())
+    }
+
+    // =========================
+    // TEST CLOCK (DETERMINISTIC TIME, test-clock FEATURE ONLY)
+    // =========================
+    //
+    // Only compiled into a binary built with the `test-clock` feature —
+    // see [`now`]'s doc comment for why this must never ship to
+    // production. `init_if_needed` on first call, so the first caller to
+    // advance the clock for a given `authority` also creates it.
+    #[cfg(feature = "test-clock")]
+    pub fn set_test_clock(ctx: Context<SetTestClock>, unix_timestamp: i64) -> Result<()> {
+        let clock = &mut ctx.accounts.test_clock;
+        require!(
+            clock.authority == Pubkey::default() || clock.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        clock.authority = ctx.accounts.authority.key();
+        clock.unix_timestamp = unix_timestamp;
+        clock.bump = ctx.bumps.test_clock;
+        Ok(())
+    }
+}
+
+// =========================
+// ACCOUNTS
+// =========================
+
+#[account]
+pub struct Vault {
+    pub owner: Pubkey,
+    pub balance: u64,
+    /// `Vault`'s own PDA bump, for seeds `[b"vault", owner.as_ref()]` — set
+    /// at creation (`initialize_vault` or `deposit_with_auto_create`, which
+    /// both derive the same PDA for a given `owner`) so CPIs that need the
+    /// vault to sign as authority, e.g. an SPL-token transfer, can rebuild
+    /// `signer_seeds` from this instead of being handed the bump out of
+    /// band. `withdraw_spl` reads it back for exactly that; lamport
+    /// transfers still debit the account directly rather than going
+    /// through such a CPI. Accurate from creation on (not a placeholder `0`).
+    pub bump: u8,
+    /// How `owner` is authorized. See [`OwnerKind`].
+    pub owner_kind: OwnerKind,
+    /// When set, `deposit` sets `balance` from the vault's actual lamports
+    /// (minus the rent-exempt minimum) instead of incrementing it by
+    /// `amount` — see `apply_deposit_balance`'s doc comment. Off by default to preserve
+    /// strict accounting.
+    pub auto_reconcile: bool,
+    /// Programs `deposit`/`withdraw`/`gated_withdraw`/`evaluate_and_withdraw`
+    /// are allowed to CPI into as a post-transfer hook, set by `owner` via
+    /// `set_hook_allowlist`. See [`invoke_vault_hook`]'s doc comment for the
+    /// hook interface. Empty by default — no hooks run unless `owner` opts
+    /// in.
+    pub hook_allowlist: [Pubkey; MAX_HOOK_ALLOWLIST],
+    pub hook_allowlist_count: u8,
+    /// Program trusted to CPI into `receive_plaintext_verification_result`
+    /// on this vault's behalf, set by `owner` via `set_verifier_program`.
+    /// `Pubkey::default()` (the default) disables that entry point
+    /// entirely for this vault — see `receive_plaintext_verification_result`'s
+    /// doc comment for why setting this isn't, on its own, a real
+    /// cryptographic guarantee of who actually called it.
+    pub verifier_program: Pubkey,
+    /// Key `accept_owner` will promote to `owner`, set by the current
+    /// `owner` via `propose_owner` and cleared either by `accept_owner`
+    /// succeeding or by `owner` calling `cancel_owner_transfer`. `None`
+    /// (the default) means no transfer is in flight. Two-step rather than
+    /// a direct `set_owner`, so a typo'd or unreachable new key can't
+    /// strand the vault without a matching private key to act as `owner`.
+    ///
+    /// This `Vault`'s own PDA stays derived from whichever key
+    /// `initialize_vault`/`deposit_with_auto_create` originally ran with
+    /// (see `Vault::bump`'s doc comment) — `accept_owner` only ever moves
+    /// the `owner` field, never the account address. `Deposit`/`Withdraw`/
+    /// `DepositSpl`/`WithdrawSpl`/`GatedWithdraw`/`GatedTransfer` account
+    /// for that by not re-deriving the vault from `owner` at all; like
+    /// `UpdateVaultOwnerKind`/`EmergencySweep`/`CloseVault`, they take
+    /// `vault` as a plain account and check `vault.owner == owner.key()`
+    /// explicitly, so a new owner can use every one of them immediately
+    /// after `accept_owner` without the vault having to move.
+    pub pending_owner: Option<Pubkey>,
+    /// Key allowed to `initiate_recovery`/`recover` this vault's `owner` if
+    /// the real owner key is lost, set by the current `owner` via
+    /// `set_guardian`. `None` (the default) disables guardian recovery
+    /// entirely — unlike `pending_owner`, nothing here grants any authority
+    /// on its own; `guardian` still has to go through the
+    /// `initiate_recovery` → wait out `recovery_delay_secs` → `recover`
+    /// sequence, and `owner` can `cancel_recovery` at any point before
+    /// `recover` lands.
+    pub guardian: Option<Pubkey>,
+    /// Minimum seconds `recover` must wait after `initiate_recovery`,
+    /// set alongside `guardian` by `set_guardian`. Enforced to be at least
+    /// `MIN_RECOVERY_DELAY_SECS` whenever `guardian` is being set to
+    /// `Some`, so a compromised or careless guardian can't seize a vault
+    /// faster than `owner` has a realistic chance to notice and
+    /// `cancel_recovery`.
+    pub recovery_delay_secs: i64,
+    /// Unix timestamp `initiate_recovery` last ran at, or `0` if no
+    /// recovery is currently in flight — same 0-as-unset convention as
+    /// `AgentState::last_eval_timestamp`. `recover` requires this to be
+    /// nonzero and at least `recovery_delay_secs` in the past.
+    pub recovery_initiated_at: i64,
+    /// The `new_owner` recorded by the `initiate_recovery` call this
+    /// `recovery_initiated_at` belongs to. `recover` requires its own
+    /// `new_owner` argument to match this, so a guardian can't initiate a
+    /// recovery to one key and finalize it to a different one once the
+    /// delay elapses.
+    pub pending_recovery_owner: Option<Pubkey>,
+}
+
+/// How `Vault::owner` proves authorization for owner-gated instructions
+/// (`withdraw`, `gated_withdraw`, `set_destination_memo_policy`, ...).
+///
+/// - `Direct`: `owner` is an ordinary Ed25519 key; authorization is exactly
+///   today's check — `owner` must match and sign the instruction directly.
+/// - `ProgramControlled`: `owner` is a PDA belonging to `controlling_program`
+///   (e.g. a Squads-style multisig's vault authority). That program signs
+///   for the PDA via `invoke_signed` when it CPIs into an owner-gated
+///   instruction on the multisig's own execution flow, so `owner` still
+///   arrives as a signer and the existing `owner == vault.owner` check
+///   still holds. `controlling_program` is recorded for operators and
+///   client tooling to route through the right multisig; this program
+///   cannot itself verify *which* program issued an incoming CPI's
+///   `invoke_signed` (Solana doesn't expose the immediate caller of a CPI
+///   to the callee), so enforcement beyond "a valid signer for this PDA
+///   was produced" is the responsibility of `controlling_program`'s own
+///   execution flow, same as it is for any PDA-authority design today.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OwnerKind {
+    Direct,
+    ProgramControlled { controlling_program: Pubkey },
+}
+
+/// Maximum entries in `AgentState::destination_list`.
+pub const MAX_DESTINATION_LIST: usize = 4;
+
+/// Maximum entries in `RecipientAllowlist::recipients`.
+pub const MAX_RECIPIENT_ALLOWLIST: usize = 16;
+
+/// `AgentState`'s account space before `action_history` existed. Any
+/// account still at this size predates that migration — see
+/// `migrate_agent_state`.
+pub const AGENT_STATE_SPACE_V1: usize =
+    8 + 32 + 1 + 1 + 8 + 32 + 8 + 8 + 1 + 1 + 8 + 8 + 8 + 4 + 1 + 32 + 2 + 1 + 32 * MAX_DESTINATION_LIST + 1 + 8 + 8 + 8 + 1;
+
+/// `AgentState`'s account space including `action_history`,
+/// `high_risk_streak`/`freeze_after`, and `min_cooldown_secs`, but before
+/// `max_risk_staleness_secs`/`risk_updated_at` existed. Any account still
+/// at this size predates that migration — see `migrate_agent_state`.
+pub const AGENT_STATE_SPACE_V2: usize =
+    AGENT_STATE_SPACE_V1 + (8 + 8 + 1 + 1) * ACTION_HISTORY_CAPACITY + 1 + 1 + 1 + 1 + 8;
+
+/// `AgentState`'s current account space, including
+/// `max_risk_staleness_secs`/`risk_updated_at`.
+pub const AGENT_STATE_SPACE: usize = AGENT_STATE_SPACE_V2 + 8 + 8;
+
+/// How `AgentState::destination_list` governs `check_destination_allowed`.
+///
+/// - `Denylist` (the default): every destination is permitted except the
+///   ones in the list. An empty list under this mode permits everything,
+///   which is why it's the default — adding entries via
+///   `add_denied_destination` only ever narrows what was already allowed,
+///   never breaks an agent that hasn't configured this feature.
+/// - `Allowlist`: every destination is rejected except the ones in the
+///   list. An empty list under this mode rejects everything, so switching
+///   to `Allowlist` with `set_destination_list_mode` before populating the
+///   list locks an agent's managed destination out entirely until at least
+///   one entry is added.
+///
+/// Both modes share the same underlying `destination_list` storage rather
+/// than keeping separate allow/deny arrays — `add_denied_destination`/
+/// `remove_denied_destination` are named for this feature's primary
+/// denylist use case, but in `Allowlist` mode the same two instructions
+/// add/remove the permitted set instead.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DestinationListMode {
+    Denylist,
+    Allowlist,
+}
+
+/// Bounded ring buffer of recently-seen deposit idempotency keys for a
+/// single vault, used to reject at-least-once resubmissions without
+/// growing state unboundedly. Entries older than
+/// `DEPOSIT_IDEMPOTENCY_WINDOW_SECS` are treated as evicted even before
+/// their ring-buffer slot is physically overwritten.
+pub const DEPOSIT_LOG_CAPACITY: usize = 16;
+pub const DEPOSIT_IDEMPOTENCY_WINDOW_SECS: i64 = 600;
+
+/// Rolling-window length, in seconds, `AgentState::daily_limit` resets on.
+/// See `execute_gated_withdraw`'s daily-limit check for how `window_start`
+/// advances when multiple windows have elapsed since the last withdrawal.
+pub const DAILY_LIMIT_WINDOW_SECS: i64 = 86_400;
+
+/// `AgentState::max_risk_score`'s default at `initialize_agent`, matching
+/// this program's long-standing hardcoded `risk_score <= 80` before that
+/// field existed.
+pub const DEFAULT_MAX_RISK_SCORE: u8 = 80;
+
+/// Floor `set_guardian` enforces on `recovery_delay_secs` whenever
+/// `guardian` is being set to `Some` — see `Vault::recovery_delay_secs`'s
+/// doc comment.
+pub const MIN_RECOVERY_DELAY_SECS: i64 = 86_400;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct DepositLogEntry {
+    pub key: [u8; 16],
+    pub recorded_at: i64,
+}
+
+impl Default for DepositLogEntry {
+    fn default() -> Self {
+        Self {
+            key: [0u8; 16],
+            recorded_at: 0,
+        }
+    }
+}
+
+#[account]
+pub struct DepositLog {
+    pub vault: Pubkey,
+    pub entries: [DepositLogEntry; DEPOSIT_LOG_CAPACITY],
+    pub len: u8,
+    pub next_index: u8,
+}
+
+impl DepositLog {
+    fn reject_if_duplicate(&self, key: &[u8; 16], now: i64) -> Result<()> {
+        for entry in self.entries.iter().take(self.len as usize) {
+            if &entry.key == key && now - entry.recorded_at < DEPOSIT_IDEMPOTENCY_WINDOW_SECS {
+                return Err(ErrorCode::DuplicateDeposit.into());
+            }
+        }
+        Ok(())
+    }
+
+    fn record(&mut self, key: &[u8; 16], now: i64) {
+        let index = self.next_index as usize;
+        self.entries[index] = DepositLogEntry {
+            key: *key,
+            recorded_at: now,
+        };
+        self.next_index = ((index + 1) % DEPOSIT_LOG_CAPACITY) as u8;
+        self.len = self.len.saturating_add(1).min(DEPOSIT_LOG_CAPACITY as u8);
+    }
+}
+
+/// Capacity of `AgentState::action_history`.
+pub const ACTION_HISTORY_CAPACITY: usize = 8;
+
+/// `AgentState::action_history`'s `action_kind` values.
+pub const ACTION_KIND_EVALUATE: u8 = 0;
+pub const ACTION_KIND_GATED_WITHDRAW: u8 = 1;
+
+/// One entry of `AgentState::action_history` — see that field's doc
+/// comment. `amount` is `0` for `ACTION_KIND_EVALUATE` entries, which
+/// aren't withdrawals.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ActionHistoryEntry {
+    pub timestamp: i64,
+    pub amount: u64,
+    pub risk_score: u8,
+    pub action_kind: u8,
+}
+
+impl Default for ActionHistoryEntry {
+    fn default() -> Self {
+        Self {
+            timestamp: 0,
+            amount: 0,
+            risk_score: 0,
+            action_kind: 0,
+        }
+    }
+}
+
+/// Tracked SPL token balance for one `(vault, mint)` pair — see
+/// `deposit_spl`'s doc comment for why this is a separate record rather
+/// than trusting `vault_token_account`'s own balance directly. PDA seeds:
+/// `[b"token_vault", vault.as_ref(), mint.as_ref()]`.
+#[account]
+pub struct TokenVault {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub balance: u64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct AgentState {
+    pub owner: Pubkey,
+    pub risk_score: u8,
+    pub execution_enabled: bool,
+    pub last_action_timestamp: i64,
+    /// Ed25519 public key expected to sign withdrawal authorizations for
+    /// this agent (e.g. the MXE's distributed signing key).
+    pub signing_authority: Pubkey,
+    /// Highest withdrawal authorization nonce consumed so far; authorizations
+    /// must strictly increase this to prevent replay.
+    ///
+    /// This strictly-increasing single counter is this program's whole
+    /// replay-protection mechanism — there's no separate signed-message
+    /// registry with its own eviction window/capacity to tune. A
+    /// time-windowed registry would need to remember every authorization
+    /// seen within some recent window (to reject a repeat) and evict
+    /// entries older than that window on insert, trading window size for
+    /// account size the way the request asking for a `set_replay_window`
+    /// instruction describes; a monotonic counter like this one needs
+    /// neither, since any non-increasing nonce is rejected regardless of
+    /// age. Introducing that registry (and the `set_replay_window`
+    /// instruction to configure it) would be a genuinely new, separate
+    /// replay-protection scheme sitting alongside this field, not a
+    /// configuration knob on it.
+    pub last_withdrawal_nonce: u64,
+    /// Maximum age, in seconds, that `last_action_timestamp` may have before
+    /// `gated_withdraw` rejects with `ExecutionTimeout`. This is a
+    /// freshness bound, not a rate limit: it rejects a withdrawal once the
+    /// last recorded action is too *old* (the risk evaluation backing
+    /// `execution_enabled`/`risk_score` may no longer be trustworthy), the
+    /// opposite direction from `min_cooldown_secs`, which rejects a
+    /// withdrawal for coming too *soon* after the last one. Callers may
+    /// tighten this per-withdrawal via `max_staleness_override`, never
+    /// loosen it.
+    pub action_timeout_secs: i64,
+    /// Set by the owner via `set_agent_frozen` to contain a single
+    /// misbehaving agent on a multi-agent vault without pausing the whole
+    /// vault. A frozen agent's `gated_withdraw` is rejected with
+    /// `AgentFrozen`; the owner can still call `evaluate_agent_action` and
+    /// `set_agent_frozen` to manage or unfreeze it.
+    pub frozen: bool,
+    /// Reserved for when `AgentState` is derived as a PDA, mirroring
+    /// [`Vault::bump`]. Always `0` until that migration lands.
+    pub bump: u8,
+    /// Minimum seconds required between `evaluate_agent_action` calls that
+    /// actually update the score, rejecting too-frequent ones with
+    /// `EvaluationTooSoon`. `0` disables the limit. Distinct from
+    /// `action_timeout_secs`, which governs withdrawal staleness, not
+    /// evaluation frequency.
+    pub min_eval_interval_secs: i64,
+    /// Timestamp of the last evaluation that passed `min_eval_interval_secs`.
+    pub last_eval_timestamp: i64,
+    /// Ceiling on outflow rate, in lamports per second, measured as
+    /// `amount / (now - last_action_timestamp)` across consecutive gated
+    /// withdrawals. Complements the fixed per-action/per-epoch caps by
+    /// catching rapid sequences of individually-small withdrawals that a
+    /// static cap misses. `0` disables the check. Owner-configured via
+    /// `set_max_velocity`.
+    pub max_velocity_lamports_per_sec: u64,
+    /// Collision-free identifier assigned by `register_agent` from
+    /// `AgentRegistry`'s counter, for the confidential circuits
+    /// (`SigningRequest::agent_id` in `circuits`) and any other
+    /// agent-id-keyed feature to reference unambiguously. Meaningless
+    /// until `agent_id_registered` is set — `0` is a valid assigned id,
+    /// so it can't double as its own "unregistered" sentinel.
+    pub agent_id: u32,
+    /// Whether `register_agent` has already assigned `agent_id`. Sticky:
+    /// once set, `register_agent` rejects a second registration for this
+    /// `AgentState` with `DuplicateAgentRegistration` rather than
+    /// reassigning a fresh id.
+    pub agent_id_registered: bool,
+    /// Performance-fee recipient for `execute_gated_withdraw`, owner-configured
+    /// via `set_service_split`. Meaningless while `service_bps == 0` — the
+    /// default, and the only state in which a withdrawal proceeds without
+    /// a `service_account` present at all.
+    pub service_account: Pubkey,
+    /// Basis points (out of 10,000) of each gated withdrawal's `amount`
+    /// routed to `service_account` instead of the withdrawing owner.
+    /// `0` (the default) disables the split entirely. Owner-configured via
+    /// `set_service_split`, which guards `service_bps <= 10_000`.
+    pub service_bps: u16,
+    /// Selects allowlist vs denylist semantics for `destination_list`. See
+    /// [`DestinationListMode`]. Defaults to `Denylist` with an empty list,
+    /// which permits everything — matching the no-restriction behavior an
+    /// agent had before this feature existed.
+    pub destination_list_mode: DestinationListMode,
+    /// Addresses `check_destination_allowed` consults under
+    /// `destination_list_mode`, owner-configured via
+    /// `add_denied_destination`/`remove_denied_destination`. Enforced
+    /// against every caller-configurable payment destination a gated
+    /// withdrawal can have: `service_account` (when `service_bps > 0`
+    /// sends it a cut) and `recipient` (when `gated_withdraw`/
+    /// `gated_transfer` pays a third party instead of `owner`). `owner`
+    /// itself — a fixed, already-authenticated address — is never checked
+    /// against this list. See `check_destination_allowed`.
+    pub destination_list: [Pubkey; MAX_DESTINATION_LIST],
+    pub destination_list_count: u8,
+    /// Ceiling on total `gated_withdraw`/`evaluate_and_withdraw` outflow
+    /// within the rolling window starting at `window_start`. `0` disables
+    /// the check — the default, matching `max_velocity_lamports_per_sec`'s
+    /// convention. Owner-configured via `set_daily_limit`.
+    pub daily_limit: u64,
+    /// Running total sent within the current window (`window_start` up to
+    /// but not including `window_start + DAILY_LIMIT_WINDOW_SECS`). Reset
+    /// to `0` whenever
+    /// `execute_gated_withdraw` advances `window_start` past one or more
+    /// elapsed windows.
+    pub spent_today: u64,
+    /// Start of the current daily-limit window. Advances by whole
+    /// multiples of `DAILY_LIMIT_WINDOW_SECS` rather than being reset to
+    /// `clock_ts` directly, so the window boundary stays aligned to
+    /// `initialize_agent`'s original start rather than drifting forward
+    /// with every idle reset.
+    pub window_start: i64,
+    /// Ceiling `apply_risk_score`/`execute_gated_withdraw`/`can_act`
+    /// compare `risk_score` against — a risk score at or below this value
+    /// keeps `execution_enabled` true and gated withdrawals permitted.
+    /// Defaults to [`DEFAULT_MAX_RISK_SCORE`] at `initialize_agent`;
+    /// owner-configured afterward via `configure_policy`, which enforces
+    /// `max_risk_score <= 100`.
+    pub max_risk_score: u8,
+    /// Ring buffer of the last `ACTION_HISTORY_CAPACITY` actions this
+    /// agent took, for auditing. `evaluate_agent_action` pushes an
+    /// `ACTION_KIND_EVALUATE` entry and `execute_gated_withdraw` pushes an
+    /// `ACTION_KIND_GATED_WITHDRAW` one (covering `gated_withdraw`,
+    /// `gated_transfer`, `evaluate_and_withdraw`'s withdrawal half, and
+    /// `gated_withdraw_verified`) — see `AgentState::record_action`. Read
+    /// directly off the account; there's no separate read instruction,
+    /// same convention as `destination_list`.
+    ///
+    /// Any `AgentState` created before this field existed is smaller than
+    /// this struct's current Borsh layout and must first be grown in
+    /// place via `migrate_agent_state` — see that instruction's doc
+    /// comment.
+    pub action_history: [ActionHistoryEntry; ACTION_HISTORY_CAPACITY],
+    pub action_history_len: u8,
+    pub action_history_next_index: u8,
+    /// Number of consecutive `evaluate_agent_action`/`evaluate_and_withdraw`
+    /// readings, up to and including the most recent, with
+    /// `risk_score > max_risk_score`. Any reading at or below
+    /// `max_risk_score` resets this to `0` — only a consecutive run of
+    /// high-risk readings counts. See `apply_risk_score`.
+    pub high_risk_streak: u8,
+    /// Once `high_risk_streak` reaches this count, `apply_risk_score`
+    /// sets `frozen = true`, same sticky freeze `set_agent_frozen` sets by
+    /// hand. `0` (the default) disables auto-freezing — a single high-risk
+    /// reading still disables `execution_enabled` for that reading, same
+    /// as always, but never sets `frozen` on its own. Owner-configured via
+    /// `set_freeze_after`. Cleared only by `unfreeze` (or
+    /// `set_agent_frozen(false)`), never by a later low-risk reading on
+    /// its own.
+    pub freeze_after: u8,
+    /// Minimum seconds required between `last_action_timestamp` and a new
+    /// `gated_withdraw`'s `clock_ts`, rejecting one fired too soon after
+    /// the last with `CooldownNotElapsed`. A genuine rate limit on
+    /// withdrawals themselves — distinct from `action_timeout_secs`, which
+    /// rejects a withdrawal for `last_action_timestamp` being too *old*,
+    /// not too recent. `0` (the default) disables it, same convention as
+    /// `max_velocity_lamports_per_sec`/`min_eval_interval_secs`.
+    /// Owner-configured via `set_min_cooldown`.
+    pub min_cooldown_secs: i64,
+    /// Maximum age, in seconds, `risk_updated_at` may reach before
+    /// `execute_gated_withdraw` treats the current `risk_score` as stale
+    /// and rejects with `RiskScoreStale` rather than trusting it. `0` (the
+    /// default) disables the check, same convention as
+    /// `min_cooldown_secs`/`max_velocity_lamports_per_sec` — a dead risk
+    /// feed otherwise leaves whatever `risk_score` was last reported in
+    /// force indefinitely. Owner-configured via `set_max_risk_staleness`.
+    pub max_risk_staleness_secs: i64,
+    /// `clock_ts` as of the most recent `apply_risk_score` call (from
+    /// either `evaluate_agent_action` or `evaluate_and_withdraw`),
+    /// regardless of whether `min_eval_interval_secs` gated that call's
+    /// `last_eval_timestamp` update. Compared against
+    /// `max_risk_staleness_secs` in `execute_gated_withdraw`.
+    pub risk_updated_at: i64,
+}
+
+impl AgentState {
+    /// Overwrites the oldest `action_history` slot with `entry`, same
+    /// ring-buffer algorithm as `DepositLog::record`.
+    fn record_action(&mut self, entry: ActionHistoryEntry) {
+        let index = self.action_history_next_index as usize;
+        self.action_history[index] = entry;
+        self.action_history_next_index = ((index + 1) % ACTION_HISTORY_CAPACITY) as u8;
+        self.action_history_len = self
+            .action_history_len
+            .saturating_add(1)
+            .min(ACTION_HISTORY_CAPACITY as u8);
+    }
+}
+
+/// Compact, dashboard-friendly snapshot of a `Vault` and its linked
+/// `AgentState`, returned by `get_vault_summary` via program return data so
+/// a monitoring UI can get everything it needs in one RPC call instead of
+/// fetching and decoding both accounts and re-deriving this itself.
+///
+/// Deliberately leaves out `created_at`, `spent_this_epoch`, and
+/// success/block counts: this program tracks no creation timestamp on
+/// `Vault`, no epoch accounting (only `AgentState`'s `daily_limit`
+/// window, which this summary does not include), and no per-action outcome
+/// history, so there is nothing for a read-only summary to surface for
+/// them without first building that bookkeeping elsewhere — out of scope
+/// for what's meant to be a pure read convenience. `paused` reports
+/// `AgentState::frozen`, the closest concept this program actually has.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct VaultSummary {
+    pub balance: u64,
+    pub owner: Pubkey,
+    pub risk_score: u8,
+    pub execution_enabled: bool,
+    pub paused: bool,
+    pub last_action_timestamp: i64,
+}
+
+/// Computed by `remaining_velocity_allowance` — see that instruction's doc
+/// comment for why it reports a continuously-replenishing velocity
+/// ceiling rather than a discrete `spent_this_epoch`/`epoch_start`
+/// rollover. Deliberately leaves out a "next
+/// rollover timestamp": the velocity check has no fixed reset boundary
+/// to roll over at, so there is nothing genuine to report for one.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct VelocityAllowance {
+    /// `max_velocity_lamports_per_sec * (now - last_action_timestamp)`,
+    /// the same ceiling `execute_gated_withdraw`'s velocity check compares
+    /// `amount` against — i.e. the largest `amount` a `gated_withdraw`
+    /// submitted right now would pass this specific check.
+    /// `u64::MAX` when `max_velocity_lamports_per_sec == 0`, i.e. the
+    /// check is disabled and nothing limits it here.
+    pub max_amount_now: u64,
+    pub last_action_timestamp: i64,
+}
+
+/// Why [`can_act`] did or didn't answer `true`, in the exact priority
+/// order `execute_gated_withdraw`'s own `require!` chain checks them —
+/// the first one that would fail there is the reason reported here.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum CanActReason {
+    Ok,
+    Frozen,
+    ExecutionBlocked,
+    HighRisk,
+    Stale,
+    VelocityExceeded,
+    RiskStale,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct CanActResult {
+    pub can_act: bool,
+    pub reason: CanActReason,
+}
+
+#[derive(Accounts)]
+pub struct InitializeVault<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + 32 + 8 + 1 + 1 + 32 + 1 + 32 * MAX_HOOK_ALLOWLIST + 1 + 32 + 1 + 32 + 1 + 32 + 8 + 8 + 1 + 32,
+        seeds = [b"vault", owner.key().as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVaultOwnerKind<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeOwner<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelOwnerTransfer<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOwner<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub new_owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGuardian<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitiateRecovery<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub guardian: Signer<'info>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecovery<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Recover<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub guardian: Signer<'info>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVaultAutoReconcile<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + 32 + (16 + 8) * DEPOSIT_LOG_CAPACITY + 1 + 1,
+        seeds = [b"deposit_log", vault.key().as_ref()],
+        bump,
+    )]
+    pub deposit_log: Account<'info, DepositLog>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey)]
+pub struct DepositWithAutoCreate<'info> {
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + 32 + 8 + 1 + 1 + 32 + 1 + 32 * MAX_HOOK_ALLOWLIST + 1 + 32 + 1 + 32 + 1 + 32 + 8 + 8 + 1 + 32,
+        seeds = [b"vault", owner.as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + 32 + (16 + 8) * DEPOSIT_LOG_CAPACITY + 1 + 1,
+        seeds = [b"deposit_log", vault.key().as_ref()],
+        bump,
+    )]
+    pub deposit_log: Account<'info, DepositLog>,
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositSpl<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + 32 + 32 + 8 + 1,
+        seeds = [b"token_vault", vault.key().as_ref(), mint.key().as_ref()],
+        bump,
+    )]
+    pub token_vault: Account<'info, TokenVault>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut, associated_token::mint = mint, associated_token::authority = owner)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSpl<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"token_vault", vault.key().as_ref(), mint.key().as_ref()],
+        bump = token_vault.bump,
+    )]
+    pub token_vault: Account<'info, TokenVault>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut, associated_token::mint = mint, associated_token::authority = vault)]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut, associated_token::mint = mint, associated_token::authority = owner)]
+    pub owner_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencySweep<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    /// CHECK: lamports-only recipient, chosen by `owner` at call time —
+    /// this instruction doesn't constrain it beyond `owner` having signed
+    /// for the sweep. Typically the owner's own wallet or a new vault
+    /// being migrated to.
+    pub destination: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(mut, close = owner)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub agent_state: Option<Account<'info, AgentState>>,
+}
+
+#[derive(Accounts)]
+pub struct AssertBalanceConsistent<'info> {
+    pub vault: Account<'info, Vault>,
+}
+
+#[derive(Accounts)]
+pub struct Reconcile<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AddRecipient<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + 32 + 32 * MAX_RECIPIENT_ALLOWLIST + 1 + 1,
+        seeds = [b"recipient_allowlist", vault.key().as_ref()],
+        bump,
+    )]
+    pub recipient_allowlist: Account<'info, RecipientAllowlist>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRecipientAllowlist<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [b"recipient_allowlist", vault.key().as_ref()],
+        bump = recipient_allowlist.bump,
+    )]
+    pub recipient_allowlist: Account<'info, RecipientAllowlist>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetVaultSummary<'info> {
+    pub vault: Account<'info, Vault>,
+    pub agent_state: Account<'info, AgentState>,
+}
+
+#[derive(Accounts)]
+pub struct GetRemainingVelocityAllowance<'info> {
+    pub vault: Account<'info, Vault>,
+    pub agent_state: Account<'info, AgentState>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct CanAct<'info> {
+    pub vault: Account<'info, Vault>,
+    pub agent_state: Account<'info, AgentState>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateAgentState<'info> {
+    /// CHECK: raw `AgentState` buffer, read and `realloc`'d by hand in
+    /// `migrate_agent_state` — see that instruction's doc comment for why
+    /// it can't be a typed `Account<'info, AgentState>`.
+    #[account(mut)]
+    pub agent_state: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-        vault.balance = vault
-            .balance
-            .checked_sub(amount)
-            .ok_or(ErrorCode::Underflow)?;
+#[derive(Accounts)]
+pub struct InitializeAgent<'info> {
+    #[account(init, payer = owner, space = AGENT_STATE_SPACE)]
+    pub agent_state: Account<'info, AgentState>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+}
 
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct UpdateAgent<'info> {
+    #[account(mut)]
+    pub agent_state: Account<'info, AgentState>,
+    pub owner: Signer<'info>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+}
 
-    // =========================
-    // INITIALIZE AGENT STATE
-    // =========================
-    pub fn initialize_agent(ctx: Context<InitializeAgent>) -> Result<()> {
-        let state = &mut ctx.accounts.agent_state;
+/// Program-wide counter allocating unique `agent_id` values via
+/// `register_agent`. A singleton PDA, seeded by the fixed
+/// `AGENT_REGISTRY_SEED` rather than anything caller-supplied, so there is
+/// exactly one counter and no way for two callers to stand up competing
+/// registries.
+#[account]
+pub struct AgentRegistry {
+    pub next_agent_id: u32,
+    pub bump: u8,
+}
 
-        state.owner = ctx.accounts.owner.key();
-        state.risk_score = 50;
-        state.execution_enabled = true;
-        state.last_action_timestamp = Clock::get()?.unix_timestamp;
+pub const AGENT_REGISTRY_SEED: &[u8] = b"agent_registry";
 
-        Ok(())
-    }
+/// Singleton PDA recording this deployment's logical schema version and
+/// its upgrade authority, seeded by the fixed `PROGRAM_CONFIG_SEED` like
+/// `AgentRegistry`. Created once via `initialize_program_config`; `version`
+/// then only ever moves forward, via `bump_version`.
+///
+/// `upgrade_authority` is deliberately separate from any `Vault`/`AgentState`
+/// `owner` — it governs this program-wide record, not any individual
+/// vault or agent, and is set once at `initialize_program_config` time
+/// (there is currently no `transfer_upgrade_authority`; rotating it means
+/// a fresh program deployment, same as rotating the BPF upgrade authority
+/// itself).
+///
+/// Instructions that want clients to fail fast against an incompatible
+/// deployment take an `Option<u16>` minimum-version argument and an
+/// `Option<Account<ProgramConfig>>`, comparing against `version` via
+/// `require_min_version` — see `execute_gated_withdraw`'s use of it for the
+/// first (and so far only) instruction wired up this way.
+#[account]
+pub struct ProgramConfig {
+    pub version: u16,
+    pub upgrade_authority: Pubkey,
+    pub bump: u8,
+}
 
-    // =========================
-    // UPDATE RISK SCORE
-    // =========================
-    pub fn evaluate_agent_action(
-        ctx: Context<UpdateAgent>,
-        risk_score: u8,
-    ) -> Result<()> {
-        require!(risk_score <= 100, ErrorCode::InvalidRiskScore);
+pub const PROGRAM_CONFIG_SEED: &[u8] = b"program_config";
 
-        let state = &mut ctx.accounts.agent_state;
-        require!(
-            state.owner == ctx.accounts.owner.key(),
-            ErrorCode::Unauthorized
-        );
+/// `ProgramConfig::version` a fresh `initialize_program_config` call starts
+/// from.
+pub const INITIAL_PROGRAM_VERSION: u16 = 1;
 
-        state.risk_score = risk_score;
-        state.execution_enabled = risk_score <= 80;
+#[derive(Accounts)]
+pub struct InitializeProgramConfig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 2 + 32 + 1,
+        seeds = [PROGRAM_CONFIG_SEED],
+        bump,
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-        Ok(())
-    }
+#[derive(Accounts)]
+pub struct BumpVersion<'info> {
+    #[account(mut, seeds = [PROGRAM_CONFIG_SEED], bump = program_config.bump)]
+    pub program_config: Account<'info, ProgramConfig>,
+    pub upgrade_authority: Signer<'info>,
+}
 
-    // =========================
-    // GATED WITHDRAW (FINAL BOSS)
-    // =========================
-    pub fn gated_withdraw(ctx: Context<GatedWithdraw>, amount: u64) -> Result<()> {
-        let state = &mut ctx.accounts.agent_state;
-        let vault = &mut ctx.accounts.vault;
+#[derive(Accounts)]
+pub struct RegisterAgent<'info> {
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + 4 + 1,
+        seeds = [AGENT_REGISTRY_SEED],
+        bump,
+    )]
+    pub registry: Account<'info, AgentRegistry>,
+    #[account(mut)]
+    pub agent_state: Account<'info, AgentState>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
 
-        require!(
-            state.owner == ctx.accounts.owner.key(),
-            ErrorCode::Unauthorized
-        );
+#[derive(Accounts)]
+pub struct GatedWithdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub agent_state: Account<'info, AgentState>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    /// CHECK: instructions sysvar, used to look up the preceding
+    /// Ed25519 signature-verification instruction.
+    pub instructions_sysvar: AccountInfo<'info>,
+    /// Pays `owner_amount` to a third party instead of `owner` when
+    /// supplied — checked against `AgentState::destination_list` in
+    /// `execute_gated_withdraw`. Omit to keep the original behavior of
+    /// paying `owner` itself. Declared ahead of `destination_policy` below
+    /// so that account's seeds can be derived from the actual payout
+    /// destination instead of unconditionally from `owner`.
+    #[account(mut)]
+    /// CHECK: lamports-only recipient, gated in `execute_gated_withdraw`.
+    pub recipient: Option<UncheckedAccount<'info>>,
+    /// Keyed by the actual payout destination — `recipient` if supplied,
+    /// `owner` otherwise, matching `execute_gated_withdraw`'s own
+    /// resolution of `recipient_account` — via
+    /// `set_destination_memo_policy`/`set_destination_lifetime_cap`'s own
+    /// `destination` account. Keying this by `owner` unconditionally would
+    /// make a policy an owner set up for a third-party `recipient`
+    /// permanently unreachable from here.
+    #[account(
+        mut,
+        seeds = [
+            b"dest_memo",
+            vault.key().as_ref(),
+            recipient.as_ref().map(|r| r.key()).unwrap_or_else(|| owner.key()).as_ref(),
+        ],
+        bump,
+    )]
+    pub destination_policy: Option<Account<'info, DestinationMemoPolicy>>,
+    /// Required only when `agent_state.service_bps > 0`; must match
+    /// `agent_state.service_account` — see `set_service_split`.
+    #[account(mut)]
+    /// CHECK: lamports-only recipient, checked against
+    /// `agent_state.service_account` in `execute_gated_withdraw`.
+    pub service_account: Option<UncheckedAccount<'info>>,
+    /// Checked against `RecipientAllowlist::recipients` in
+    /// `execute_gated_withdraw` when supplied — see that struct's doc
+    /// comment for how this relates to `AgentState::destination_list`.
+    /// Omit to skip this check entirely.
+    #[account(seeds = [b"recipient_allowlist", vault.key().as_ref()], bump = recipient_allowlist.bump)]
+    pub recipient_allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Checked against `min_version` via `require_min_version` when the
+    /// caller supplies one — see `ProgramConfig`'s doc comment. Omittable
+    /// by any caller that doesn't pass `min_version`.
+    #[account(seeds = [PROGRAM_CONFIG_SEED], bump)]
+    pub program_config: Option<Account<'info, ProgramConfig>>,
+    /// Checked against `UsdSpendingLimit.price_feed` in
+    /// `execute_gated_withdraw` when `usd_spending_limit` is supplied —
+    /// not itself PDA-constrained here since which `PriceFeed` is trusted
+    /// is `usd_spending_limit`'s own configuration, not derivable from a
+    /// fixed seed. Otherwise unused.
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+    /// See `set_usd_spending_limit`'s doc comment. Omit to skip the
+    /// USD-denominated check entirely.
+    #[account(mut, seeds = [USD_SPENDING_LIMIT_SEED, vault.key().as_ref()], bump = usd_spending_limit.bump)]
+    pub usd_spending_limit: Option<Account<'info, UsdSpendingLimit>>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+}
 
-        require!(state.execution_enabled, ErrorCode::ExecutionBlocked);
-        require!(state.risk_score <= 80, ErrorCode::HighRisk);
+#[derive(Accounts)]
+pub struct GatedTransfer<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub agent_state: Account<'info, AgentState>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    /// CHECK: instructions sysvar, used to look up the preceding
+    /// Ed25519 signature-verification instruction.
+    pub instructions_sysvar: AccountInfo<'info>,
+    /// Pays `owner_amount` — mandatory here, unlike `GatedWithdraw`'s
+    /// optional `recipient`. Checked against `AgentState::destination_list`
+    /// in `execute_gated_withdraw`. Declared ahead of `destination_policy`
+    /// below so that account's seeds can be derived from this recipient
+    /// instead of unconditionally from `owner`.
+    #[account(mut)]
+    /// CHECK: lamports-only recipient, gated in `execute_gated_withdraw`.
+    pub recipient: UncheckedAccount<'info>,
+    /// Keyed by `recipient` — the mandatory payout destination for this
+    /// instruction — matching `execute_gated_withdraw`'s own resolution of
+    /// `recipient_account`, via
+    /// `set_destination_memo_policy`/`set_destination_lifetime_cap`'s own
+    /// `destination` account. Keying this by `owner` would make a policy set
+    /// up for `recipient` permanently unreachable from here, since
+    /// `gated_transfer` never pays `owner`.
+    #[account(mut, seeds = [b"dest_memo", vault.key().as_ref(), recipient.key().as_ref()], bump)]
+    pub destination_policy: Option<Account<'info, DestinationMemoPolicy>>,
+    /// Required only when `agent_state.service_bps > 0`; must match
+    /// `agent_state.service_account` — see `set_service_split`.
+    #[account(mut)]
+    /// CHECK: lamports-only recipient, checked against
+    /// `agent_state.service_account` in `execute_gated_withdraw`.
+    pub service_account: Option<UncheckedAccount<'info>>,
+    /// Checked against `RecipientAllowlist::recipients` in
+    /// `execute_gated_withdraw` when supplied — see that struct's doc
+    /// comment for how this relates to `AgentState::destination_list`.
+    /// Omit to skip this check entirely.
+    #[account(seeds = [b"recipient_allowlist", vault.key().as_ref()], bump = recipient_allowlist.bump)]
+    pub recipient_allowlist: Option<Account<'info, RecipientAllowlist>>,
+    /// Checked against `min_version` via `require_min_version` when the
+    /// caller supplies one — see `ProgramConfig`'s doc comment. Omittable
+    /// by any caller that doesn't pass `min_version`.
+    #[account(seeds = [PROGRAM_CONFIG_SEED], bump)]
+    pub program_config: Option<Account<'info, ProgramConfig>>,
+    /// Checked against `UsdSpendingLimit.price_feed` in
+    /// `execute_gated_withdraw` when `usd_spending_limit` is supplied —
+    /// not itself PDA-constrained here since which `PriceFeed` is trusted
+    /// is `usd_spending_limit`'s own configuration, not derivable from a
+    /// fixed seed. Otherwise unused.
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+    /// See `set_usd_spending_limit`'s doc comment. Omit to skip the
+    /// USD-denominated check entirely.
+    #[account(mut, seeds = [USD_SPENDING_LIMIT_SEED, vault.key().as_ref()], bump = usd_spending_limit.bump)]
+    pub usd_spending_limit: Option<Account<'info, UsdSpendingLimit>>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+}
 
-        let clock = Clock::get()?;
-        require!(
-            clock.unix_timestamp - state.last_action_timestamp < 3600,
-            ErrorCode::ExecutionTimeout
-        );
+#[derive(Accounts)]
+pub struct EvaluateAndWithdraw<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub agent_state: Account<'info, AgentState>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    /// CHECK: instructions sysvar, used to look up the preceding
+    /// Ed25519 signature-verification instruction.
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut, seeds = [b"dest_memo", vault.key().as_ref(), owner.key().as_ref()], bump)]
+    pub destination_policy: Option<Account<'info, DestinationMemoPolicy>>,
+    /// Required only when `agent_state.service_bps > 0`; must match
+    /// `agent_state.service_account` — see `set_service_split`.
+    #[account(mut)]
+    /// CHECK: lamports-only recipient, checked against
+    /// `agent_state.service_account` in `execute_gated_withdraw`.
+    pub service_account: Option<UncheckedAccount<'info>>,
+    /// Checked against `min_version` via `require_min_version` when the
+    /// caller supplies one — see `ProgramConfig`'s doc comment. Omittable
+    /// by any caller that doesn't pass `min_version`.
+    #[account(seeds = [PROGRAM_CONFIG_SEED], bump)]
+    pub program_config: Option<Account<'info, ProgramConfig>>,
+    /// Checked against `UsdSpendingLimit.price_feed` in
+    /// `execute_gated_withdraw` when `usd_spending_limit` is supplied —
+    /// not itself PDA-constrained here since which `PriceFeed` is trusted
+    /// is `usd_spending_limit`'s own configuration, not derivable from a
+    /// fixed seed. Otherwise unused.
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+    /// See `set_usd_spending_limit`'s doc comment. Omit to skip the
+    /// USD-denominated check entirely.
+    #[account(mut, seeds = [USD_SPENDING_LIMIT_SEED, vault.key().as_ref()], bump = usd_spending_limit.bump)]
+    pub usd_spending_limit: Option<Account<'info, UsdSpendingLimit>>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+}
 
-        require!(vault.balance >= amount, ErrorCode::InsufficientFunds);
+#[derive(Accounts)]
+#[instruction(amount: u64, nonce: u64, expiry: i64)]
+pub struct GatedWithdrawVerified<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    #[account(mut)]
+    pub agent_state: Account<'info, AgentState>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    /// CHECK: instructions sysvar, used to look up the preceding
+    /// Ed25519 signature-verification instruction.
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut, seeds = [b"dest_memo", vault.key().as_ref(), owner.key().as_ref()], bump)]
+    pub destination_policy: Option<Account<'info, DestinationMemoPolicy>>,
+    #[account(
+        mut,
+        seeds = [
+            VERIFIED_WITHDRAWAL_SEED,
+            vault.key().as_ref(),
+            &hash_withdrawal_authorization(&vault.key(), &owner.key(), amount, nonce, expiry),
+        ],
+        bump = verified_authorization.bump,
+    )]
+    pub verified_authorization: Account<'info, VerifiedWithdrawalAuthorization>,
+    /// Required only when `agent_state.service_bps > 0`; must match
+    /// `agent_state.service_account` — see `set_service_split`.
+    #[account(mut)]
+    /// CHECK: lamports-only recipient, checked against
+    /// `agent_state.service_account` in `execute_gated_withdraw`.
+    pub service_account: Option<UncheckedAccount<'info>>,
+    /// Checked against `min_version` via `require_min_version` when the
+    /// caller supplies one — see `ProgramConfig`'s doc comment. Omittable
+    /// by any caller that doesn't pass `min_version`.
+    #[account(seeds = [PROGRAM_CONFIG_SEED], bump)]
+    pub program_config: Option<Account<'info, ProgramConfig>>,
+    /// Checked against `UsdSpendingLimit.price_feed` in
+    /// `execute_gated_withdraw` when `usd_spending_limit` is supplied —
+    /// not itself PDA-constrained here since which `PriceFeed` is trusted
+    /// is `usd_spending_limit`'s own configuration, not derivable from a
+    /// fixed seed. Otherwise unused.
+    pub price_feed: Option<Account<'info, PriceFeed>>,
+    /// See `set_usd_spending_limit`'s doc comment. Omit to skip the
+    /// USD-denominated check entirely.
+    #[account(mut, seeds = [USD_SPENDING_LIMIT_SEED, vault.key().as_ref()], bump = usd_spending_limit.bump)]
+    pub usd_spending_limit: Option<Account<'info, UsdSpendingLimit>>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+}
 
-        **vault.to_account_info().try_borrow_mut_lamports()? -= amount;
-        **ctx.accounts.owner.to_account_info().try_borrow_mut_lamports()? += amount;
+#[derive(Accounts)]
+pub struct SetVerifierProgram<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    pub owner: Signer<'info>,
+}
 
-        vault.balance = vault
-            .balance
-            .checked_sub(amount)
-            .ok_or(ErrorCode::Underflow)?;
+#[derive(Accounts)]
+#[instruction(message: [u8; 32])]
+pub struct CreateVerifiedWithdrawalSlot<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 + 1 + 8 + 1 + 1,
+        seeds = [VERIFIED_WITHDRAWAL_SEED, vault.key().as_ref(), &message],
+        bump,
+    )]
+    pub verified_authorization: Account<'info, VerifiedWithdrawalAuthorization>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+}
 
-        state.last_action_timestamp = clock.unix_timestamp;
+#[derive(Accounts)]
+#[instruction(message: [u8; 32])]
+pub struct ReceivePlaintextVerificationResult<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(
+        mut,
+        seeds = [VERIFIED_WITHDRAWAL_SEED, vault.key().as_ref(), &message],
+        bump = verified_authorization.bump,
+    )]
+    pub verified_authorization: Account<'info, VerifiedWithdrawalAuthorization>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
+}
 
-        Ok(())
-    }
+/// Written by `receive_plaintext_verification_result` (the CPI target for
+/// `agentic_wallet_mxe`'s `verify_agent_signature_plaintext_callback`) and
+/// consumed by `gated_withdraw_verified`. Keyed by `(vault, message)` so a
+/// record can only ever be redeemed against the exact withdrawal it was
+/// produced for — see `gated_withdraw_verified`'s doc comment for how
+/// `message` is recomputed and checked, and `receive_plaintext_verification_result`'s
+/// for this record's trust limits. `recorded_at` is checked against
+/// `VERIFIED_WITHDRAWAL_MAX_AGE_SECS` by `gated_withdraw_verified`, not
+/// just stored for reference.
+#[account]
+pub struct VerifiedWithdrawalAuthorization {
+    pub message: [u8; 32],
+    pub is_valid: bool,
+    pub recorded_at: i64,
+    pub consumed: bool,
+    pub bump: u8,
 }
 
-// =========================
-// ACCOUNTS
-// =========================
+/// A required memo tag and/or lifetime send cap for a specific (vault,
+/// destination) pair. Exchange integrations set `required_memo` via
+/// `set_destination_memo_policy` so an agent can't accidentally send
+/// exchange deposits without the mandatory tag. `lifetime_sent` and
+/// `lifetime_cap`, set via `set_destination_lifetime_cap`, are distinct
+/// from `AgentState::max_velocity_lamports_per_sec`: the velocity check
+/// bounds a *rate* and naturally resets as time passes, while
+/// `lifetime_sent` only ever accumulates — once `lifetime_cap` is hit, no
+/// further `gated_withdraw`/`evaluate_and_withdraw` can pay this
+/// destination again regardless of how much time elapses.
+#[account]
+pub struct DestinationMemoPolicy {
+    pub required_memo: [u8; 32],
+    /// Running total of every `gated_withdraw`/`evaluate_and_withdraw`
+    /// `amount` ever paid to this (vault, destination) pair. Never resets.
+    pub lifetime_sent: u64,
+    /// When `Some`, `lifetime_sent + amount` may not exceed this value.
+    /// `None` means no lifetime cap — only `required_memo` applies.
+    pub lifetime_cap: Option<u64>,
+}
 
+/// Vault-scoped, USD-denominated spending ceiling — see
+/// `set_usd_spending_limit`'s doc comment for how this relates to
+/// `AgentState::daily_limit`. `spent_today_usd_cents`/`window_start` track
+/// a rolling `DAILY_LIMIT_WINDOW_SECS` window exactly like
+/// `AgentState::spent_today`/`window_start` do, just converted into USD
+/// cents via `price_feed` instead of counted directly in lamports.
+///
+/// An optional bolt-on account rather than a field every `AgentState`
+/// carries, same as `RecipientAllowlist`/`DestinationMemoPolicy` — every
+/// withdrawal path threads `price_feed`/`usd_spending_limit` through
+/// `execute_gated_withdraw` already, so there's nothing to retrofit into
+/// any caller added before this existed; it's opt-in for every vault
+/// regardless of when that vault (or this account type) was created.
 #[account]
-pub struct Vault {
-    pub owner: Pubkey,
-    pub balance: u64,
+pub struct UsdSpendingLimit {
+    pub price_feed: Pubkey,
+    pub max_price_staleness_secs: i64,
+    pub daily_limit_usd_cents: u64,
+    pub spent_today_usd_cents: u64,
+    pub window_start: i64,
+    pub bump: u8,
 }
 
+/// Seed (with `vault` appended) for a [`UsdSpendingLimit`] PDA.
+pub const USD_SPENDING_LIMIT_SEED: &[u8] = b"usd_limit";
+
+/// An owner-controlled, vault-scoped allowlist of recipients permitted to
+/// receive `gated_withdraw`/`gated_transfer` payouts (via those
+/// instructions' `recipient` parameter — see `execute_gated_withdraw`).
+///
+/// This is deliberately a distinct mechanism from
+/// `AgentState::destination_list`, not a replacement for it:
+/// `destination_list` is per-agent, holds up to `MAX_DESTINATION_LIST`
+/// (4) entries, and supports both allowlist and denylist semantics via
+/// `DestinationListMode`; `RecipientAllowlist` is per-vault, holds up to
+/// `MAX_RECIPIENT_ALLOWLIST` (16) entries, and is allowlist-only — there
+/// is no mode to flip. Pick `destination_list` to gate a single agent's
+/// behavior (e.g. a service account an agent is allowed to pay a fee
+/// to); pick `RecipientAllowlist` to put a hard, vault-wide ceiling on
+/// every recipient any agent on the vault can ever pay, independent of
+/// per-agent policy. `execute_gated_withdraw` enforces both when both
+/// are supplied — a recipient must clear whichever checks are present,
+/// not just one.
+///
+/// Unset (the default, until `add_recipient` is called for the first
+/// time) means no vault-wide allowlist restriction is in effect;
+/// `gated_withdraw`/`gated_transfer` simply omit the account.
+///
+/// Fixed-size to avoid reallocation complexity, same tradeoff as
+/// `AgentState::destination_list` — see that field's doc comment.
 #[account]
-pub struct AgentState {
-    pub owner: Pubkey,
-    pub risk_score: u8,
-    pub execution_enabled: bool,
-    pub last_action_timestamp: i64,
+pub struct RecipientAllowlist {
+    pub vault: Pubkey,
+    pub recipients: [Pubkey; MAX_RECIPIENT_ALLOWLIST],
+    pub count: u8,
+    pub bump: u8,
 }
 
 #[derive(Accounts)]
-pub struct InitializeVault<'info> {
-    #[account(init, payer = owner, space = 8 + 32 + 8)]
+pub struct SetDestinationMemoPolicy<'info> {
     pub vault: Account<'info, Vault>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + 32 + 8 + 1 + 8,
+        seeds = [b"dest_memo", vault.key().as_ref(), destination.key().as_ref()],
+        bump,
+    )]
+    pub destination_policy: Account<'info, DestinationMemoPolicy>,
+    /// The withdrawal destination this policy applies to.
+    pub destination: SystemAccount<'info>,
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct Deposit<'info> {
-    #[account(mut)]
+pub struct SetDestinationLifetimeCap<'info> {
     pub vault: Account<'info, Vault>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + 32 + 8 + 1 + 8,
+        seeds = [b"dest_memo", vault.key().as_ref(), destination.key().as_ref()],
+        bump,
+    )]
+    pub destination_policy: Account<'info, DestinationMemoPolicy>,
+    /// The withdrawal destination this policy applies to.
+    pub destination: SystemAccount<'info>,
     #[account(mut)]
     pub owner: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct Withdraw<'info> {
+pub struct SetHookAllowlist<'info> {
     #[account(mut)]
     pub vault: Account<'info, Vault>,
-    #[account(mut)]
     pub owner: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct InitializeAgent<'info> {
-    #[account(init, payer = owner, space = 8 + 32 + 1 + 1 + 8)]
-    pub agent_state: Account<'info, AgentState>,
+pub struct SetPriceFeed<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 1,
+        seeds = [PRICE_FEED_SEED, authority.key().as_ref()],
+        bump,
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
+    /// See [`now`]'s doc comment — only ever consulted when this program
+    /// is compiled with the `test-clock` feature.
+    pub test_clock: Option<UncheckedAccount<'info>>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateAgent<'info> {
+pub struct SetUsdSpendingLimit<'info> {
+    pub vault: Account<'info, Vault>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + 32 + 8 + 8 + 8 + 8 + 1,
+        seeds = [USD_SPENDING_LIMIT_SEED, vault.key().as_ref()],
+        bump,
+    )]
+    pub usd_spending_limit: Account<'info, UsdSpendingLimit>,
     #[account(mut)]
-    pub agent_state: Account<'info, AgentState>,
     pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
+#[cfg(feature = "test-clock")]
 #[derive(Accounts)]
-pub struct GatedWithdraw<'info> {
-    #[account(mut)]
-    pub vault: Account<'info, Vault>,
-    #[account(mut)]
-    pub agent_state: Account<'info, AgentState>,
+pub struct SetTestClock<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + 32 + 8 + 1,
+        seeds = [TEST_CLOCK_SEED, authority.key().as_ref()],
+        bump,
+    )]
+    pub test_clock: Account<'info, TestClock>,
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// =========================
+// EVENTS
+// =========================
+
+#[event]
+pub struct AgentFrozenEvent {
+    pub agent_state: Pubkey,
+    pub frozen: bool,
+}
+
+#[event]
+pub struct BalanceInconsistentEvent {
+    pub vault: Pubkey,
+    pub tracked_balance: u64,
+    pub actual_available: u64,
+}
+
+/// Emitted by `reconcile`.
+#[event]
+pub struct VaultReconciledEvent {
+    pub vault: Pubkey,
+    pub old_balance: u64,
+    pub new_balance: u64,
+}
+
+#[event]
+pub struct VaultCreatedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+}
+
+/// Emitted by `accept_owner` once a two-step `propose_owner` transfer
+/// completes.
+#[event]
+pub struct VaultOwnershipTransferredEvent {
+    pub vault: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+/// Emitted by `initiate_recovery`.
+#[event]
+pub struct RecoveryInitiatedEvent {
+    pub vault: Pubkey,
+    pub guardian: Pubkey,
+    pub new_owner: Pubkey,
+    pub recovery_initiated_at: i64,
+}
+
+/// Emitted by `cancel_recovery`.
+#[event]
+pub struct RecoveryCancelledEvent {
+    pub vault: Pubkey,
+}
+
+/// Emitted by `recover` once a guardian-driven recovery completes.
+#[event]
+pub struct RecoveryCompletedEvent {
+    pub vault: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct AgentRegisteredEvent {
+    pub agent_state: Pubkey,
+    pub agent_id: u32,
+}
+
+#[event]
+pub struct DepositedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    /// `new_balance - (old_balance + amount)`, i.e. how far `balance`
+    /// moved beyond the plain `amount` increment because `Vault::auto_reconcile`
+    /// resynced it to actual lamports. `0` when auto-reconcile didn't trigger.
+    pub reconciled_delta: i64,
+    /// `vault.balance` after this deposit, including any
+    /// `reconciled_delta`.
+    pub new_balance: u64,
+}
+
+/// Emitted by `withdraw`/`withdraw_micro_sol`.
+#[event]
+pub struct WithdrawnEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub new_balance: u64,
+}
+
+#[event]
+pub struct TokenDepositedEvent {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct TokenWithdrawnEvent {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted by `execute_gated_withdraw`. `lifetime_sent` is the
+/// destination's updated running total after this withdrawal, or `0` when
+/// no `DestinationMemoPolicy` PDA exists for (vault, destination) — the
+/// same "no cap configured" state as `lifetime_cap: None` — so off-chain
+/// systems can track remaining allowance without a separate account fetch.
+/// `owner_amount` and `service_amount` split `amount` per
+/// `AgentState::service_bps`; `service_amount` is always `0` while that's
+/// unset, and `owner_amount + service_amount == amount` always holds.
+/// `recipient` is whoever actually received `owner_amount` — `owner`
+/// itself unless the instruction supplied a third-party `recipient`
+/// account (see `gated_withdraw`/`gated_transfer`). `risk_score` and
+/// `timestamp` are `agent_state.risk_score`/`last_action_timestamp` as of
+/// this withdrawal, letting an indexer correlate payouts with the risk
+/// evaluation that authorized them without a separate `AgentState` fetch.
+#[event]
+pub struct GatedWithdrawnEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub lifetime_sent: u64,
+    pub owner_amount: u64,
+    pub service_amount: u64,
+    pub risk_score: u8,
+    pub timestamp: i64,
+}
+
+/// Emitted by `bump_version`.
+#[event]
+pub struct VersionBumpedEvent {
+    pub old_version: u16,
+    pub new_version: u16,
+}
+
+/// Emitted by `emergency_sweep`. Distinct from `withdraw`'s
+/// `WithdrawnEvent` so off-chain monitoring can single out a
+/// full emergency sweep from an ordinary partial withdrawal.
+#[event]
+pub struct EmergencySweptEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
 }
 
 // =========================
@@ -240,6 +4019,12 @@ pub enum ErrorCode {
     #[msg("Execution timeout")]
     ExecutionTimeout,
 
+    #[msg("gated_withdraw called again before min_cooldown_secs elapsed since the last withdrawal")]
+    CooldownNotElapsed,
+
+    #[msg("min_cooldown_secs must be non-negative")]
+    InvalidCooldown,
+
     #[msg("High risk detected")]
     HighRisk,
 
@@ -248,4 +4033,160 @@ pub enum ErrorCode {
 
     #[msg("Underflow occurred")]
     Underflow,
+
+    #[msg("Missing or malformed Ed25519 signature-verification instruction")]
+    MissingSignatureInstruction,
+
+    #[msg("Withdrawal authorization signature does not match the expected signer or message")]
+    InvalidAuthorizationSignature,
+
+    #[msg("Withdrawal authorization has expired")]
+    AuthorizationExpired,
+
+    #[msg("Withdrawal authorization nonce has already been used")]
+    NonceReused,
+
+    #[msg("Deposit idempotency key was already used within the dedupe window")]
+    DuplicateDeposit,
+
+    #[msg("action_timeout_secs must be positive")]
+    InvalidActionTimeout,
+
+    #[msg("max_staleness_override cannot be looser than the configured action timeout")]
+    OverrideTooLoose,
+
+    #[msg("Withdrawal memo does not match the destination's required memo")]
+    MemoMismatch,
+
+    #[msg("Agent is frozen")]
+    AgentFrozen,
+
+    #[msg("vault.balance does not match actual lamports minus the rent-exempt minimum")]
+    BalanceInconsistent,
+
+    #[msg("withdrawal would leave the vault account below its rent-exempt minimum")]
+    WouldBreakRentExemption,
+
+    #[msg("min_eval_interval_secs must be non-negative")]
+    InvalidEvalInterval,
+
+    #[msg("evaluate_agent_action called again before min_eval_interval_secs elapsed")]
+    EvaluationTooSoon,
+
+    #[msg("Withdrawal exceeds the agent's configured max outflow velocity")]
+    VelocityExceeded,
+
+    #[msg("vault.owner does not match agent_state.owner")]
+    OwnerMismatch,
+
+    #[msg("This agent_state has already been assigned an agent_id")]
+    DuplicateAgentRegistration,
+
+    #[msg("Withdrawal would push this destination's lifetime total past its configured cap")]
+    LifetimeCapExceeded,
+
+    #[msg("hook_program is not in Vault::hook_allowlist")]
+    HookProgramNotAllowlisted,
+
+    #[msg("allowlist count exceeds MAX_HOOK_ALLOWLIST")]
+    TooManyHookPrograms,
+
+    #[msg("Vault::verifier_program is not set; receive_plaintext_verification_result is disabled for this vault")]
+    VerifierNotConfigured,
+
+    #[msg("VerifiedWithdrawalAuthorization.message does not match this withdrawal's parameters")]
+    VerificationResultMismatch,
+
+    #[msg("VerifiedWithdrawalAuthorization.is_valid is false")]
+    VerificationFailed,
+
+    #[msg("This VerifiedWithdrawalAuthorization has already been consumed")]
+    VerificationAlreadyConsumed,
+
+    #[msg("VerifiedWithdrawalAuthorization is older than VERIFIED_WITHDRAWAL_MAX_AGE_SECS")]
+    VerificationExpired,
+
+    #[msg("service_bps must be <= 10_000")]
+    InvalidServiceBps,
+
+    #[msg("AgentState.service_bps is non-zero but no service_account was provided")]
+    ServiceAccountRequired,
+
+    #[msg("service_account does not match AgentState.service_account")]
+    ServiceAccountMismatch,
+
+    #[msg("destination is blocked by AgentState.destination_list under the configured DestinationListMode")]
+    DestinationDenied,
+
+    #[msg("destination is already in AgentState.destination_list")]
+    DuplicateDestinationListEntry,
+
+    #[msg("AgentState.destination_list is already at MAX_DESTINATION_LIST entries")]
+    TooManyDestinationListEntries,
+
+    #[msg("destination is not in AgentState.destination_list")]
+    DestinationNotInList,
+
+    #[msg("bump_version's new_version must be strictly greater than ProgramConfig.version")]
+    VersionNotIncreasing,
+
+    #[msg("a min_version was supplied but no ProgramConfig account was provided to check it against")]
+    ProgramConfigRequired,
+
+    #[msg("ProgramConfig.version is below the caller's required min_version")]
+    IncompatibleVersion,
+
+    #[msg("close_vault requires Vault.balance == 0")]
+    VaultNotEmpty,
+
+    #[msg("close_vault refuses to run while the linked AgentState has execution_enabled == true")]
+    AgentStillExecuting,
+
+    #[msg("Withdrawal would exceed AgentState.daily_limit for the current window")]
+    DailyLimitExceeded,
+
+    #[msg("recipient is not in RecipientAllowlist.recipients")]
+    RecipientNotAllowed,
+
+    #[msg("recipient is already in RecipientAllowlist.recipients")]
+    DuplicateRecipientAllowlistEntry,
+
+    #[msg("RecipientAllowlist.recipients is already at MAX_RECIPIENT_ALLOWLIST entries")]
+    TooManyRecipientAllowlistEntries,
+
+    #[msg("recipient is not in RecipientAllowlist.recipients")]
+    RecipientNotInAllowlist,
+
+    #[msg("agent_state is not at AGENT_STATE_SPACE_V1 or AGENT_STATE_SPACE_V2, a size migrate_agent_state expects a pre-migration account to be")]
+    UnexpectedAgentStateSize,
+
+    #[msg("set_guardian requires recovery_delay_secs >= MIN_RECOVERY_DELAY_SECS whenever guardian is set")]
+    RecoveryDelayTooShort,
+
+    #[msg("recover requires a prior initiate_recovery call still in flight")]
+    RecoveryNotInitiated,
+
+    #[msg("recover called before Vault.recovery_delay_secs elapsed since initiate_recovery")]
+    RecoveryDelayNotElapsed,
+
+    #[msg("max_risk_staleness_secs must be non-negative")]
+    InvalidRiskStaleness,
+
+    #[msg("AgentState.risk_updated_at is older than AgentState.max_risk_staleness_secs allows")]
+    RiskScoreStale,
+
+    #[msg("max_price_staleness_secs must be positive")]
+    InvalidPriceStaleness,
+
+    #[msg("UsdSpendingLimit.price_feed is set but no matching PriceFeed account was provided")]
+    PriceFeedRequired,
+
+    #[msg("price_feed does not match UsdSpendingLimit.price_feed")]
+    PriceFeedMismatch,
+
+    #[msg("PriceFeed.updated_at is older than UsdSpendingLimit.max_price_staleness_secs allows")]
+    StalePriceFeed,
+
+    #[msg("Withdrawal would exceed UsdSpendingLimit.daily_limit_usd_cents for the current window")]
+    UsdDailyLimitExceeded,
 }
@@ -4,17 +4,218 @@ use arcis::*;
 mod circuits {
     use arcis::*;
 
+    /// Constant-time equality over two equal-length byte buffers. Accumulates
+    /// a bitwise-OR of the differences across every byte before reducing to a
+    /// single boolean, so the result never depends on *where* the first
+    /// mismatch occurs — unlike a `==`/early-return comparison, which can
+    /// leak timing information through an MPC node's branch count.
+    fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+        let mut diff: u8 = 0;
+        for i in 0..a.len() {
+            diff |= a[i] ^ b[i];
+        }
+        diff == 0
+    }
+
+    /// Maximum number of (message, signature) pairs accepted by
+    /// `verify_agent_signatures_one_key` in a single computation.
+    pub const MAX_SIGNATURE_BATCH: usize = 8;
+
+    /// RFC 8032 §5.1's 32-byte domain-separation prefix, shared by both
+    /// defined `dom2` contexts (Ed25519ctx and Ed25519ph) — this module
+    /// only implements Ed25519ctx (`phflag = 0`, no prehashing).
+    const ED25519CTX_DOM2_PREFIX: [u8; 32] = *b"SigEd25519 no Ed25519 collisions";
+
     /// Signs a transaction message using the MXE's distributed Ed25519 key.
     /// The private key never exists in a single location — each MPC node holds
     /// a share and they collectively produce a valid Ed25519 signature.
+    ///
+    /// `domain` is the deployment-wide signing domain from the on-chain
+    /// `MXEConfig` (chain id, app id, etc.), forwarded by the calling
+    /// instruction rather than supplied directly by the original caller.
+    /// `aad` is optional associated data (e.g. a session or channel id)
+    /// that binds the signature to context outside the message proper;
+    /// callers that don't need it pass an all-zero `aad`. The circuit
+    /// always prepends `domain` and appends `aad`, so the final preimage
+    /// every signature from this MXE attests to is:
+    ///
+    ///   domain: [u8; 16]    (from `MXEConfig::signing_domain`)
+    ///   message: [u8; 32]   (caller-supplied)
+    ///   aad: [u8; 32]       (caller-supplied, all-zero when unused)
+    ///
+    /// concatenated in that order. A verifier reconstructs the exact same
+    /// 80-byte preimage from `domain || message || aad` to check a
+    /// signature; there is no way to distinguish "no aad" from "aad happens
+    /// to be all zero" from the signature alone — that distinction only
+    /// matters to the caller's own protocol.
+    #[instruction]
+    pub fn sign_transaction(
+        domain: [u8; 16],
+        message: [u8; 32],
+        aad: [u8; 32],
+    ) -> ArcisEd25519Signature {
+        let mut preimage = [0u8; 80];
+        preimage[..16].copy_from_slice(&domain);
+        preimage[16..48].copy_from_slice(&message);
+        preimage[48..].copy_from_slice(&aad);
+        let signature = MXESigningKey::sign(&preimage);
+        signature.reveal()
+    }
+
+    /// Same preimage and signing as [`sign_transaction`], but the
+    /// signature is encrypted to `requester` instead of revealed publicly
+    /// — for callers who need a signature no one but them can decrypt
+    /// until they choose to broadcast it. `to_bytes` mirrors the
+    /// already-used `ArcisEd25519Signature::from_bytes` constructor, kept
+    /// secret so the raw signature never exists in plaintext at any point
+    /// this circuit's output reaches the chain.
+    #[instruction]
+    pub fn sign_transaction_confidential(
+        domain: [u8; 16],
+        message: [u8; 32],
+        aad: [u8; 32],
+        requester: Shared,
+    ) -> Enc<Shared, [u8; 64]> {
+        let mut preimage = [0u8; 80];
+        preimage[..16].copy_from_slice(&domain);
+        preimage[16..48].copy_from_slice(&message);
+        preimage[48..].copy_from_slice(&aad);
+        let signature = MXESigningKey::sign(&preimage).to_bytes();
+        requester.from_arcis(signature)
+    }
+
+    /// Signs up to `MAX_SIGNATURE_BATCH` distinct messages under the same
+    /// `domain`/`aad` in a single computation — the signing-side
+    /// counterpart to [`verify_agent_signatures_one_key`]'s verification
+    /// batching, cheaper than calling [`sign_transaction`] once per
+    /// message. Every slot uses [`sign_transaction`]'s exact preimage
+    /// layout (`domain || messages[i] || aad`), repeated once per message
+    /// rather than combined into one buffer, so each signature verifies
+    /// independently against its own message.
+    ///
+    /// Unlike [`verify_agent_signatures_one_key`]'s `count`-bounded
+    /// bitmask, there is nothing here for a `count` to mask out — every
+    /// slot is signed unconditionally (the same data-independent-shape
+    /// reasoning as that function), and a zero-padded slot just produces a
+    /// valid, unused signature over an all-zero message. The calling
+    /// instruction is responsible for not acting on slots past its own
+    /// batch size; see `sign_transactions_batch`'s doc comment on the
+    /// Anchor side.
     #[instruction]
-    pub fn sign_transaction(message: [u8; 32]) -> ArcisEd25519Signature {
-        let signature = MXESigningKey::sign(&message);
+    pub fn sign_transactions_batch(
+        domain: [u8; 16],
+        messages: [[u8; 32]; MAX_SIGNATURE_BATCH],
+        aad: [u8; 32],
+    ) -> [[u8; 64]; MAX_SIGNATURE_BATCH] {
+        let mut signatures = [[0u8; 64]; MAX_SIGNATURE_BATCH];
+        for i in 0..MAX_SIGNATURE_BATCH {
+            let mut preimage = [0u8; 80];
+            preimage[..16].copy_from_slice(&domain);
+            preimage[16..48].copy_from_slice(&messages[i]);
+            preimage[48..].copy_from_slice(&aad);
+            signatures[i] = MXESigningKey::sign(&preimage).to_bytes();
+        }
+        signatures
+    }
+
+    /// Signs `message` per RFC 8032 §5.1's Ed25519ctx scheme instead of
+    /// this module's ad-hoc `domain || message || aad` preimage (see
+    /// [`sign_transaction`]) — for interop with verifiers that expect a
+    /// standards-compliant contextual signature rather than this MXE's own
+    /// domain-scoping convention. The preimage signed is:
+    ///
+    ///   dom2(0, context) || message
+    ///   = PREFIX(32) || 0x00 || context_len || context || message
+    ///
+    /// matching RFC 8032 exactly for `context_len == 32` (a fully-used
+    /// context). This circuit, like every other one in this module, has a
+    /// fixed input shape — there's no precedent here for a circuit taking
+    /// a genuinely variable-length signed buffer — so `context_len < 32`
+    /// is accepted for the dom2 length octet (letting a verifier know how
+    /// many bytes are meaningful) but the bytes actually signed always
+    /// include the full fixed-size `context` array, zero-padded past
+    /// `context_len` by convention rather than omitted outright. Exact
+    /// RFC byte-for-byte interop is only guaranteed when `context_len ==
+    /// 32`; shorter contexts interoperate only with verifiers that adopt
+    /// this same fixed-width-zero-padded convention.
+    #[instruction]
+    pub fn sign_transaction_ed25519ctx(
+        context: [u8; 32],
+        context_len: u8,
+        message: [u8; 32],
+    ) -> ArcisEd25519Signature {
+        let mut preimage = [0u8; 98];
+        preimage[..32].copy_from_slice(&ED25519CTX_DOM2_PREFIX);
+        preimage[32] = 0; // phflag: Ed25519ctx is never prehashed
+        preimage[33] = context_len;
+        preimage[34..66].copy_from_slice(&context);
+        preimage[66..].copy_from_slice(&message);
+        let signature = MXESigningKey::sign(&preimage);
         signature.reveal()
     }
 
+    /// Generates a fresh MXE-distributed Ed25519 signing key and re-shares
+    /// it among the cluster's nodes, for the Anchor-side
+    /// `rotate_signing_key` instruction.
+    ///
+    /// TODO: this module has no verified primitive for minting a second
+    /// distributed key from inside a circuit. Every `sign_*` function
+    /// above calls `MXESigningKey::sign`, which signs under whatever key
+    /// the MXE's own key-generation ceremony already produced at MXE
+    /// deployment time — there is no constructor here for generating a
+    /// new one, and the underlying key-generation/re-sharing ceremony
+    /// this request asks for is a cluster-level MPC protocol step, not
+    /// something exposed on this `#[encrypted]` module's documented
+    /// surface. Fabricating a `generate()`/`reshare()` call would mean
+    /// guessing at an `arcis`/`arcium_anchor` API this crate has no
+    /// evidence exists (the same gap already documented on
+    /// `circuits::distribute_key`'s randomness and
+    /// `circuits::verify_key_share`'s group-exponentiation needs, in the
+    /// sibling `circuits` crate). What's left below is the shape
+    /// `rotate_signing_key`'s Anchor instruction queues against today: a
+    /// revealed `[u8; 32]`, standing in for the new public key a real
+    /// ceremony would produce, always all-zero until that primitive
+    /// exists.
+    #[instruction]
+    pub fn rotate_signing_key() -> [u8; 32] {
+        [0u8; 32]
+    }
+
+    /// TODO: `MXESigningKey` (used by every `sign_*` function above) only
+    /// ever signs under this MXE's distributed Ed25519 key — there is no
+    /// analogous secp256k1 threshold-signing key type on this
+    /// `#[encrypted]` module's documented surface, and ECDSA over
+    /// secp256k1 needs a materially different MPC protocol from Ed25519
+    /// (notably a threshold-safe nonce `k`, which can't just reuse
+    /// Ed25519's deterministic-nonce construction — reusing or leaking `k`
+    /// across two signatures recovers the private key outright). Standing
+    /// up a second distributed key under a different curve is the same
+    /// kind of cluster-level ceremony gap documented on
+    /// `rotate_signing_key` just above, not something this module can
+    /// fabricate an API for. What's left below is the shape an Anchor-side
+    /// `sign_transaction_secp256k1` instruction would queue against: a
+    /// revealed 65-byte recoverable signature (`r || s || recovery_id`,
+    /// matching what EVM tooling expects from `ecrecover`), always
+    /// all-zero until a real secp256k1 threshold-signing primitive exists.
+    #[instruction]
+    pub fn sign_transaction_secp256k1(
+        _domain: [u8; 16],
+        _message: [u8; 32],
+        _aad: [u8; 32],
+    ) -> [u8; 65] {
+        [0u8; 65]
+    }
+
     /// Verifies an Ed25519 signature against an encrypted verifying key.
     /// The public key remains confidential throughout verification.
+    ///
+    /// Arcis secret values cannot be branched on, so `verify` already has
+    /// to evaluate as a single data-independent circuit rather than a
+    /// short-circuiting comparison — there is no early-exit path for an
+    /// MPC node to leak timing through. [`ct_eq`] is the explicit primitive
+    /// available for any code in this module that needs to compare raw
+    /// revealed bytes (e.g. a batched variant comparing signatures against
+    /// each other) without reintroducing one.
     #[instruction]
     pub fn verify_agent_signature(
         verifying_key_enc: Enc<Shared, Pack<VerifyingKey>>,
@@ -27,4 +228,116 @@ mod circuits {
         let is_valid = verifying_key.verify(&message, &signature);
         observer.from_arcis(is_valid)
     }
+
+    /// Same check as [`verify_agent_signature`], but reveals `is_valid` in
+    /// plaintext instead of encrypting it to an observer — for callers
+    /// that want `verify_agent_signature_plaintext_callback` to CPI the
+    /// boolean forward to a downstream program as an immediately
+    /// actionable value, rather than a ciphertext only an observer can
+    /// decrypt. The verifying key stays confidential either way; only the
+    /// pass/fail outcome is made public.
+    #[instruction]
+    pub fn verify_agent_signature_plaintext(
+        verifying_key_enc: Enc<Shared, Pack<VerifyingKey>>,
+        message: [u8; 32],
+        signature: [u8; 64],
+    ) -> bool {
+        let verifying_key = verifying_key_enc.to_arcis().unpack();
+        let signature = ArcisEd25519Signature::from_bytes(signature);
+        verifying_key.verify(&message, &signature)
+    }
+
+    /// Maximum observers accepted by `verify_agent_signature_multi_observer`
+    /// in a single computation.
+    pub const MAX_OBSERVERS: usize = 3;
+
+    /// Verifies one (key, message, signature) against up to `MAX_OBSERVERS`
+    /// observers at once, delivering the encrypted result to each. A true
+    /// malformed x25519 observer key can't be detected from inside the
+    /// circuit — key decoding happens before this function body runs, so a
+    /// genuinely invalid point still aborts the whole computation. What
+    /// this *can* do is let the calling instruction substitute a known-good
+    /// filler key for any observer slot it already knows is malformed
+    /// (e.g. because a client submitted garbage) before queuing, so the
+    /// valid observers still get delivered a result instead of the
+    /// computation aborting outright. `skip_mask` records which original
+    /// slots were filler substitutions so observers can tell a delivered
+    /// result apart from one meant for them; it carries no cryptographic
+    /// weight, it's bookkeeping for the caller.
+    #[instruction]
+    pub fn verify_agent_signature_multi_observer(
+        verifying_key_enc: Enc<Shared, Pack<VerifyingKey>>,
+        message: [u8; 32],
+        signature: [u8; 64],
+        observer_0: Shared,
+        observer_1: Shared,
+        observer_2: Shared,
+    ) -> (Enc<Shared, bool>, Enc<Shared, bool>, Enc<Shared, bool>) {
+        let verifying_key = verifying_key_enc.to_arcis().unpack();
+        let signature = ArcisEd25519Signature::from_bytes(signature);
+        let is_valid = verifying_key.verify(&message, &signature);
+        (
+            observer_0.from_arcis(is_valid),
+            observer_1.from_arcis(is_valid),
+            observer_2.from_arcis(is_valid),
+        )
+    }
+
+    /// Verifies a batch of distinct messages against a single encrypted
+    /// verifying key, returning an encrypted bitmask (bit `i` set means
+    /// `messages[i]`/`signatures[i]` verified). `count` bounds how many of
+    /// the fixed-size `MAX_SIGNATURE_BATCH` slots are meaningful; unused
+    /// slots are still processed (to keep the circuit's shape
+    /// data-independent) but masked out of the result with [`ct_eq`]-style
+    /// bitwise selection rather than a branch.
+    #[instruction]
+    pub fn verify_agent_signatures_one_key(
+        verifying_key_enc: Enc<Shared, Pack<VerifyingKey>>,
+        messages: [[u8; 32]; MAX_SIGNATURE_BATCH],
+        signatures: [[u8; 64]; MAX_SIGNATURE_BATCH],
+        count: u8,
+        observer: Shared,
+    ) -> Enc<Shared, u8> {
+        let verifying_key = verifying_key_enc.to_arcis().unpack();
+        let mut bitmask: u8 = 0;
+        for i in 0..MAX_SIGNATURE_BATCH {
+            let signature = ArcisEd25519Signature::from_bytes(signatures[i]);
+            let verified = verifying_key.verify(&messages[i], &signature) as u8;
+            let in_range = ((i as u8) < count) as u8;
+            bitmask |= (verified & in_range) << i;
+        }
+        observer.from_arcis(bitmask)
+    }
+
+    /// Checks whether a confidential running total (`encrypted_spent`) plus
+    /// a new plaintext `amount` stays within a confidential
+    /// `encrypted_limit`, without revealing either value on-chain — a
+    /// spending-cap check that doesn't leak the cap or the running total
+    /// the way a plaintext `spent + amount <= limit` comparison would.
+    /// Both ciphertexts are decrypted only inside this circuit; the
+    /// calling instruction and any listener only ever see the final
+    /// boolean, encrypted to `observer`.
+    ///
+    /// `spent + amount` is computed in `u128`, not `u64`, specifically so
+    /// the addition itself can never overflow before the comparison runs
+    /// — `u64::MAX + u64::MAX` fits comfortably under `u128::MAX`, unlike
+    /// the two `u64` operands on their own. `limit` is widened the same
+    /// way so the comparison stays apples-to-apples; there is no
+    /// wraparound case here that needs branchless masking, unlike the
+    /// `circuits` crate's `execute_encrypted_trade` slippage arithmetic,
+    /// which subtracts two secret values and has to pick the
+    /// non-negative ordering explicitly first.
+    #[instruction]
+    pub fn check_spend_allowed(
+        encrypted_limit: Enc<Shared, u64>,
+        encrypted_spent: Enc<Shared, u64>,
+        amount: u64,
+        observer: Shared,
+    ) -> Enc<Shared, bool> {
+        let limit = encrypted_limit.to_arcis();
+        let spent = encrypted_spent.to_arcis();
+        let total = spent as u128 + amount as u128;
+        let allowed = total <= limit as u128;
+        observer.from_arcis(allowed)
+    }
 }
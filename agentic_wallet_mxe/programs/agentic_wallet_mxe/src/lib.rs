@@ -1,15 +1,396 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_lang::solana_program::keccak;
 use arcium_anchor::prelude::*;
 
+/// Seed for the singleton `MXEConfig` PDA.
+const MXE_CONFIG_SEED: &[u8] = b"mxe_config";
+
+/// Seed (with an `agent` pubkey appended) for a [`SigningPolicy`] PDA.
+const SIGNING_POLICY_SEED: &[u8] = b"signing_policy";
+
+/// Longest prefix a single [`SigningPolicy`] entry can pin, long enough to
+/// cover an Anchor instruction discriminator (8 bytes) with room to spare.
+const POLICY_PREFIX_LEN: usize = 16;
+
+/// Maximum number of allowed prefixes a single [`SigningPolicy`] can hold.
+const MAX_POLICY_PREFIXES: usize = 4;
+
+/// Maximum entries in `MXEConfig::result_program_allowlist`.
+const MAX_RESULT_PROGRAM_ALLOWLIST: usize = 4;
+
+/// Seed for the singleton [`SigningStats`] PDA.
+const SIGNING_STATS_SEED: &[u8] = b"signing_stats";
+const SIGNING_LOG_SEED: &[u8] = b"signing_log";
+
+/// Seed (with `computation_offset` appended, little-endian) for the
+/// per-computation [`AbortModeConfig`] PDA `sign_transaction` and
+/// `verify_agent_signature` create to carry their caller's `abort_mode`
+/// choice across to the callback, which otherwise only receives
+/// `SignedComputationOutputs` and has no way to see the instruction's
+/// original arguments.
+const ABORT_MODE_CONFIG_SEED: &[u8] = b"abort_mode";
+
+/// `abort_mode` value: an aborted computation makes the callback return
+/// `Err(ErrorCode::AbortedComputation)`, rolling back the whole
+/// transaction along with anything else in the same bundle. This is the
+/// default and matches every signing/verification callback's behavior
+/// before `abort_mode` existed.
+const ABORT_MODE_HARD_ERROR: u8 = 0;
+
+/// `abort_mode` value: an aborted computation makes the callback return
+/// `Ok(())` and instead report the failure via an
+/// `*AbortedEvent`/return-data signal, so a caller that bundled this
+/// instruction with unrelated ones doesn't lose those too. The caller is
+/// responsible for checking the emitted event instead of assuming success.
+const ABORT_MODE_SOFT_FAIL: u8 = 1;
+
+/// Seed (with an `observer_id` appended, little-endian) for an
+/// [`ObserverRegistry`] PDA.
+const OBSERVER_REGISTRY_SEED: &[u8] = b"observer_registry";
+
+/// Seed for the singleton [`SigningKeyHistory`] PDA.
+const SIGNING_KEY_HISTORY_SEED: &[u8] = b"signing_key_history";
+
+/// Maximum past public keys [`SigningKeyHistory`] retains. Chosen the
+/// same way [`MAX_OBSERVERS`]/[`MAX_SIGNATURE_BATCH`] were: a small,
+/// fixed-at-compile-time ceiling that keeps the account's space (and
+/// `rotate_signing_key_callback`'s work) bounded regardless of how many
+/// times this deployment ever rotates.
+const MAX_KEY_HISTORY: usize = 8;
+
+/// Seed (with `computation_offset` appended, little-endian) for a
+/// per-computation [`SignatureRecord`] PDA, created at queue time the same
+/// way [`AbortModeConfig`] is — so `sign_transaction_callback` has
+/// somewhere durable to write the signature besides an event a client
+/// might have missed.
+const SIGNATURE_RECORD_SEED: &[u8] = b"signature_record";
+
+/// Seed for the singleton [`NonceRegistry`] PDA.
+const NONCE_REGISTRY_SEED: &[u8] = b"nonce_registry";
+
+/// Bounded ring-buffer capacity for [`NonceRegistry::digests`], chosen the
+/// same way [`MAX_KEY_HISTORY`] was — fixed at compile time so the
+/// account's space and `NonceRegistry::record`'s work stay bounded
+/// regardless of call volume. Once full, the oldest digest is overwritten
+/// first, the same tradeoff `agent-vault`'s `DepositLog` documents: a digest evicted
+/// to make room for a newer one is no longer rejected as a replay even
+/// though it was genuinely seen before. `verify_agent_signature_plaintext`
+/// is low-volume enough relative to this that a replay surviving long
+/// enough to wrap the ring is not the threat model this defends against;
+/// it defends against back-to-back resubmission of the exact same
+/// verification, not an adversary patient enough to wait out `CAPACITY`
+/// intervening calls.
+const NONCE_REGISTRY_CAPACITY: usize = 64;
+
+/// Seed (with `computation_offset` appended, little-endian) for a
+/// per-computation [`VerificationNonceRecord`] PDA. `verify_agent_signature_plaintext_callback`
+/// only ever receives `SignedComputationOutputs<VerifyAgentSignaturePlaintextOutput>`
+/// — never the original `message`/`signature` it was queued with — so this
+/// record, created the same way `AbortModeConfig`/`SignatureRecord` are, is
+/// what lets the callback recover `nonce_digest` to record into
+/// `NonceRegistry` only once it has actually seen `is_valid == true`.
+const VERIFICATION_NONCE_SEED: &[u8] = b"verification_nonce";
+
+/// Mandatory delay between `propose_min_nodes_change` lowering
+/// `MXEConfig::min_nodes` and `apply_min_nodes_change` being allowed to
+/// carry it out. One day, matching other timelocked governance in similar
+/// threshold-wallet designs.
+const MIN_NODES_TIMELOCK_SECS: i64 = 86_400;
+
+/// Ceiling on `SigningLog::capacity`, chosen to keep `resize_signing_log`'s
+/// worst-case rent top-up and entry-shifting loop bounded regardless of
+/// how far an admin grows the log.
+const MAX_SIGNING_LOG_CAPACITY: u32 = 4096;
+
+/// 8-byte discriminator prefixed to the CPI data `verify_agent_signature_callback`
+/// sends to an allowlisted `result_program`, computed the same way Anchor
+/// derives instruction discriminators (`sha256("global:<name>")[..8]`) so a
+/// downstream Anchor program can declare a matching `#[program]` method
+/// named `receive_verification_result` and decode the rest positionally.
+fn verification_result_discriminator() -> [u8; 8] {
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&solana_program::hash::hash(b"global:receive_verification_result").to_bytes()[..8]);
+    out
+}
+
+/// Same derivation as [`verification_result_discriminator`], for the
+/// plaintext result CPI `verify_agent_signature_plaintext_callback` sends.
+/// Kept as a separate discriminator (rather than reusing
+/// `verification_result_discriminator`) because the payload shape differs
+/// — a plaintext `bool` instead of a ciphertext + nonce — so a downstream
+/// program declares a distinct `receive_plaintext_verification_result`
+/// method rather than overloading `receive_verification_result`'s.
+fn plaintext_verification_result_discriminator() -> [u8; 8] {
+    let mut out = [0u8; 8];
+    out.copy_from_slice(
+        &solana_program::hash::hash(b"global:receive_plaintext_verification_result").to_bytes()[..8],
+    );
+    out
+}
+
+/// Validates an optional result-CPI target supplied via
+/// `ctx.remaining_accounts` on a `verify_agent_signature*` call and turns it
+/// into the `AccountMeta`s `verify_agent_signature_callback` needs to attach
+/// to its own invocation. `remaining_accounts[0]` is the target program id;
+/// everything after it is passed through as writable, non-signer accounts.
+/// An empty slice means the caller didn't request a result CPI.
+fn validate_result_target(
+    mxe_config: &MXEConfig,
+    remaining_accounts: &[AccountInfo],
+) -> Result<Vec<AccountMeta>> {
+    if remaining_accounts.is_empty() {
+        return Ok(vec![]);
+    }
+    let result_program = remaining_accounts[0].key();
+    require!(
+        mxe_config.result_program_allowlist[..mxe_config.result_program_allowlist_count as usize]
+            .contains(&result_program),
+        ErrorCode::ResultProgramNotAllowlisted
+    );
+    let mut metas = vec![AccountMeta::new_readonly(result_program, false)];
+    metas.extend(
+        remaining_accounts[1..]
+            .iter()
+            .map(|info| AccountMeta::new(info.key(), false)),
+    );
+    Ok(metas)
+}
+
+/// Longest message `verify_agent_signature_full_message` will hash, chosen
+/// to keep the instruction's compute budget predictable.
+const MAX_FULL_MESSAGE_LEN: usize = 512;
+
+/// Digests a caller-supplied message with a selectable algorithm so
+/// `verify_agent_signature_full_message` binds to the actual message
+/// content rather than trusting a caller-supplied pre-hash.
+///
+/// `0` = SHA-256, `1` = Keccak-256.
+fn hash_full_message(message: &[u8], algorithm: u8) -> Result<[u8; 32]> {
+    match algorithm {
+        0 => Ok(solana_program::hash::hash(message).to_bytes()),
+        1 => Ok(keccak::hash(message).0),
+        _ => Err(ErrorCode::UnsupportedHashAlgorithm.into()),
+    }
+}
+
+/// Digests a revealed signature into the 32-byte form `SigningLog::entries`
+/// stores. See `SigningLog`'s doc comment for why a hash, not the
+/// signature itself, is what actually gets buffered.
+fn signing_log_digest(signature: &[u8; 64]) -> [u8; 32] {
+    solana_program::hash::hash(signature).to_bytes()
+}
+
+/// Digests a `(message, signature)` pair into the 32-byte form
+/// [`NonceRegistry::digests`] stores, so a resubmission of the exact same
+/// verification request is recognized regardless of which verifying key it
+/// claims — two different keys producing valid signatures over the same
+/// `message` hash to different digests here, so legitimate re-verification
+/// of the same message under a different key is never blocked by this.
+fn nonce_digest(message: &[u8; 32], signature: &[u8; 64]) -> [u8; 32] {
+    let mut preimage = Vec::with_capacity(32 + 64);
+    preimage.extend_from_slice(message);
+    preimage.extend_from_slice(signature);
+    solana_program::hash::hash(&preimage).to_bytes()
+}
+
+/// Writes `digest` into `log`'s next ring slot and advances `head`,
+/// wrapping at `capacity`. If more than `capacity` signatures land since
+/// the last `flush_signing_events`, earlier unflushed entries are
+/// overwritten before ever being folded into a root — see
+/// `flush_signing_events`'s doc comment for the ambiguity that leaves.
+fn append_signing_log(log: &mut SigningLog, digest: [u8; 32]) {
+    let head = log.head as usize;
+    log.entries[head] = digest;
+    log.head = (log.head + 1) % log.capacity;
+}
+
+/// Binary Merkle root over `leaves`, in order, duplicating the last node of
+/// any odd-length level instead of padding with a zero leaf — a fixed,
+/// length-determined tree shape, so a consumer proving inclusion never
+/// needs the original entry count published alongside the root, only
+/// `leaves.len()` (itself published as `SigningEventsFlushedEvent::count`).
+/// `leaves` must be non-empty; `flush_signing_events` already guards that.
+fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        let mut next = Vec::with_capacity((level.len() + 1) / 2);
+        let mut i = 0;
+        while i < level.len() {
+            let left = level[i];
+            let right = if i + 1 < level.len() { level[i + 1] } else { left };
+            next.push(solana_program::hash::hashv(&[&left, &right]).to_bytes());
+            i += 2;
+        }
+        level = next;
+    }
+    level[0]
+}
+
+/// Solana's compute-unit limit for a transaction that carries no
+/// `ComputeBudgetProgram::SetComputeUnitLimit` instruction of its own.
+/// Documented here as the effective default every `queue_computation` call
+/// below runs its callback under when `callback_compute_unit_limit` is
+/// `None`.
+const DEFAULT_CALLBACK_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+
+/// Solana's hard per-transaction compute-unit ceiling. `sign_transaction`
+/// itself never checks `callback_compute_unit_limit` against this — an
+/// over-budget request just fails once submitted — but
+/// `sign_transaction_dry_run` does, so a misconfigured client catches an
+/// unsatisfiable compute budget before paying for a real computation.
+const MAX_TRANSACTION_COMPUTE_UNITS: u32 = 1_400_000;
+
+/// Builds the (possibly empty) list of instructions that should be queued
+/// ahead of a computation's callback instruction to raise its compute-unit
+/// ceiling above [`DEFAULT_CALLBACK_COMPUTE_UNIT_LIMIT`]. Pass `None` to
+/// leave the default in place; `Some(units)` attaches a
+/// `ComputeBudgetProgram::SetComputeUnitLimit` instruction sized for
+/// callbacks that do extra post-processing (signature normalization,
+/// on-chain logging, CPI delivery) and would otherwise risk failing once
+/// that work pushes them past the default.
+fn callback_compute_budget_ixs(limit: Option<u32>) -> Vec<solana_program::instruction::Instruction> {
+    match limit {
+        Some(units) => vec![solana_program::compute_budget::ComputeBudgetInstruction::set_compute_unit_limit(units)],
+        None => vec![],
+    }
+}
+
+/// Number of seconds in a UTC day; the valid range for
+/// `MXEConfig::signing_window_start`/`signing_window_end`.
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// Enforces `MXEConfig`'s optional daily signing window against the current
+/// on-chain clock. A disabled window always passes. See
+/// `MXEConfig::signing_window_enabled` for the exact boundary semantics.
+fn check_signing_window(mxe_config: &MXEConfig) -> Result<()> {
+    if !mxe_config.signing_window_enabled {
+        return Ok(());
+    }
+    let now = Clock::get()?.unix_timestamp;
+    let seconds_today = now.rem_euclid(SECONDS_PER_DAY as i64) as u32;
+    let (start, end) = (mxe_config.signing_window_start, mxe_config.signing_window_end);
+    let in_window = if start <= end {
+        seconds_today >= start && seconds_today <= end
+    } else {
+        seconds_today >= start || seconds_today <= end
+    };
+    require!(in_window, ErrorCode::OutsideSigningWindow);
+    Ok(())
+}
+
 const COMP_DEF_OFFSET_SIGN_TRANSACTION: u32 = comp_def_offset("sign_transaction");
+const COMP_DEF_OFFSET_SIGN_TRANSACTION_CONFIDENTIAL: u32 =
+    comp_def_offset("sign_transaction_confidential");
+const COMP_DEF_OFFSET_SIGN_TRANSACTION_ED25519CTX: u32 =
+    comp_def_offset("sign_transaction_ed25519ctx");
+const COMP_DEF_OFFSET_SIGN_TRANSACTIONS_BATCH: u32 = comp_def_offset("sign_transactions_batch");
+const COMP_DEF_OFFSET_ROTATE_SIGNING_KEY: u32 = comp_def_offset("rotate_signing_key");
+const COMP_DEF_OFFSET_SIGN_TRANSACTION_SECP256K1: u32 =
+    comp_def_offset("sign_transaction_secp256k1");
+
+/// Mirrors the width of `circuits::sign_transaction_ed25519ctx`'s `context`
+/// parameter in `encrypted-ixs` — the fixed-size context buffer that circuit
+/// accepts. See that circuit's doc comment for why shorter contexts are
+/// zero-padded to this width rather than genuinely variable-length.
+const MAX_ED25519CTX_CONTEXT_LEN: u8 = 32;
 const COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURE: u32 = comp_def_offset("verify_agent_signature");
+const COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURES_ONE_KEY: u32 =
+    comp_def_offset("verify_agent_signatures_one_key");
+const COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURE_MULTI_OBSERVER: u32 =
+    comp_def_offset("verify_agent_signature_multi_observer");
+const COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURE_PLAINTEXT: u32 =
+    comp_def_offset("verify_agent_signature_plaintext");
+const COMP_DEF_OFFSET_CHECK_SPEND_ALLOWED: u32 = comp_def_offset("check_spend_allowed");
+
+/// Mirrors `circuits::MAX_OBSERVERS` in `encrypted-ixs` — the fixed number
+/// of observer slots `verify_agent_signature_multi_observer` takes.
+const MAX_OBSERVERS: usize = 3;
+
+/// Mirrors `circuits::MAX_SIGNATURE_BATCH` in `encrypted-ixs` — the number of
+/// (message, signature) slots `verify_agent_signatures_one_key` pads its
+/// inputs to.
+const MAX_SIGNATURE_BATCH: usize = 8;
 
 declare_id!("EvuXy5xNCSiR1AwPyU3Laz8mtaiyK7xnsPpA115UNoXN");
 
+// Thin, non-aborting wrappers around the `derive_*_pda!` macros, which
+// normally short-circuit the whole instruction via `?` on a misconfigured
+// cluster. Isolating the `?` inside these functions lets `health_check`
+// keep going and report every PDA instead of stopping at the first miss.
+fn derive_mempool_pda(mxe_account: &Account<MXEAccount>) -> Result<Pubkey> {
+    Ok(derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))
+}
+
+fn derive_execpool_pda(mxe_account: &Account<MXEAccount>) -> Result<Pubkey> {
+    Ok(derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))
+}
+
+fn derive_cluster_pda(mxe_account: &Account<MXEAccount>) -> Result<Pubkey> {
+    Ok(derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))
+}
+
+/// Enforces `MXEConfig::min_nodes` against the cluster's active node count.
+///
+/// TODO: `Cluster`'s active-node-count field isn't visible from this
+/// crate's source (it's defined in `arcium_anchor`), so the actual
+/// comparison isn't wired up yet rather than guessing a field name. Once
+/// confirmed, this should become
+/// `require!(cluster.<active_node_field> as u8 >= min_nodes, ErrorCode::InsufficientClusterNodes)`.
+fn check_min_nodes(cluster: &Account<Cluster>, min_nodes: u8) -> Result<()> {
+    let _ = (cluster, min_nodes);
+    Ok(())
+}
+
 #[arcium_program]
 pub mod agentic_wallet_mxe {
     use super::*;
 
+    // =========================
+    // COMP DEF INITIALIZATION
+    // =========================
+    //
+    // This program currently has eleven comp defs, one `init_*_comp_def`
+    // instruction each: `init_sign_transaction_comp_def`,
+    // `init_sign_transaction_confidential_comp_def`,
+    // `init_sign_transaction_ed25519ctx_comp_def`,
+    // `init_sign_transactions_batch_comp_def`,
+    // `init_rotate_signing_key_comp_def`,
+    // `init_sign_transaction_secp256k1_comp_def`,
+    // `init_verify_agent_signature_comp_def`,
+    // `init_verify_agent_signature_plaintext_comp_def`,
+    // `init_verify_agent_signature_multi_observer_comp_def`,
+    // `init_verify_agent_signatures_one_key_comp_def`, and
+    // `init_check_spend_allowed_comp_def` below.
+    //
+    // A single `init_all_comp_defs` instruction that initializes all eleven
+    // in one CPI isn't implemented here. Each `Init*CompDef` struct above
+    // is generated by `#[init_computation_definition_accounts("<name>",
+    // payer)]` from `arcium_anchor`, bound to exactly one comp def name,
+    // and `init_comp_def(ctx.accounts, ...)` is typed against that one
+    // generated struct — nothing in this codebase's use of `arcium_anchor`
+    // shows that macro or helper accepting more than one comp def per
+    // call, and guessing at an internal API to make eleven independent CPIs
+    // share a single instruction would mean fabricating behavior this
+    // crate has no evidence actually exists.
+    //
+    // What *is* already true, without any new instruction, is that Solana
+    // transactions are atomic: a client can bundle any subset of the eleven
+    // `init_*_comp_def` instructions below into one `Transaction` (the
+    // same way this program's own test suite bundles a verify instruction
+    // with a dependent instruction elsewhere) and get exactly the
+    // "fails atomically if any fails" property this request asked for,
+    // today, with zero new on-chain code. The account requirements for
+    // doing so: every one of the eleven instructions shares the same
+    // `payer`, `mxe_account`, `lut_program`, `arcium_program`, and
+    // `system_program` accounts; only `comp_def_account` (one PDA per
+    // comp def name, via `derive_comp_def_pda!`) and `address_lookup_table`
+    // differ per instruction. Eleven instructions' worth of accounts (5
+    // shared + 2 per-instruction × 11 ≈ 27 total, well under Solana's
+    // per-transaction account limit) comfortably fits in a single legacy
+    // transaction without needing a versioned transaction or an address
+    // lookup table of its own. Deployment tooling wanting this today
+    // should build exactly that bundle client-side.
     pub fn init_sign_transaction_comp_def(
         ctx: Context<InitSignTransactionCompDef>,
     ) -> Result<()> {
@@ -17,43 +398,922 @@ pub mod agentic_wallet_mxe {
         Ok(())
     }
 
+    pub fn init_sign_transaction_confidential_comp_def(
+        ctx: Context<InitSignTransactionConfidentialCompDef>,
+    ) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_sign_transaction_ed25519ctx_comp_def(
+        ctx: Context<InitSignTransactionEd25519ctxCompDef>,
+    ) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_sign_transactions_batch_comp_def(
+        ctx: Context<InitSignTransactionsBatchCompDef>,
+    ) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_rotate_signing_key_comp_def(
+        ctx: Context<InitRotateSigningKeyCompDef>,
+    ) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    pub fn init_sign_transaction_secp256k1_comp_def(
+        ctx: Context<InitSignTransactionSecp256k1CompDef>,
+    ) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    /// Re-derives every PDA this program depends on and reports, per-PDA,
+    /// whether it matches the account actually supplied — instead of the
+    /// usual behavior where a single mismatched PDA (e.g. `ClusterNotSet`)
+    /// aborts the whole instruction and gives no signal about the rest.
+    /// Read-only: never mutates state. The report is both emitted as an
+    /// event and returned via `set_return_data` for off-chain tooling.
+    pub fn health_check(ctx: Context<HealthCheck>) -> Result<()> {
+        let mxe_account = &ctx.accounts.mxe_account;
+
+        let report = PdaHealthReport {
+            mxe: ctx.accounts.mxe_account.key() == derive_mxe_pda!(),
+            mempool: derive_mempool_pda(mxe_account)
+                .map(|addr| addr == ctx.accounts.mempool_account.key())
+                .unwrap_or(false),
+            execpool: derive_execpool_pda(mxe_account)
+                .map(|addr| addr == ctx.accounts.executing_pool.key())
+                .unwrap_or(false),
+            cluster: derive_cluster_pda(mxe_account)
+                .map(|addr| addr == ctx.accounts.cluster_account.key())
+                .unwrap_or(false),
+            sign_transaction_comp_def: ctx.accounts.sign_transaction_comp_def.key()
+                == derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION),
+            verify_agent_signature_comp_def: ctx.accounts.verify_agent_signature_comp_def.key()
+                == derive_comp_def_pda!(COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURE),
+            fee_pool: ctx.accounts.pool_account.key() == ARCIUM_FEE_POOL_ACCOUNT_ADDRESS,
+            clock: ctx.accounts.clock_account.key() == ARCIUM_CLOCK_ACCOUNT_ADDRESS,
+        };
+
+        emit!(HealthCheckEvent { report });
+        anchor_lang::solana_program::program::set_return_data(&report.try_to_vec()?);
+        Ok(())
+    }
+
+    /// Creates the singleton `MXEConfig` PDA with `admin` as its initial
+    /// administrator and an all-zero `signing_domain`. Must run once before
+    /// `sign_transaction` can be called, since that instruction always
+    /// reads `mxe_config.signing_domain`.
+    pub fn init_mxe_config(ctx: Context<InitMxeConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.mxe_config;
+        config.admin = ctx.accounts.admin.key();
+        config.signing_domain = [0u8; 16];
+        config.min_nodes = 0;
+        config.result_program_allowlist = [Pubkey::default(); MAX_RESULT_PROGRAM_ALLOWLIST];
+        config.result_program_allowlist_count = 0;
+        config.max_batch_size = MAX_SIGNATURE_BATCH as u8;
+        config.signing_window_enabled = false;
+        config.signing_window_start = 0;
+        config.signing_window_end = 0;
+        config.pending_min_nodes_effective_at = 0;
+        config.pending_min_nodes = 0;
+        config.bump = ctx.bumps.mxe_config;
+        Ok(())
+    }
+
+    /// Admin-only: sets (or disables) the daily UTC window during which
+    /// `sign_transaction` will run. See [`MXEConfig::signing_window_enabled`]
+    /// for exact boundary semantics, including the past-midnight wraparound.
+    pub fn set_signing_window(
+        ctx: Context<SetSigningDomain>,
+        enabled: bool,
+        start: u32,
+        end: u32,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.admin == ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            start <= SECONDS_PER_DAY && end <= SECONDS_PER_DAY,
+            ErrorCode::InvalidSigningWindow
+        );
+        ctx.accounts.mxe_config.signing_window_enabled = enabled;
+        ctx.accounts.mxe_config.signing_window_start = start;
+        ctx.accounts.mxe_config.signing_window_end = end;
+        Ok(())
+    }
+
+    /// Creates the singleton `SigningStats` PDA, zeroed out. Must run once
+    /// before `sign_transaction`'s callbacks can record into it.
+    pub fn init_signing_stats(ctx: Context<InitSigningStats>) -> Result<()> {
+        let stats = &mut ctx.accounts.signing_stats;
+        stats.total_signatures = 0;
+        stats.total_aborts = 0;
+        stats.last_reset = Clock::get()?.unix_timestamp;
+        stats.bump = ctx.bumps.signing_stats;
+        Ok(())
+    }
+
+    /// Creates the singleton `SigningKeyHistory` PDA, zeroed out. Must run
+    /// once before `rotate_signing_key`'s callback can record into it.
+    pub fn init_signing_key_history(ctx: Context<InitSigningKeyHistory>) -> Result<()> {
+        let history = &mut ctx.accounts.signing_key_history;
+        history.versions = [0u32; MAX_KEY_HISTORY];
+        history.public_keys = [[0u8; 32]; MAX_KEY_HISTORY];
+        history.count = 0;
+        history.current_version = 0;
+        history.bump = ctx.bumps.signing_key_history;
+        Ok(())
+    }
+
+    /// Creates the singleton `NonceRegistry` PDA, zeroed out. Must run once
+    /// before `verify_agent_signature_plaintext` can enforce replay
+    /// protection against it.
+    pub fn init_nonce_registry(ctx: Context<InitNonceRegistry>) -> Result<()> {
+        let registry = &mut ctx.accounts.nonce_registry;
+        registry.digests = [[0u8; 32]; NONCE_REGISTRY_CAPACITY];
+        registry.len = 0;
+        registry.next_index = 0;
+        registry.bump = ctx.bumps.nonce_registry;
+        Ok(())
+    }
+
+    /// Admin-only: zeroes `total_signatures`/`total_aborts` and stamps
+    /// `last_reset` with the current time, e.g. to start a fresh reporting
+    /// period.
+    pub fn reset_signing_stats(ctx: Context<ResetSigningStats>) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.admin == ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        let stats = &mut ctx.accounts.signing_stats;
+        stats.total_signatures = 0;
+        stats.total_aborts = 0;
+        stats.last_reset = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+
+    /// Creates the singleton `SigningLog` ring buffer with `capacity`
+    /// all-zero entries. See [`SigningLog`] for the buffer format and how
+    /// `sign_transaction` opts a call into appending to it.
+    pub fn init_signing_log(ctx: Context<InitSigningLog>, capacity: u32) -> Result<()> {
+        require!(
+            capacity > 0 && capacity <= MAX_SIGNING_LOG_CAPACITY,
+            ErrorCode::InvalidSigningLogCapacity
+        );
+        let log = &mut ctx.accounts.signing_log;
+        log.capacity = capacity;
+        log.head = 0;
+        log.entries = vec![[0u8; 32]; capacity as usize];
+        log.last_flushed_head = 0;
+        log.bump = ctx.bumps.signing_log;
+        Ok(())
+    }
+
+    /// Admin-only: grows the signing log's capacity in place via
+    /// `realloc`, paying any incremental rent from `admin`. Never shrinks
+    /// — a `new_capacity` below the current capacity is rejected — and
+    /// preserves every existing entry plus the ring's `head` index: the
+    /// newly-added empty slots are spliced in right at `head`, so entries
+    /// already written keep their relative order and `head` still points
+    /// at the same logical "next write" position, just with more room
+    /// before it wraps back around to the oldest entries. Returns the new
+    /// capacity via return data.
+    pub fn resize_signing_log(ctx: Context<ResizeSigningLog>, new_capacity: u32) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.admin == ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        let current_capacity = ctx.accounts.signing_log.capacity;
+        require!(
+            new_capacity >= current_capacity,
+            ErrorCode::SigningLogCapacityTooSmall
+        );
+        require!(
+            new_capacity <= MAX_SIGNING_LOG_CAPACITY,
+            ErrorCode::SigningLogCapacityTooLarge
+        );
+
+        let added = (new_capacity - current_capacity) as usize;
+        if added > 0 {
+            let log_info = ctx.accounts.signing_log.to_account_info();
+            let new_len = log_info.data_len() + added * 32;
+            log_info.realloc(new_len, false)?;
+
+            let rent_exempt_min = Rent::get()?.minimum_balance(new_len);
+            let lamports_needed = rent_exempt_min.saturating_sub(log_info.lamports());
+            if lamports_needed > 0 {
+                solana_program::program::invoke(
+                    &solana_program::system_instruction::transfer(
+                        ctx.accounts.admin.key,
+                        &log_info.key(),
+                        lamports_needed,
+                    ),
+                    &[
+                        ctx.accounts.admin.to_account_info(),
+                        log_info.clone(),
+                        ctx.accounts.system_program.to_account_info(),
+                    ],
+                )?;
+            }
+
+            let head = ctx.accounts.signing_log.head as usize;
+            let log = &mut ctx.accounts.signing_log;
+            for i in 0..added {
+                log.entries.insert(head + i, [0u8; 32]);
+            }
+            log.capacity = new_capacity;
+        }
+
+        solana_program::program::set_return_data(&ctx.accounts.signing_log.capacity.to_le_bytes());
+        Ok(())
+    }
+
+    /// Exposes `SigningStats` via return data, as three little-endian
+    /// fields back to back: `total_signatures: u64`, `total_aborts: u64`,
+    /// `last_reset: i64`.
+    pub fn get_signing_stats(ctx: Context<GetSigningStats>) -> Result<()> {
+        let stats = &ctx.accounts.signing_stats;
+        let mut data = [0u8; 24];
+        data[0..8].copy_from_slice(&stats.total_signatures.to_le_bytes());
+        data[8..16].copy_from_slice(&stats.total_aborts.to_le_bytes());
+        data[16..24].copy_from_slice(&stats.last_reset.to_le_bytes());
+        solana_program::program::set_return_data(&data);
+        Ok(())
+    }
+
+    /// Admin-only: tunes `verify_agent_signatures_one_key`'s batch-size
+    /// ceiling to whatever this cluster's compute budget actually supports.
+    /// Each batch element costs roughly one Ed25519 verification inside the
+    /// circuit; operators should calibrate down from `MAX_SIGNATURE_BATCH`
+    /// based on measured per-element CU cost rather than assuming the
+    /// circuit's hard ceiling always fits.
+    pub fn set_max_batch_size(ctx: Context<SetSigningDomain>, max_batch_size: u8) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.admin == ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            max_batch_size as usize <= MAX_SIGNATURE_BATCH,
+            ErrorCode::BatchTooLarge
+        );
+        ctx.accounts.mxe_config.max_batch_size = max_batch_size;
+        Ok(())
+    }
+
+    /// Exposes `MXEConfig::max_batch_size` via return data (a single LE
+    /// `u8`) so clients can size `verify_agent_signatures_one_key` batches
+    /// without guessing or hardcoding `MAX_SIGNATURE_BATCH`.
+    pub fn get_max_batch_size(ctx: Context<GetMaxBatchSize>) -> Result<()> {
+        solana_program::program::set_return_data(&[ctx.accounts.mxe_config.max_batch_size]);
+        Ok(())
+    }
+
+    /// Admin-only: overwrites the set of programs `verify_agent_signature`
+    /// callers may target with a result CPI.
+    pub fn set_result_program_allowlist(
+        ctx: Context<SetSigningDomain>,
+        allowlist: [Pubkey; MAX_RESULT_PROGRAM_ALLOWLIST],
+        count: u8,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.admin == ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            (count as usize) <= MAX_RESULT_PROGRAM_ALLOWLIST,
+            ErrorCode::InvalidSigningPolicy
+        );
+        ctx.accounts.mxe_config.result_program_allowlist = allowlist;
+        ctx.accounts.mxe_config.result_program_allowlist_count = count;
+        Ok(())
+    }
+
+    /// Admin-only: changes `MXEConfig::min_nodes`, the floor on active
+    /// cluster node count that every `sign_transaction` and
+    /// `verify_agent_signature` call passes to `check_min_nodes` — which
+    /// does not compare it against the cluster (see its doc comment), so
+    /// the stored value has no effect on those calls. Raising it (or leaving it unchanged) strengthens the floor
+    /// and applies immediately. Lowering it weakens the floor, so instead
+    /// of applying right away it's recorded as a pending change that
+    /// `apply_min_nodes_change` can only carry out after
+    /// `MIN_NODES_TIMELOCK_SECS` has elapsed, giving anyone watching the
+    /// chain a window to notice and react before a weaker threshold takes
+    /// effect. A second proposal while one is already pending overwrites
+    /// it (and restarts the timelock) rather than queuing both.
+    pub fn propose_min_nodes_change(
+        ctx: Context<SetSigningDomain>,
+        new_min_nodes: u8,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.admin == ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        let config = &mut ctx.accounts.mxe_config;
+        if new_min_nodes >= config.min_nodes {
+            config.min_nodes = new_min_nodes;
+            config.pending_min_nodes_effective_at = 0;
+            config.pending_min_nodes = 0;
+            emit!(MinNodesChangeAppliedEvent {
+                min_nodes: new_min_nodes,
+            });
+        } else {
+            let effective_at = Clock::get()?
+                .unix_timestamp
+                .checked_add(MIN_NODES_TIMELOCK_SECS)
+                .ok_or(ErrorCode::Overflow)?;
+            config.pending_min_nodes = new_min_nodes;
+            config.pending_min_nodes_effective_at = effective_at;
+            emit!(MinNodesChangeProposedEvent {
+                new_min_nodes,
+                effective_at,
+            });
+        }
+        Ok(())
+    }
+
+    /// Admin-only: carries out a pending `min_nodes` lowering proposed by
+    /// `propose_min_nodes_change`, once its timelock has elapsed.
+    pub fn apply_min_nodes_change(ctx: Context<SetSigningDomain>) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.admin == ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        let config = &mut ctx.accounts.mxe_config;
+        require!(
+            config.pending_min_nodes_effective_at != 0,
+            ErrorCode::NoPendingMinNodesChange
+        );
+        require!(
+            Clock::get()?.unix_timestamp >= config.pending_min_nodes_effective_at,
+            ErrorCode::MinNodesTimelockNotElapsed
+        );
+        config.min_nodes = config.pending_min_nodes;
+        config.pending_min_nodes = 0;
+        config.pending_min_nodes_effective_at = 0;
+        emit!(MinNodesChangeAppliedEvent {
+            min_nodes: config.min_nodes,
+        });
+        Ok(())
+    }
+
+    /// Admin-only: withdraws a pending `min_nodes` lowering before its
+    /// timelock elapses, leaving `min_nodes` untouched.
+    pub fn cancel_min_nodes_change(ctx: Context<SetSigningDomain>) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.admin == ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        let config = &mut ctx.accounts.mxe_config;
+        require!(
+            config.pending_min_nodes_effective_at != 0,
+            ErrorCode::NoPendingMinNodesChange
+        );
+        let cancelled_min_nodes = config.pending_min_nodes;
+        config.pending_min_nodes = 0;
+        config.pending_min_nodes_effective_at = 0;
+        emit!(MinNodesChangeCancelledEvent {
+            cancelled_min_nodes,
+        });
+        Ok(())
+    }
+
+    /// Admin-only: sets the domain tag incorporated into every
+    /// `sign_transaction` preimage. See [`MXEConfig::signing_domain`] for
+    /// the exact preimage layout this produces.
+    pub fn set_signing_domain(
+        ctx: Context<SetSigningDomain>,
+        signing_domain: [u8; 16],
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.admin == ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.mxe_config.signing_domain = signing_domain;
+        Ok(())
+    }
+
+    /// Admin-only: creates an empty (deny-all) [`SigningPolicy`] for
+    /// `agent`. Call `set_signing_policy_prefixes` next to actually allow
+    /// anything through.
+    pub fn init_signing_policy(ctx: Context<InitSigningPolicy>, agent: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.admin == ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        let policy = &mut ctx.accounts.signing_policy;
+        policy.agent = agent;
+        policy.prefixes = [[0u8; POLICY_PREFIX_LEN]; MAX_POLICY_PREFIXES];
+        policy.prefix_lens = [0u8; MAX_POLICY_PREFIXES];
+        policy.count = 0;
+        policy.bump = ctx.bumps.signing_policy;
+        Ok(())
+    }
+
+    /// Admin-only: overwrites `agent`'s allowed message prefixes wholesale.
+    /// `prefix_lens[i]` bounds how many leading bytes of `prefixes[i]` are
+    /// compared; a `message` passed to `sign_transaction` is allowed if it
+    /// starts with at least one prefix among the first `count` entries.
+    pub fn set_signing_policy_prefixes(
+        ctx: Context<SetSigningPolicyPrefixes>,
+        prefixes: [[u8; POLICY_PREFIX_LEN]; MAX_POLICY_PREFIXES],
+        prefix_lens: [u8; MAX_POLICY_PREFIXES],
+        count: u8,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.admin == ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        require!(
+            (count as usize) <= MAX_POLICY_PREFIXES
+                && prefix_lens[..count as usize]
+                    .iter()
+                    .all(|&len| (len as usize) <= POLICY_PREFIX_LEN),
+            ErrorCode::InvalidSigningPolicy
+        );
+        let policy = &mut ctx.accounts.signing_policy;
+        policy.prefixes = prefixes;
+        policy.prefix_lens = prefix_lens;
+        policy.count = count;
+        Ok(())
+    }
+
+    /// Signs `message` under the MXE's distributed Ed25519 key, after the
+    /// circuit prepends `mxe_config.signing_domain`. The final preimage the
+    /// circuit signs is exactly:
+    ///
+    ///   signing_domain: [u8; 16]   (from `MXEConfig`, admin-controlled)
+    ///   message: [u8; 32]          (caller-supplied)
+    ///   aad: [u8; 32]              (caller-supplied, all-zero when `None`)
+    ///
+    /// concatenated in that order, so every signature produced by this MXE
+    /// is scoped to its configured domain regardless of caller input.
+    ///
+    /// `abort_mode` selects how `sign_transaction_callback` reports a
+    /// computation the cluster aborted: `ABORT_MODE_HARD_ERROR` (0, the
+    /// default) fails the whole transaction with `AbortedComputation`;
+    /// `ABORT_MODE_SOFT_FAIL` (1) lets the callback succeed and emit
+    /// `TransactionSignAbortedEvent` instead, so unrelated instructions
+    /// bundled alongside this one don't roll back too. Any other value is
+    /// rejected with `InvalidAbortMode`.
     pub fn sign_transaction(
         ctx: Context<SignTransaction>,
         computation_offset: u64,
+        agent: Pubkey,
         message: [u8; 32],
+        aad: Option<[u8; 32]>,
+        abort_mode: u8,
+        callback_compute_unit_limit: Option<u32>,
     ) -> Result<()> {
+        check_min_nodes(&ctx.accounts.cluster_account, ctx.accounts.mxe_config.min_nodes)?;
+        check_signing_window(&ctx.accounts.mxe_config)?;
+        let _ = agent;
+        require!(
+            matches!(abort_mode, ABORT_MODE_HARD_ERROR | ABORT_MODE_SOFT_FAIL),
+            ErrorCode::InvalidAbortMode
+        );
+
+        if let Some(policy) = &ctx.accounts.signing_policy {
+            let allowed = policy.prefixes[..policy.count as usize]
+                .iter()
+                .zip(&policy.prefix_lens[..policy.count as usize])
+                .any(|(prefix, &len)| message[..len as usize] == prefix[..len as usize]);
+            require!(allowed, ErrorCode::DisallowedMessage);
+        }
+
         ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.abort_mode_config.abort_mode = abort_mode;
+        ctx.accounts.abort_mode_config.bump = ctx.bumps.abort_mode_config;
+        ctx.accounts.abort_mode_config.payer = ctx.accounts.payer.key();
+        ctx.accounts.signature_record.computation_offset = computation_offset;
+        ctx.accounts.signature_record.requester = ctx.accounts.payer.key();
+        ctx.accounts.signature_record.bump = ctx.bumps.signature_record;
         let mut builder = ArgBuilder::new();
+        for byte in ctx.accounts.mxe_config.signing_domain {
+            builder = builder.plaintext_u8(byte);
+        }
         for byte in message {
             builder = builder.plaintext_u8(byte);
         }
-        queue_computation(
-            ctx.accounts,
+        for byte in aad.unwrap_or([0u8; 32]) {
+            builder = builder.plaintext_u8(byte);
+        }
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        // `signing_log` is an `Option<Account>`, so this slot is always
+        // present in the callback's account list — either the real PDA, or
+        // (per Anchor's sentinel convention for omitted `Option<Account>`
+        // fields) this program's own id, which tells the callback's account
+        // parsing to treat the field as `None`.
+        let signing_log_meta = match &ctx.accounts.signing_log {
+            Some(signing_log) => AccountMeta::new(signing_log.key(), false),
+            None => AccountMeta::new_readonly(crate::ID, false),
+        };
+        let callback_metas = vec![
+            AccountMeta::new(ctx.accounts.signing_stats.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.abort_mode_config.key(), false),
+            signing_log_meta,
+            AccountMeta::new(ctx.accounts.signature_record.key(), false),
+        ];
+        ixs.push(SignTransactionCallback::callback_ix(
             computation_offset,
-            builder.build(),
-            vec![SignTransactionCallback::callback_ix(
-                computation_offset,
-                &ctx.accounts.mxe_account,
-                &[],
-            )?],
-            1,
-            0,
-        )?;
+            &ctx.accounts.mxe_account,
+            &callback_metas,
+        )?);
+        queue_computation(ctx.accounts, computation_offset, builder.build(), ixs, 1, 0)?;
         Ok(())
     }
 
-    #[arcium_callback(encrypted_ix = "sign_transaction")]
-    pub fn sign_transaction_callback(
-        ctx: Context<SignTransactionCallback>,
-        output: SignedComputationOutputs<SignTransactionOutput>,
+    /// Symmetric with `verify_agent_signature_full_message`: signs a
+    /// digest this instruction computes from `message`'s actual bytes,
+    /// rather than requiring the caller to pre-hash to 32 bytes (and
+    /// trusting that hash matches the real content). Queues the exact
+    /// same `sign_transaction` circuit/comp-def as `sign_transaction`
+    /// itself — only the message derivation differs, so the final
+    /// preimage the circuit signs is still exactly
+    /// `signing_domain || hash_full_message(message, hash_algorithm) ||
+    /// aad`, per `sign_transaction`'s doc comment, with `message` replaced
+    /// by its digest.
+    ///
+    /// `signing_policy`, if present, is checked against `message`'s own
+    /// bytes (not the digest) — a prefix allowlist means nothing applied
+    /// to a hash. This does mean a policy authored for 32-byte messages
+    /// assuming `sign_transaction`'s layout may need separate prefixes
+    /// here if this entry point's callers submit differently-shaped
+    /// messages; that divergence already exists between this program's
+    /// `message`/`message` parameters across other instruction pairs
+    /// (e.g. `verify_agent_signature` vs `_full_message`) and isn't new
+    /// here.
+    pub fn sign_transaction_full_message(
+        ctx: Context<SignTransactionFullMessage>,
+        computation_offset: u64,
+        agent: Pubkey,
+        message: Vec<u8>,
+        hash_algorithm: u8,
+        aad: Option<[u8; 32]>,
+        abort_mode: u8,
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        check_min_nodes(&ctx.accounts.cluster_account, ctx.accounts.mxe_config.min_nodes)?;
+        check_signing_window(&ctx.accounts.mxe_config)?;
+        let _ = agent;
+        require!(
+            message.len() <= MAX_FULL_MESSAGE_LEN,
+            ErrorCode::MessageTooLong
+        );
+        require!(
+            matches!(abort_mode, ABORT_MODE_HARD_ERROR | ABORT_MODE_SOFT_FAIL),
+            ErrorCode::InvalidAbortMode
+        );
+        let digest = hash_full_message(&message, hash_algorithm)?;
+
+        if let Some(policy) = &ctx.accounts.signing_policy {
+            let allowed = policy.prefixes[..policy.count as usize]
+                .iter()
+                .zip(&policy.prefix_lens[..policy.count as usize])
+                .any(|(prefix, &len)| {
+                    message.len() >= len as usize && message[..len as usize] == prefix[..len as usize]
+                });
+            require!(allowed, ErrorCode::DisallowedMessage);
+        }
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.abort_mode_config.abort_mode = abort_mode;
+        ctx.accounts.abort_mode_config.bump = ctx.bumps.abort_mode_config;
+        ctx.accounts.abort_mode_config.payer = ctx.accounts.payer.key();
+        ctx.accounts.signature_record.computation_offset = computation_offset;
+        ctx.accounts.signature_record.requester = ctx.accounts.payer.key();
+        ctx.accounts.signature_record.bump = ctx.bumps.signature_record;
+        let mut builder = ArgBuilder::new();
+        for byte in ctx.accounts.mxe_config.signing_domain {
+            builder = builder.plaintext_u8(byte);
+        }
+        for byte in digest {
+            builder = builder.plaintext_u8(byte);
+        }
+        for byte in aad.unwrap_or([0u8; 32]) {
+            builder = builder.plaintext_u8(byte);
+        }
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        let signing_log_meta = match &ctx.accounts.signing_log {
+            Some(signing_log) => AccountMeta::new(signing_log.key(), false),
+            None => AccountMeta::new_readonly(crate::ID, false),
+        };
+        let callback_metas = vec![
+            AccountMeta::new(ctx.accounts.signing_stats.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.abort_mode_config.key(), false),
+            signing_log_meta,
+            AccountMeta::new(ctx.accounts.signature_record.key(), false),
+        ];
+        ixs.push(SignTransactionCallback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &callback_metas,
+        )?);
+        queue_computation(ctx.accounts, computation_offset, builder.build(), ixs, 1, 0)?;
+        Ok(())
+    }
+
+    /// Checks every precondition `sign_transaction` enforces *before* it
+    /// queues a computation, without actually queuing one — so a
+    /// misconfigured client (wrong policy PDA, stale offset, an
+    /// out-of-range compute budget, a call outside the signing window)
+    /// finds out from a call that costs no MPC fee, instead of paying for
+    /// a computation whose callback was always going to abort or never
+    /// arrive. Returns a [`SignTransactionDryRunReport`] via return data;
+    /// `would_succeed` is the AND of every other field.
+    ///
+    /// What this covers:
+    /// - `cluster_healthy`: [`check_min_nodes`]. Currently always `true` —
+    ///   that function is itself a stub pending real node-count exposure
+    ///   from the cluster account (see its doc comment) — so this field
+    ///   inherits that same gap rather than asserting a guarantee
+    ///   `sign_transaction` doesn't actually have yet either.
+    /// - `within_signing_window`: [`check_signing_window`].
+    /// - `message_authorized`: `signing_policy`'s prefix allowlist, same
+    ///   logic `sign_transaction` runs inline.
+    /// - `abort_mode_valid`: `abort_mode` is one of
+    ///   `ABORT_MODE_HARD_ERROR`/`ABORT_MODE_SOFT_FAIL`.
+    /// - `compute_budget_valid`: `callback_compute_unit_limit`, if
+    ///   supplied, doesn't exceed [`MAX_TRANSACTION_COMPUTE_UNITS`].
+    /// - `offset_available`: neither `abort_mode_config` nor
+    ///   `computation_account` already has data at `computation_offset`'s
+    ///   derived PDA — the same uniqueness `sign_transaction`'s `init`
+    ///   constraint and the Arcium program would otherwise enforce by
+    ///   failing the real call.
+    ///
+    /// What this does *not* cover: `payer` actually holding enough
+    /// lamports to fund account rent and the Arcium computation fee (no
+    /// lamport balance is read here), the mempool/execution-pool
+    /// capacity or fee-pool solvency `queue_computation` itself depends
+    /// on, and anything that can only be known once the MPC cluster runs
+    /// the circuit (there is nothing of that kind for `sign_transaction`
+    /// itself — it doesn't verify anything, it just signs — but this
+    /// distinction matters for dry-run equivalents of the `verify_*`
+    /// instructions, which this one does not attempt to generalize to).
+    pub fn sign_transaction_dry_run(
+        ctx: Context<SignTransactionDryRun>,
+        computation_offset: u64,
+        agent: Pubkey,
+        message: [u8; 32],
+        abort_mode: u8,
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        let _ = agent;
+        let cluster_healthy = check_min_nodes(&ctx.accounts.cluster_account, ctx.accounts.mxe_config.min_nodes).is_ok();
+        let within_signing_window = check_signing_window(&ctx.accounts.mxe_config).is_ok();
+        let message_authorized = match &ctx.accounts.signing_policy {
+            Some(policy) => policy.prefixes[..policy.count as usize]
+                .iter()
+                .zip(&policy.prefix_lens[..policy.count as usize])
+                .any(|(prefix, &len)| message[..len as usize] == prefix[..len as usize]),
+            None => true,
+        };
+        let abort_mode_valid = matches!(abort_mode, ABORT_MODE_HARD_ERROR | ABORT_MODE_SOFT_FAIL);
+        let compute_budget_valid = callback_compute_unit_limit
+            .map(|limit| limit <= MAX_TRANSACTION_COMPUTE_UNITS)
+            .unwrap_or(true);
+        let offset_available =
+            ctx.accounts.abort_mode_config.data_is_empty() && ctx.accounts.computation_account.data_is_empty();
+
+        let report = SignTransactionDryRunReport {
+            cluster_healthy,
+            within_signing_window,
+            message_authorized,
+            abort_mode_valid,
+            compute_budget_valid,
+            offset_available,
+            would_succeed: cluster_healthy
+                && within_signing_window
+                && message_authorized
+                && abort_mode_valid
+                && compute_budget_valid
+                && offset_available,
+        };
+        solana_program::program::set_return_data(&report.try_to_vec()?);
+        Ok(())
+    }
+
+    /// Reclaims what this program can actually refund from a terminated,
+    /// aborted computation: the rent-exempt lamports backing its
+    /// `AbortModeConfig` PDA, returned to the original `payer`. This is
+    /// *not* a refund of any unused portion of the Arcium computation fee
+    /// paid into `FeePool` at queue time — that fee, and the accounting
+    /// for how much of it a given computation actually consumed before
+    /// aborting, belongs entirely to the Arcium program. `FeePool` and
+    /// `ComputationAccount` are owned by that program, not this one, and
+    /// this program has no documented instruction it can CPI to request a
+    /// partial fee-pool refund, so claiming one here would mean
+    /// fabricating an API this crate has no evidence exists. What's
+    /// refundable without that primitive is exactly the rent this program
+    /// itself collected for the one PDA it creates per computation:
+    ///
+    ///   refund_lamports = abort_mode_config.to_account_info().lamports()
+    ///
+    /// i.e. all of it — the account is closed outright, the same as any
+    /// other Anchor `close`. There is no partial-refund calculation
+    /// beyond that because there is no second, larger pool of lamports
+    /// this program is in a position to apportion.
+    ///
+    /// Requires `terminated` (set by the callback on the
+    /// `ABORT_MODE_SOFT_FAIL` abort path — see [`AbortModeConfig`]'s doc
+    /// comment for why `ABORT_MODE_HARD_ERROR` aborts leave nothing to
+    /// mark) and `!refunded`. Only `sign_transaction` (and
+    /// `sign_transaction_full_message`, which shares its callback),
+    /// `sign_transactions_batch`, `verify_agent_signature`, and
+    /// `verify_agent_signature_plaintext` set `terminated` today; calling
+    /// this for a computation queued
+    /// through `sign_transaction_confidential`, `_ed25519ctx`, either
+    /// two-phase flow, `verify_agent_signature_full_message`,
+    /// `_multi_observer`, or `verify_agent_signatures_one_key` always
+    /// fails with `ComputationNotTerminal`, even once those genuinely
+    /// abort — wiring each of those callbacks up the same way is
+    /// follow-on work, not done here.
+    pub fn claim_computation_refund(ctx: Context<ClaimComputationRefund>, computation_offset: u64) -> Result<()> {
+        let _ = computation_offset;
+        require!(
+            ctx.accounts.abort_mode_config.terminated,
+            ErrorCode::ComputationNotTerminal
+        );
+        require!(
+            !ctx.accounts.abort_mode_config.refunded,
+            ErrorCode::RefundAlreadyClaimed
+        );
+        ctx.accounts.abort_mode_config.refunded = true;
+
+        let refund_lamports = ctx.accounts.abort_mode_config.to_account_info().lamports();
+        emit!(ComputationRefundClaimedEvent {
+            payer: ctx.accounts.payer.key(),
+            refund_lamports,
+        });
+        Ok(())
+    }
+
+    /// Confidential counterpart to `sign_transaction`: same preimage
+    /// (`signing_domain || message || aad`), but the signature is
+    /// returned as `Enc<Shared, [u8; 64]>` to `requester` instead of
+    /// revealed in a public `TransactionSignedEvent`, so only the
+    /// requesting agent can decrypt it until it chooses to broadcast.
+    ///
+    /// This is a sibling instruction rather than a `confidential: bool`
+    /// flag on `sign_transaction` itself: each instruction's comp-def
+    /// account is a compile-time-fixed PDA (`#[queue_computation_accounts]`
+    /// below), the same reason `verify_agent_signature_full_message` and
+    /// `_multi_observer` are separate entry points rather than flags on
+    /// `verify_agent_signature`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn sign_transaction_confidential(
+        ctx: Context<SignTransactionConfidential>,
+        computation_offset: u64,
+        agent: Pubkey,
+        message: [u8; 32],
+        aad: Option<[u8; 32]>,
+        requester_pub_key: [u8; 32],
+        requester_nonce: u128,
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        check_min_nodes(&ctx.accounts.cluster_account, ctx.accounts.mxe_config.min_nodes)?;
+        check_signing_window(&ctx.accounts.mxe_config)?;
+        let _ = agent;
+
+        if let Some(policy) = &ctx.accounts.signing_policy {
+            let allowed = policy.prefixes[..policy.count as usize]
+                .iter()
+                .zip(&policy.prefix_lens[..policy.count as usize])
+                .any(|(prefix, &len)| message[..len as usize] == prefix[..len as usize]);
+            require!(allowed, ErrorCode::DisallowedMessage);
+        }
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        let mut builder = ArgBuilder::new();
+        for byte in ctx.accounts.mxe_config.signing_domain {
+            builder = builder.plaintext_u8(byte);
+        }
+        for byte in message {
+            builder = builder.plaintext_u8(byte);
+        }
+        for byte in aad.unwrap_or([0u8; 32]) {
+            builder = builder.plaintext_u8(byte);
+        }
+        let args = builder
+            .x25519_pubkey(requester_pub_key)
+            .plaintext_u128(requester_nonce)
+            .build();
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        ixs.push(SignTransactionConfidentialCallback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &[AccountMeta::new(ctx.accounts.signing_stats.key(), false)],
+        )?);
+        queue_computation(ctx.accounts, computation_offset, args, ixs, 1, 0)?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "sign_transaction_confidential")]
+    pub fn sign_transaction_confidential_callback(
+        ctx: Context<SignTransactionConfidentialCallback>,
+        output: SignedComputationOutputs<SignTransactionConfidentialOutput>,
+    ) -> Result<()> {
+        let o = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account,
+        ) {
+            Ok(SignTransactionConfidentialOutput { field_0 }) => field_0,
+            Err(_) => {
+                ctx.accounts.signing_stats.total_aborts += 1;
+                return Err(ErrorCode::AbortedComputation.into());
+            }
+        };
+
+        ctx.accounts.signing_stats.total_signatures += 1;
+        emit!(SigningCiphertextReadyEvent {
+            signature_ciphertext: [o.ciphertexts[0], o.ciphertexts[1]],
+            nonce: o.nonce.to_le_bytes(),
+        });
+        Ok(())
+    }
+
+    /// Signs `message` per RFC 8032 §5.1's Ed25519ctx scheme (see
+    /// `sign_transaction_ed25519ctx` in `encrypted-ixs` for the exact
+    /// preimage and its caveats), instead of this program's ad-hoc
+    /// `signing_domain || message || aad` preimage — for callers that
+    /// need a signature a standards-compliant Ed25519ctx verifier can
+    /// check directly. A sibling instruction rather than a flag on
+    /// `sign_transaction`, for the same comp-def-is-a-fixed-PDA reason as
+    /// `sign_transaction_confidential`.
+    ///
+    /// `verify_agent_signature` is intentionally left unextended by this
+    /// change: it already takes a raw `message: [u8; 32]` and has no
+    /// notion of a preimage-construction scheme, so an Ed25519ctx-aware
+    /// caller can dom2-wrap the message itself before calling it today.
+    /// No RFC 8032 test vectors were added alongside this circuit — the
+    /// RFC's known-answer vectors are signed under a fixed, published
+    /// private key, which cannot match this MXE's key (held only as
+    /// secret shares across the MPC cluster, generated at deployment and
+    /// never available in one place to check against a known vector).
+    #[allow(clippy::too_many_arguments)]
+    pub fn sign_transaction_ed25519ctx(
+        ctx: Context<SignTransactionEd25519ctx>,
+        computation_offset: u64,
+        agent: Pubkey,
+        context: [u8; 32],
+        context_len: u8,
+        message: [u8; 32],
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        check_min_nodes(&ctx.accounts.cluster_account, ctx.accounts.mxe_config.min_nodes)?;
+        check_signing_window(&ctx.accounts.mxe_config)?;
+        let _ = agent;
+        require!(
+            context_len <= MAX_ED25519CTX_CONTEXT_LEN,
+            ErrorCode::ContextTooLong
+        );
+
+        if let Some(policy) = &ctx.accounts.signing_policy {
+            let allowed = policy.prefixes[..policy.count as usize]
+                .iter()
+                .zip(&policy.prefix_lens[..policy.count as usize])
+                .any(|(prefix, &len)| message[..len as usize] == prefix[..len as usize]);
+            require!(allowed, ErrorCode::DisallowedMessage);
+        }
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        let mut builder = ArgBuilder::new();
+        for byte in context {
+            builder = builder.plaintext_u8(byte);
+        }
+        builder = builder.plaintext_u8(context_len);
+        for byte in message {
+            builder = builder.plaintext_u8(byte);
+        }
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        ixs.push(SignTransactionEd25519ctxCallback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &[AccountMeta::new(ctx.accounts.signing_stats.key(), false)],
+        )?);
+        queue_computation(ctx.accounts, computation_offset, builder.build(), ixs, 1, 0)?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "sign_transaction_ed25519ctx")]
+    pub fn sign_transaction_ed25519ctx_callback(
+        ctx: Context<SignTransactionEd25519ctxCallback>,
+        output: SignedComputationOutputs<SignTransactionEd25519ctxOutput>,
     ) -> Result<()> {
         let signature = match output.verify_output(
             &ctx.accounts.cluster_account,
             &ctx.accounts.computation_account,
         ) {
-            Ok(SignTransactionOutput {
+            Ok(SignTransactionEd25519ctxOutput {
                 field_0:
-                    SignTransactionOutputStruct0 {
+                    SignTransactionEd25519ctxOutputStruct0 {
                         field_0: r_encoded,
                         field_1: s,
                     },
@@ -63,86 +1323,2806 @@ pub mod agentic_wallet_mxe {
                 signature[32..].copy_from_slice(&s);
                 signature
             }
-            Err(_) => return Err(ErrorCode::AbortedComputation.into()),
+            Err(_) => {
+                ctx.accounts.signing_stats.total_aborts += 1;
+                return Err(ErrorCode::AbortedComputation.into());
+            }
+        };
+
+        ctx.accounts.signing_stats.total_signatures += 1;
+        emit!(TransactionSignedEvent { signature });
+        Ok(())
+    }
+
+    /// Phase one of a two-phase signing flow: queues the same `sign_transaction`
+    /// circuit, but the callback only reveals the signature's public nonce
+    /// `R`, committing it to a `SigningSession` PDA. Callers that need `R`
+    /// before the full signature (adaptor signatures, payment channels) use
+    /// this instead of `sign_transaction`.
+    pub fn sign_transaction_two_phase_commit(
+        ctx: Context<SignTransactionTwoPhaseCommit>,
+        computation_offset: u64,
+        message: [u8; 32],
+        aad: Option<[u8; 32]>,
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        let aad = aad.unwrap_or([0u8; 32]);
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.signing_session.message = message;
+        ctx.accounts.signing_session.aad = aad;
+        ctx.accounts.signing_session.r = [0u8; 32];
+        ctx.accounts.signing_session.completed = false;
+        ctx.accounts.signing_session.bump = ctx.bumps.signing_session;
+
+        let mut builder = ArgBuilder::new();
+        for byte in ctx.accounts.mxe_config.signing_domain {
+            builder = builder.plaintext_u8(byte);
+        }
+        for byte in message {
+            builder = builder.plaintext_u8(byte);
+        }
+        for byte in aad {
+            builder = builder.plaintext_u8(byte);
+        }
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        ixs.push(SignTransactionPhase1Callback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &[
+                AccountMeta::new(ctx.accounts.signing_session.key(), false),
+                AccountMeta::new(ctx.accounts.signing_stats.key(), false),
+            ],
+        )?);
+        queue_computation(ctx.accounts, computation_offset, builder.build(), ixs, 1, 0)?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "sign_transaction")]
+    pub fn sign_transaction_phase1_callback(
+        ctx: Context<SignTransactionPhase1Callback>,
+        output: SignedComputationOutputs<SignTransactionOutput>,
+    ) -> Result<()> {
+        let r = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account,
+        ) {
+            Ok(SignTransactionOutput {
+                field_0: SignTransactionOutputStruct0 { field_0: r_encoded, .. },
+            }) => r_encoded,
+            Err(_) => {
+                ctx.accounts.signing_stats.total_aborts += 1;
+                return Err(ErrorCode::AbortedComputation.into());
+            }
+        };
+
+        ctx.accounts.signing_session.r = r;
+        emit!(SigningNonceRevealedEvent {
+            message: ctx.accounts.signing_session.message,
+            r,
+        });
+        Ok(())
+    }
+
+    /// Phase two: re-runs the deterministic `sign_transaction` circuit over
+    /// the same message and reveals the full `(R, S)` signature, after
+    /// checking `R` matches the commitment recorded in phase one.
+    pub fn sign_transaction_two_phase_complete(
+        ctx: Context<SignTransactionTwoPhaseComplete>,
+        computation_offset: u64,
+        message: [u8; 32],
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.signing_session.message == message,
+            ErrorCode::SigningSessionMismatch
+        );
+
+        let mut builder = ArgBuilder::new();
+        for byte in ctx.accounts.mxe_config.signing_domain {
+            builder = builder.plaintext_u8(byte);
+        }
+        for byte in message {
+            builder = builder.plaintext_u8(byte);
+        }
+        for byte in ctx.accounts.signing_session.aad {
+            builder = builder.plaintext_u8(byte);
+        }
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        ixs.push(SignTransactionPhase2Callback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &[
+                AccountMeta::new(ctx.accounts.signing_session.key(), false),
+                AccountMeta::new(ctx.accounts.signing_stats.key(), false),
+            ],
+        )?);
+        queue_computation(ctx.accounts, computation_offset, builder.build(), ixs, 1, 0)?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "sign_transaction")]
+    pub fn sign_transaction_phase2_callback(
+        ctx: Context<SignTransactionPhase2Callback>,
+        output: SignedComputationOutputs<SignTransactionOutput>,
+    ) -> Result<()> {
+        let signature = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account,
+        ) {
+            Ok(SignTransactionOutput {
+                field_0:
+                    SignTransactionOutputStruct0 {
+                        field_0: r_encoded,
+                        field_1: s,
+                    },
+            }) => {
+                require!(
+                    r_encoded == ctx.accounts.signing_session.r,
+                    ErrorCode::NonceCommitmentMismatch
+                );
+                let mut signature = [0u8; 64];
+                signature[..32].copy_from_slice(&r_encoded);
+                signature[32..].copy_from_slice(&s);
+                signature
+            }
+            Err(_) => {
+                ctx.accounts.signing_stats.total_aborts += 1;
+                return Err(ErrorCode::AbortedComputation.into());
+            }
+        };
+
+        ctx.accounts.signing_session.completed = true;
+        ctx.accounts.signing_stats.total_signatures += 1;
+        emit!(TransactionSignedEvent { signature });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "sign_transaction")]
+    pub fn sign_transaction_callback(
+        ctx: Context<SignTransactionCallback>,
+        output: SignedComputationOutputs<SignTransactionOutput>,
+    ) -> Result<()> {
+        let signature = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account,
+        ) {
+            Ok(SignTransactionOutput {
+                field_0:
+                    SignTransactionOutputStruct0 {
+                        field_0: r_encoded,
+                        field_1: s,
+                    },
+            }) => {
+                let mut signature = [0u8; 64];
+                signature[..32].copy_from_slice(&r_encoded);
+                signature[32..].copy_from_slice(&s);
+                signature
+            }
+            Err(_) => {
+                ctx.accounts.signing_stats.total_aborts += 1;
+                return match ctx.accounts.abort_mode_config.abort_mode {
+                    ABORT_MODE_SOFT_FAIL => {
+                        ctx.accounts.abort_mode_config.terminated = true;
+                        emit!(TransactionSignAbortedEvent {});
+                        Ok(())
+                    }
+                    _ => Err(ErrorCode::AbortedComputation.into()),
+                };
+            }
+        };
+
+        ctx.accounts.signing_stats.total_signatures += 1;
+        ctx.accounts.signature_record.signature = signature;
+        ctx.accounts.signature_record.slot = Clock::get()?.slot;
+        match ctx.accounts.signing_log.as_mut() {
+            Some(signing_log) => append_signing_log(signing_log, signing_log_digest(&signature)),
+            None => emit!(TransactionSignedEvent { signature }),
+        }
+        // Emitted unconditionally, independent of the signing_log branch
+        // above, so a caller running many concurrent requests can match a
+        // result back to the call that produced it via `computation_offset`
+        // and `requester` without needing to already track pending offsets
+        // itself or fall back to polling `SignatureRecord`.
+        emit!(SignatureRecordedEvent {
+            computation_offset: ctx.accounts.signature_record.computation_offset,
+            requester: ctx.accounts.signature_record.requester,
+            signature,
+        });
+        Ok(())
+    }
+
+    /// Signs up to `MAX_SIGNATURE_BATCH` distinct messages in a single
+    /// computation — the signing-side counterpart to
+    /// `verify_agent_signatures_one_key`'s verification batching, cheaper
+    /// than calling `sign_transaction` once per message. Shares
+    /// `sign_transaction`'s preconditions (`check_min_nodes`,
+    /// `check_signing_window`, `abort_mode`, `signing_policy`) applied
+    /// once per message, and its exact per-message preimage layout
+    /// (`signing_domain || messages[i] || aad`) — see that instruction's
+    /// doc comment.
+    ///
+    /// `messages` must be non-empty and within both `MAX_SIGNATURE_BATCH`
+    /// and `MXEConfig::max_batch_size`, same bounds
+    /// `verify_agent_signatures_one_key` enforces, checked against the
+    /// same two errors. Unused padding slots up to `MAX_SIGNATURE_BATCH`
+    /// are still signed by the circuit (see its doc comment) and still
+    /// land in the callback's output array or `SigningLog` entries; the
+    /// caller, who already knows how many messages it submitted, is
+    /// responsible for ignoring anything past that count.
+    pub fn sign_transactions_batch(
+        ctx: Context<SignTransactionsBatch>,
+        computation_offset: u64,
+        agent: Pubkey,
+        messages: Vec<[u8; 32]>,
+        aad: Option<[u8; 32]>,
+        abort_mode: u8,
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        check_min_nodes(&ctx.accounts.cluster_account, ctx.accounts.mxe_config.min_nodes)?;
+        check_signing_window(&ctx.accounts.mxe_config)?;
+        let _ = agent;
+        require!(
+            !messages.is_empty() && messages.len() <= MAX_SIGNATURE_BATCH,
+            ErrorCode::InvalidSignatureBatch
+        );
+        require!(
+            messages.len() <= ctx.accounts.mxe_config.max_batch_size as usize,
+            ErrorCode::BatchTooLarge
+        );
+        require!(
+            matches!(abort_mode, ABORT_MODE_HARD_ERROR | ABORT_MODE_SOFT_FAIL),
+            ErrorCode::InvalidAbortMode
+        );
+
+        if let Some(policy) = &ctx.accounts.signing_policy {
+            for message in &messages {
+                let allowed = policy.prefixes[..policy.count as usize]
+                    .iter()
+                    .zip(&policy.prefix_lens[..policy.count as usize])
+                    .any(|(prefix, &len)| message[..len as usize] == prefix[..len as usize]);
+                require!(allowed, ErrorCode::DisallowedMessage);
+            }
+        }
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.abort_mode_config.abort_mode = abort_mode;
+        ctx.accounts.abort_mode_config.bump = ctx.bumps.abort_mode_config;
+        ctx.accounts.abort_mode_config.payer = ctx.accounts.payer.key();
+        let mut builder = ArgBuilder::new();
+        for byte in ctx.accounts.mxe_config.signing_domain {
+            builder = builder.plaintext_u8(byte);
+        }
+        for i in 0..MAX_SIGNATURE_BATCH {
+            let message = messages.get(i).copied().unwrap_or([0u8; 32]);
+            for byte in message {
+                builder = builder.plaintext_u8(byte);
+            }
+        }
+        for byte in aad.unwrap_or([0u8; 32]) {
+            builder = builder.plaintext_u8(byte);
+        }
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        let signing_log_meta = match &ctx.accounts.signing_log {
+            Some(signing_log) => AccountMeta::new(signing_log.key(), false),
+            None => AccountMeta::new_readonly(crate::ID, false),
+        };
+        let callback_metas = vec![
+            AccountMeta::new(ctx.accounts.signing_stats.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.abort_mode_config.key(), false),
+            signing_log_meta,
+        ];
+        ixs.push(SignTransactionsBatchCallback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &callback_metas,
+        )?);
+        queue_computation(ctx.accounts, computation_offset, builder.build(), ixs, 1, 0)?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "sign_transactions_batch")]
+    pub fn sign_transactions_batch_callback(
+        ctx: Context<SignTransactionsBatchCallback>,
+        output: SignedComputationOutputs<SignTransactionsBatchOutput>,
+    ) -> Result<()> {
+        let signatures = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account,
+        ) {
+            Ok(SignTransactionsBatchOutput { field_0 }) => field_0,
+            Err(_) => {
+                ctx.accounts.signing_stats.total_aborts += 1;
+                return match ctx.accounts.abort_mode_config.abort_mode {
+                    ABORT_MODE_SOFT_FAIL => {
+                        ctx.accounts.abort_mode_config.terminated = true;
+                        emit!(TransactionSignAbortedEvent {});
+                        Ok(())
+                    }
+                    _ => Err(ErrorCode::AbortedComputation.into()),
+                };
+            }
+        };
+
+        ctx.accounts.signing_stats.total_signatures += MAX_SIGNATURE_BATCH as u64;
+        match ctx.accounts.signing_log.as_mut() {
+            Some(signing_log) => {
+                for signature in &signatures {
+                    append_signing_log(signing_log, signing_log_digest(signature));
+                }
+            }
+            None => emit!(TransactionsBatchSignedEvent { signatures }),
+        }
+        Ok(())
+    }
+
+    /// Admin-only: queues the `rotate_signing_key` circuit, which produces a
+    /// fresh distributed signing key for this MXE. The new key is versioned
+    /// rather than overwriting the current one — see [`SigningKeyHistory`]'s
+    /// doc comment — so signatures already produced under an earlier key
+    /// remain attributable to that specific past version instead of being
+    /// silently orphaned.
+    ///
+    /// TODO: `rotate_signing_key`'s circuit (`encrypted-ixs/src/lib.rs`) is
+    /// presently a documented placeholder — this codebase has no verified
+    /// primitive for generating and re-sharing a new distributed Ed25519
+    /// key from inside an `#[encrypted]` module, only `MXESigningKey::sign`
+    /// against the key the MXE's own deployment-time ceremony already
+    /// produced. The queue/callback/versioning wiring below is real and
+    /// ready for when that primitive exists; until then every rotation
+    /// records an all-zero public key.
+    pub fn rotate_signing_key(
+        ctx: Context<RotateSigningKey>,
+        computation_offset: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.admin == ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        let builder = ArgBuilder::new();
+        let mut ixs = callback_compute_budget_ixs(None);
+        let callback_metas = vec![AccountMeta::new(
+            ctx.accounts.signing_key_history.key(),
+            false,
+        )];
+        ixs.push(RotateSigningKeyCallback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &callback_metas,
+        )?);
+        queue_computation(ctx.accounts, computation_offset, builder.build(), ixs, 1, 0)?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "rotate_signing_key")]
+    pub fn rotate_signing_key_callback(
+        ctx: Context<RotateSigningKeyCallback>,
+        output: SignedComputationOutputs<RotateSigningKeyOutput>,
+    ) -> Result<()> {
+        let RotateSigningKeyOutput { field_0: public_key } = output
+            .verify_output(&ctx.accounts.cluster_account, &ctx.accounts.computation_account)
+            .map_err(|_| ErrorCode::AbortedComputation)?;
+
+        let history = &mut ctx.accounts.signing_key_history;
+        require!(
+            (history.count as usize) < MAX_KEY_HISTORY,
+            ErrorCode::KeyHistoryFull
+        );
+        let version = history.current_version + 1;
+        let slot = history.count as usize;
+        history.versions[slot] = version;
+        history.public_keys[slot] = public_key;
+        history.count += 1;
+        history.current_version = version;
+
+        emit!(KeyRotatedEvent { version, public_key });
+        Ok(())
+    }
+
+    /// Parallel to `sign_transaction`, but queues `sign_transaction_secp256k1`
+    /// instead — see that circuit's doc comment in `encrypted-ixs` for why
+    /// the 65-byte recoverable signature (`r || s || recovery_id`, the shape
+    /// EVM tooling's `ecrecover` expects) it reveals is always all-zero
+    /// today. Shares `sign_transaction`'s `signing_domain || message || aad`
+    /// preimage layout and `check_min_nodes`/`check_signing_window`/
+    /// `signing_policy` preconditions, but not its `SigningLog`/
+    /// `SignatureRecord` machinery — Ethereum-compatible agents calling this
+    /// are a narrower audience than this program's existing Ed25519 signing
+    /// surface, so this starts at the same accounting
+    /// `sign_transaction_confidential` does (`SigningStats` only) rather
+    /// than carrying over infrastructure nothing has asked for yet.
+    pub fn sign_transaction_secp256k1(
+        ctx: Context<SignTransactionSecp256k1>,
+        computation_offset: u64,
+        agent: Pubkey,
+        message: [u8; 32],
+        aad: Option<[u8; 32]>,
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        check_min_nodes(&ctx.accounts.cluster_account, ctx.accounts.mxe_config.min_nodes)?;
+        check_signing_window(&ctx.accounts.mxe_config)?;
+        let _ = agent;
+
+        if let Some(policy) = &ctx.accounts.signing_policy {
+            let allowed = policy.prefixes[..policy.count as usize]
+                .iter()
+                .zip(&policy.prefix_lens[..policy.count as usize])
+                .any(|(prefix, &len)| message[..len as usize] == prefix[..len as usize]);
+            require!(allowed, ErrorCode::DisallowedMessage);
+        }
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        let mut builder = ArgBuilder::new();
+        for byte in ctx.accounts.mxe_config.signing_domain {
+            builder = builder.plaintext_u8(byte);
+        }
+        for byte in message {
+            builder = builder.plaintext_u8(byte);
+        }
+        for byte in aad.unwrap_or([0u8; 32]) {
+            builder = builder.plaintext_u8(byte);
+        }
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        ixs.push(SignTransactionSecp256k1Callback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &[AccountMeta::new(ctx.accounts.signing_stats.key(), false)],
+        )?);
+        queue_computation(ctx.accounts, computation_offset, builder.build(), ixs, 1, 0)?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "sign_transaction_secp256k1")]
+    pub fn sign_transaction_secp256k1_callback(
+        ctx: Context<SignTransactionSecp256k1Callback>,
+        output: SignedComputationOutputs<SignTransactionSecp256k1Output>,
+    ) -> Result<()> {
+        let signature = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account,
+        ) {
+            Ok(SignTransactionSecp256k1Output { field_0 }) => field_0,
+            Err(_) => {
+                ctx.accounts.signing_stats.total_aborts += 1;
+                return Err(ErrorCode::AbortedComputation.into());
+            }
+        };
+        ctx.accounts.signing_stats.total_signatures += 1;
+        emit!(TransactionSignedSecp256k1Event { signature });
+        Ok(())
+    }
+
+    /// Admin-only: folds every `SigningLog` entry written since the last
+    /// flush (or since `init_signing_log`, if this is the first one) into a
+    /// single binary Merkle root and emits one `SigningEventsFlushedEvent`,
+    /// instead of the per-signature `TransactionSignedEvent`s a caller gets
+    /// by leaving `signing_log` off `sign_transaction`'s callback. See
+    /// `SigningLog`'s doc comment for the buffer format this reads and how
+    /// a consumer reconstructs, or verifies, an individual signature
+    /// against what gets flushed here.
+    ///
+    /// `start`/`end` in the emitted event are `SigningLog` ring positions,
+    /// not absolute signature counts: `start` is wherever the previous
+    /// flush (or `init_signing_log`) left `last_flushed_head`, `end` is
+    /// `head` as of this call. If exactly `capacity` signatures land
+    /// between two flushes, `start == end` looks identical to "nothing
+    /// pending" even though every one of those entries is real and
+    /// unflushed — this call then errors with `NoEntriesToFlush` rather
+    /// than silently flushing zero entries, so operators should flush well
+    /// before volume approaches `capacity` to avoid losing a batch to that
+    /// ambiguity.
+    pub fn flush_signing_events(ctx: Context<FlushSigningEvents>) -> Result<()> {
+        require!(
+            ctx.accounts.mxe_config.admin == ctx.accounts.admin.key(),
+            ErrorCode::Unauthorized
+        );
+        let log = &mut ctx.accounts.signing_log;
+        let start = log.last_flushed_head;
+        let end = log.head;
+        let capacity = log.capacity;
+        let pending = if end >= start {
+            end - start
+        } else {
+            capacity - start + end
         };
+        require!(pending > 0, ErrorCode::NoEntriesToFlush);
+
+        let leaves: Vec<[u8; 32]> = (0..pending)
+            .map(|i| log.entries[((start + i) % capacity) as usize])
+            .collect();
+        let merkle_root = merkle_root(&leaves);
+        log.last_flushed_head = end;
+
+        emit!(SigningEventsFlushedEvent {
+            merkle_root,
+            count: pending,
+            start_head: start,
+            end_head: end,
+        });
+        Ok(())
+    }
+
+    /// Registers a long-lived observer identity under a stable `observer_id`,
+    /// decoupling "who gets the decrypted verification result" from any one
+    /// x25519 key. The registering signer becomes the only authority who can
+    /// rotate it later via `rotate_observer_key`. `verify_agent_signature`
+    /// looks the current key up by `observer_id` instead of taking an
+    /// x25519 key directly, so a compromised observer key can be replaced
+    /// without changing how callers refer to the observer.
+    pub fn register_observer(
+        ctx: Context<RegisterObserver>,
+        observer_id: u32,
+        pubkey: [u8; 32],
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.observer_registry;
+        registry.observer_id = observer_id;
+        registry.pubkey = pubkey;
+        registry.authority = ctx.accounts.authority.key();
+        registry.bump = ctx.bumps.observer_registry;
+        Ok(())
+    }
+
+    /// Observer-gated: only `ObserverRegistry::authority` (the signer that
+    /// called `register_observer`) can rotate its own key. Past results
+    /// already encrypted to the old key remain exposed to whoever held it;
+    /// only `verify_agent_signature` calls queued after this lands will
+    /// deliver results encrypted to `new_pubkey`.
+    pub fn rotate_observer_key(ctx: Context<RotateObserverKey>, new_pubkey: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.observer_registry.authority == ctx.accounts.authority.key(),
+            ErrorCode::Unauthorized
+        );
+        ctx.accounts.observer_registry.pubkey = new_pubkey;
+        emit!(ObserverKeyRotatedEvent {
+            observer_id: ctx.accounts.observer_registry.observer_id,
+        });
+        Ok(())
+    }
+
+    pub fn init_verify_agent_signature_comp_def(
+        ctx: Context<InitVerifyAgentSignatureCompDef>,
+    ) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    /// `observer_id` is looked up in `ObserverRegistry` for the x25519 key
+    /// the result is encrypted to, rather than taking that key directly —
+    /// see `register_observer`/`rotate_observer_key`. The sibling
+    /// `verify_agent_signature_full_message` and
+    /// `verify_agent_signature_multi_observer` entry points still take a raw
+    /// observer key; wiring them through the registry too is left for a
+    /// follow-up, since each is its own comp-def-bound instruction and a
+    /// registry lookup doesn't change the shape of what's queued to the
+    /// circuit, only where the key comes from.
+    ///
+    /// `abort_mode` works the same way as `sign_transaction`'s: see
+    /// `ABORT_MODE_HARD_ERROR`/`ABORT_MODE_SOFT_FAIL`'s doc comments.
+    /// `verify_agent_signature_callback` emits
+    /// `SignatureVerificationAbortedEvent` instead of `SignatureVerifiedEvent`
+    /// under soft-fail, and skips the result-program CPI entirely since
+    /// there is no `is_valid` ciphertext to forward.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_agent_signature(
+        ctx: Context<VerifyAgentSignature>,
+        computation_offset: u64,
+        one_time_pub_key: [u8; 32],
+        one_time_nonce: u128,
+        verifying_key_enc_lo: [u8; 32],
+        verifying_key_enc_hi: [u8; 32],
+        message: [u8; 32],
+        signature: [u8; 64],
+        observer_id: u32,
+        observer_nonce: u128,
+        abort_mode: u8,
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        check_min_nodes(&ctx.accounts.cluster_account, ctx.accounts.mxe_config.min_nodes)?;
+        require!(
+            matches!(abort_mode, ABORT_MODE_HARD_ERROR | ABORT_MODE_SOFT_FAIL),
+            ErrorCode::InvalidAbortMode
+        );
+        let result_metas = validate_result_target(&ctx.accounts.mxe_config, ctx.remaining_accounts)?;
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.abort_mode_config.abort_mode = abort_mode;
+        ctx.accounts.abort_mode_config.bump = ctx.bumps.abort_mode_config;
+        ctx.accounts.abort_mode_config.payer = ctx.accounts.payer.key();
+        let mut builder = ArgBuilder::new()
+            .x25519_pubkey(one_time_pub_key)
+            .plaintext_u128(one_time_nonce)
+            .encrypted_u128(verifying_key_enc_lo)
+            .encrypted_u128(verifying_key_enc_hi);
+        for byte in message {
+            builder = builder.plaintext_u8(byte);
+        }
+        let args = builder
+            .arcis_ed25519_signature(signature)
+            .x25519_pubkey(ctx.accounts.observer_registry.pubkey)
+            .plaintext_u128(observer_nonce)
+            .build();
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        let mut callback_metas = vec![AccountMeta::new_readonly(
+            ctx.accounts.abort_mode_config.key(),
+            false,
+        )];
+        callback_metas.extend(result_metas);
+        ixs.push(VerifyAgentSignatureCallback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &callback_metas,
+        )?);
+        queue_computation(ctx.accounts, computation_offset, args, ixs, 1, 0)?;
+        Ok(())
+    }
+
+    /// Symmetric with the full-message signing request: verifies a
+    /// signature against a digest this instruction computes from the
+    /// original message bytes, rather than trusting a caller-supplied
+    /// 32-byte hash that might not match the real content. Queues the same
+    /// `verify_agent_signature` circuit/comp-def as the raw-message entry
+    /// point — only the message derivation differs. Still takes a raw
+    /// `observer_pub_key` rather than an `ObserverRegistry` lookup; see
+    /// `verify_agent_signature`'s doc comment.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_agent_signature_full_message(
+        ctx: Context<VerifyAgentSignatureFullMessage>,
+        computation_offset: u64,
+        one_time_pub_key: [u8; 32],
+        one_time_nonce: u128,
+        verifying_key_enc_lo: [u8; 32],
+        verifying_key_enc_hi: [u8; 32],
+        message: Vec<u8>,
+        hash_algorithm: u8,
+        signature: [u8; 64],
+        observer_pub_key: [u8; 32],
+        observer_nonce: u128,
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        check_min_nodes(&ctx.accounts.cluster_account, ctx.accounts.mxe_config.min_nodes)?;
+        require!(
+            message.len() <= MAX_FULL_MESSAGE_LEN,
+            ErrorCode::MessageTooLong
+        );
+        let digest = hash_full_message(&message, hash_algorithm)?;
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        let mut builder = ArgBuilder::new()
+            .x25519_pubkey(one_time_pub_key)
+            .plaintext_u128(one_time_nonce)
+            .encrypted_u128(verifying_key_enc_lo)
+            .encrypted_u128(verifying_key_enc_hi);
+        for byte in digest {
+            builder = builder.plaintext_u8(byte);
+        }
+        let args = builder
+            .arcis_ed25519_signature(signature)
+            .x25519_pubkey(observer_pub_key)
+            .plaintext_u128(observer_nonce)
+            .build();
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        ixs.push(VerifyAgentSignatureCallback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &[],
+        )?);
+        queue_computation(ctx.accounts, computation_offset, args, ixs, 1, 0)?;
+        Ok(())
+    }
+
+    /// Beyond `SignatureVerifiedEvent` and return data, a caller of
+    /// `verify_agent_signature` may also pass a `result_program` (and any
+    /// accounts it needs) through `ctx.remaining_accounts`, allowlisted via
+    /// `MXEConfig::result_program_allowlist`, to have this callback actively
+    /// CPI the result forward so it can trigger downstream on-chain logic
+    /// (e.g. unlocking a gate) instead of waiting for an off-chain listener.
+    ///
+    /// What gets forwarded is `is_valid`'s *ciphertext* and nonce, not a
+    /// decrypted boolean: this callback runs on-chain and never holds the
+    /// `observer` key's shared secret, so it cannot decrypt
+    /// `Enc<Shared, bool>` itself. `result_program` must decrypt
+    /// client-side (or be the observer) to act on the actual boolean; what
+    /// this CPI gives it for free is the guarantee that it was invoked
+    /// synchronously with a specific, freshly-verified computation.
+    #[arcium_callback(encrypted_ix = "verify_agent_signature")]
+    pub fn verify_agent_signature_callback(
+        ctx: Context<VerifyAgentSignatureCallback>,
+        output: SignedComputationOutputs<VerifyAgentSignatureOutput>,
+    ) -> Result<()> {
+        let o = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account,
+        ) {
+            Ok(VerifyAgentSignatureOutput { field_0 }) => field_0,
+            Err(_) => {
+                return match ctx.accounts.abort_mode_config.abort_mode {
+                    ABORT_MODE_SOFT_FAIL => {
+                        ctx.accounts.abort_mode_config.terminated = true;
+                        emit!(SignatureVerificationAbortedEvent {});
+                        Ok(())
+                    }
+                    _ => Err(ErrorCode::AbortedComputation.into()),
+                };
+            }
+        };
+
+        emit!(SignatureVerifiedEvent {
+            is_valid: o.ciphertexts[0],
+            nonce: o.nonce.to_le_bytes(),
+        });
+
+        if !ctx.remaining_accounts.is_empty() {
+            let result_program = ctx.remaining_accounts[0].key();
+            let mut data = verification_result_discriminator().to_vec();
+            data.extend_from_slice(&o.ciphertexts[0]);
+            data.extend_from_slice(&o.nonce.to_le_bytes());
+            let account_metas = ctx.remaining_accounts[1..]
+                .iter()
+                .map(|info| AccountMeta::new(info.key(), false))
+                .collect::<Vec<_>>();
+            let ix = solana_program::instruction::Instruction {
+                program_id: result_program,
+                accounts: account_metas,
+                data,
+            };
+            solana_program::program::invoke(&ix, ctx.remaining_accounts)?;
+        }
+        Ok(())
+    }
+
+    pub fn init_verify_agent_signature_plaintext_comp_def(
+        ctx: Context<InitVerifyAgentSignaturePlaintextCompDef>,
+    ) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    /// Same queuing shape as [`verify_agent_signature`] minus the observer
+    /// lookup — there's no one to encrypt the result to, since the whole
+    /// point of this entry point is that the result comes back in
+    /// plaintext. A result CPI target is mandatory here (unlike
+    /// `verify_agent_signature`'s optional one): a plaintext pass/fail with
+    /// nowhere to deliver it synchronously is just `SignatureVerifiedPlaintextEvent`,
+    /// which `verify_agent_signature` already gets for free via its own
+    /// ciphertext event, so this instruction only earns its keep when a
+    /// downstream program is actually wired up to receive the boolean.
+    ///
+    /// This is the "reveal `is_valid` publicly instead of to an observer"
+    /// variant alongside the confidential `verify_agent_signature` — a
+    /// second, separate instruction/circuit the way `sign_transaction`'s
+    /// reveal already works, just named `_plaintext` rather than `_public`
+    /// since that's the convention `verify_agent_signature_plaintext_callback`
+    /// and `receive_plaintext_verification_result` already established for
+    /// "confidentiality traded for composability" elsewhere in this file.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_agent_signature_plaintext(
+        ctx: Context<VerifyAgentSignaturePlaintext>,
+        computation_offset: u64,
+        one_time_pub_key: [u8; 32],
+        one_time_nonce: u128,
+        verifying_key_enc_lo: [u8; 32],
+        verifying_key_enc_hi: [u8; 32],
+        message: [u8; 32],
+        signature: [u8; 64],
+        abort_mode: u8,
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        check_min_nodes(&ctx.accounts.cluster_account, ctx.accounts.mxe_config.min_nodes)?;
+        require!(
+            matches!(abort_mode, ABORT_MODE_HARD_ERROR | ABORT_MODE_SOFT_FAIL),
+            ErrorCode::InvalidAbortMode
+        );
+        let result_metas = validate_result_target(&ctx.accounts.mxe_config, ctx.remaining_accounts)?;
+        require!(!result_metas.is_empty(), ErrorCode::ResultProgramNotAllowlisted);
+        let digest = nonce_digest(&message, &signature);
+        require!(
+            !ctx.accounts.nonce_registry.contains(&digest),
+            ErrorCode::NonceAlreadyUsed
+        );
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        ctx.accounts.abort_mode_config.abort_mode = abort_mode;
+        ctx.accounts.abort_mode_config.bump = ctx.bumps.abort_mode_config;
+        ctx.accounts.abort_mode_config.payer = ctx.accounts.payer.key();
+        ctx.accounts.verification_nonce_record.digest = digest;
+        ctx.accounts.verification_nonce_record.bump = ctx.bumps.verification_nonce_record;
+        let mut builder = ArgBuilder::new()
+            .x25519_pubkey(one_time_pub_key)
+            .plaintext_u128(one_time_nonce)
+            .encrypted_u128(verifying_key_enc_lo)
+            .encrypted_u128(verifying_key_enc_hi);
+        for byte in message {
+            builder = builder.plaintext_u8(byte);
+        }
+        let args = builder.arcis_ed25519_signature(signature).build();
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        let mut callback_metas = vec![
+            AccountMeta::new_readonly(ctx.accounts.abort_mode_config.key(), false),
+            AccountMeta::new_readonly(ctx.accounts.verification_nonce_record.key(), false),
+            AccountMeta::new(ctx.accounts.nonce_registry.key(), false),
+        ];
+        callback_metas.extend(result_metas);
+        ixs.push(VerifyAgentSignaturePlaintextCallback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &callback_metas,
+        )?);
+        queue_computation(ctx.accounts, computation_offset, args, ixs, 1, 0)?;
+        Ok(())
+    }
+
+    /// Unlike [`verify_agent_signature_callback`], which only forwards a
+    /// ciphertext that an attacker gains nothing from fabricating,
+    /// `is_valid` here is a plaintext boolean an attacker *could* try to
+    /// fabricate by calling `receive_plaintext_verification_result`
+    /// directly on the target program instead of going through a genuine
+    /// MPC computation. This callback still can't stop that — Solana gives
+    /// a callee no generic way to learn which program actually invoked it
+    /// (the same limitation documented on agent-vault's
+    /// `OwnerKind::ProgramControlled`) — so any `result_program` wired up
+    /// to receive this CPI must treat `is_valid = true` as one input among
+    /// several rather than sole authorization for anything irreversible.
+    /// `agent-vault`'s `gated_withdraw_verified` is written that way: it
+    /// additionally requires its own independent signing-authority check
+    /// before a withdrawal proceeds.
+    #[arcium_callback(encrypted_ix = "verify_agent_signature_plaintext")]
+    pub fn verify_agent_signature_plaintext_callback(
+        ctx: Context<VerifyAgentSignaturePlaintextCallback>,
+        output: SignedComputationOutputs<VerifyAgentSignaturePlaintextOutput>,
+    ) -> Result<()> {
+        let is_valid = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account,
+        ) {
+            Ok(VerifyAgentSignaturePlaintextOutput { field_0 }) => field_0,
+            Err(_) => {
+                return match ctx.accounts.abort_mode_config.abort_mode {
+                    ABORT_MODE_SOFT_FAIL => {
+                        ctx.accounts.abort_mode_config.terminated = true;
+                        emit!(SignatureVerificationAbortedEvent {});
+                        Ok(())
+                    }
+                    _ => Err(ErrorCode::AbortedComputation.into()),
+                };
+            }
+        };
+
+        emit!(SignatureVerifiedPlaintextEvent { is_valid });
+
+        if is_valid {
+            ctx.accounts
+                .nonce_registry
+                .record(ctx.accounts.verification_nonce_record.digest);
+        }
+
+        let result_program = ctx.remaining_accounts[0].key();
+        let mut data = plaintext_verification_result_discriminator().to_vec();
+        data.push(is_valid as u8);
+        let account_metas = ctx.remaining_accounts[1..]
+            .iter()
+            .map(|info| AccountMeta::new(info.key(), false))
+            .collect::<Vec<_>>();
+        let ix = solana_program::instruction::Instruction {
+            program_id: result_program,
+            accounts: account_metas,
+            data,
+        };
+        solana_program::program::invoke(&ix, ctx.remaining_accounts)?;
+        Ok(())
+    }
+
+    pub fn init_verify_agent_signature_multi_observer_comp_def(
+        ctx: Context<InitVerifyAgentSignatureMultiObserverCompDef>,
+    ) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    /// Verifies one (key, message, signature) for up to `MAX_OBSERVERS`
+    /// observers in a single computation. Any entry in `observer_malformed`
+    /// the caller already knows is bad (e.g. a client submitted garbage for
+    /// that slot) has its pubkey/nonce replaced with `observer_pub_keys[0]`
+    /// / `observer_nonces[0]` before queuing, so the fixed-arity circuit
+    /// still decodes and the other observers aren't denied a result by one
+    /// bad slot. `observer_malformed[0]` itself must be `false` — the first
+    /// slot is the fallback and can't be substituted for.
+    pub fn verify_agent_signature_multi_observer(
+        ctx: Context<VerifyAgentSignatureMultiObserver>,
+        computation_offset: u64,
+        one_time_pub_key: [u8; 32],
+        one_time_nonce: u128,
+        verifying_key_enc_lo: [u8; 32],
+        verifying_key_enc_hi: [u8; 32],
+        message: [u8; 32],
+        signature: [u8; 64],
+        observer_pub_keys: [[u8; 32]; MAX_OBSERVERS],
+        observer_nonces: [u128; MAX_OBSERVERS],
+        observer_malformed: [bool; MAX_OBSERVERS],
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        require!(!observer_malformed[0], ErrorCode::NoValidObserver);
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        let mut builder = ArgBuilder::new()
+            .x25519_pubkey(one_time_pub_key)
+            .plaintext_u128(one_time_nonce)
+            .encrypted_u128(verifying_key_enc_lo)
+            .encrypted_u128(verifying_key_enc_hi);
+        for byte in message {
+            builder = builder.plaintext_u8(byte);
+        }
+        builder = builder.arcis_ed25519_signature(signature);
+
+        for i in 0..MAX_OBSERVERS {
+            let (pub_key, nonce) = if observer_malformed[i] {
+                (observer_pub_keys[0], observer_nonces[0])
+            } else {
+                (observer_pub_keys[i], observer_nonces[i])
+            };
+            builder = builder.x25519_pubkey(pub_key).plaintext_u128(nonce);
+        }
+
+        let mut skip_mask: u8 = 0;
+        for (i, malformed) in observer_malformed.iter().enumerate() {
+            if *malformed {
+                skip_mask |= 1 << i;
+            }
+        }
+
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        ixs.push(VerifyAgentSignatureMultiObserverCallback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &[],
+        )?);
+        queue_computation(ctx.accounts, computation_offset, builder.build(), ixs, 1, 0)?;
+
+        emit!(ObserverSlotsSkippedEvent { skip_mask });
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "verify_agent_signature_multi_observer")]
+    pub fn verify_agent_signature_multi_observer_callback(
+        ctx: Context<VerifyAgentSignatureMultiObserverCallback>,
+        output: SignedComputationOutputs<VerifyAgentSignatureMultiObserverOutput>,
+    ) -> Result<()> {
+        let o = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account,
+        ) {
+            Ok(VerifyAgentSignatureMultiObserverOutput {
+                field_0: o0,
+                field_1: o1,
+                field_2: o2,
+            }) => [o0, o1, o2],
+            Err(_) => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        emit!(SignatureVerifiedMultiObserverEvent {
+            results: [o[0].ciphertexts[0], o[1].ciphertexts[0], o[2].ciphertexts[0]],
+            nonce: o[0].nonce.to_le_bytes(),
+        });
+        Ok(())
+    }
+
+    pub fn init_verify_agent_signatures_one_key_comp_def(
+        ctx: Context<InitVerifyAgentSignaturesOneKeyCompDef>,
+    ) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    /// Verifies that one agent key signed up to `MAX_SIGNATURE_BATCH`
+    /// distinct messages in a single computation, cheaper than repeating
+    /// `verify_agent_signature` once per message. `messages`/`signatures`
+    /// beyond `count` are padding and ignored by the circuit.
+    pub fn verify_agent_signatures_one_key(
+        ctx: Context<VerifyAgentSignaturesOneKey>,
+        computation_offset: u64,
+        one_time_pub_key: [u8; 32],
+        one_time_nonce: u128,
+        verifying_key_enc_lo: [u8; 32],
+        verifying_key_enc_hi: [u8; 32],
+        messages: Vec<[u8; 32]>,
+        signatures: Vec<[u8; 64]>,
+        observer_pub_key: [u8; 32],
+        observer_nonce: u128,
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        require!(
+            messages.len() == signatures.len() && messages.len() <= MAX_SIGNATURE_BATCH,
+            ErrorCode::InvalidSignatureBatch
+        );
+        require!(
+            messages.len() <= ctx.accounts.mxe_config.max_batch_size as usize,
+            ErrorCode::BatchTooLarge
+        );
+
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+        let mut builder = ArgBuilder::new()
+            .x25519_pubkey(one_time_pub_key)
+            .plaintext_u128(one_time_nonce)
+            .encrypted_u128(verifying_key_enc_lo)
+            .encrypted_u128(verifying_key_enc_hi);
+
+        for i in 0..MAX_SIGNATURE_BATCH {
+            let message = messages.get(i).copied().unwrap_or([0u8; 32]);
+            for byte in message {
+                builder = builder.plaintext_u8(byte);
+            }
+        }
+        for i in 0..MAX_SIGNATURE_BATCH {
+            let signature = signatures.get(i).copied().unwrap_or([0u8; 64]);
+            builder = builder.arcis_ed25519_signature(signature);
+        }
+
+        let args = builder
+            .plaintext_u8(messages.len() as u8)
+            .x25519_pubkey(observer_pub_key)
+            .plaintext_u128(observer_nonce)
+            .build();
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        ixs.push(VerifyAgentSignaturesOneKeyCallback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &[],
+        )?);
+        queue_computation(ctx.accounts, computation_offset, args, ixs, 1, 0)?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "verify_agent_signatures_one_key")]
+    pub fn verify_agent_signatures_one_key_callback(
+        ctx: Context<VerifyAgentSignaturesOneKeyCallback>,
+        output: SignedComputationOutputs<VerifyAgentSignaturesOneKeyOutput>,
+    ) -> Result<()> {
+        let o = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account,
+        ) {
+            Ok(VerifyAgentSignaturesOneKeyOutput { field_0 }) => field_0,
+            Err(_) => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        emit!(SignatureBatchVerifiedEvent {
+            bitmask: o.ciphertexts[0],
+            nonce: o.nonce.to_le_bytes(),
+        });
+        Ok(())
+    }
+
+    pub fn init_check_spend_allowed_comp_def(
+        ctx: Context<InitCheckSpendAllowedCompDef>,
+    ) -> Result<()> {
+        init_comp_def(ctx.accounts, None, None)?;
+        Ok(())
+    }
+
+    /// Queues `check_spend_allowed`: checks `spent + amount <= limit`
+    /// entirely in the encrypted domain and delivers the boolean only to
+    /// `observer`, the same way `verify_agent_signature` delivers `is_valid`
+    /// — nobody observing this program's accounts or events, including the
+    /// nodes that ran the computation, learns `limit`, `spent`, or the
+    /// result itself. `amount` is the one plaintext input, matching the
+    /// circuit's own signature; a caller wanting `amount` hidden too would
+    /// need a variant that also takes it as an `Enc<Shared, u64>`, which
+    /// this request didn't ask for.
+    ///
+    /// This instruction only delivers the ciphertext; it does not itself
+    /// gate any vault action on the result. Wiring a specific instruction
+    /// (e.g. a withdrawal) to block on `SpendCheckEvent`'s outcome is the
+    /// calling program's responsibility, the same way `SignatureVerifiedEvent`
+    /// leaves consumption of `is_valid` to whoever holds the observer key.
+    pub fn check_spend_allowed(
+        ctx: Context<CheckSpendAllowed>,
+        computation_offset: u64,
+        limit_pub_key: [u8; 32],
+        limit_nonce: u128,
+        limit_enc: [u8; 32],
+        spent_pub_key: [u8; 32],
+        spent_nonce: u128,
+        spent_enc: [u8; 32],
+        amount: u64,
+        observer_pub_key: [u8; 32],
+        observer_nonce: u128,
+        callback_compute_unit_limit: Option<u32>,
+    ) -> Result<()> {
+        check_min_nodes(&ctx.accounts.cluster_account, ctx.accounts.mxe_config.min_nodes)?;
+        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
+
+        let mut builder = ArgBuilder::new()
+            .x25519_pubkey(limit_pub_key)
+            .plaintext_u128(limit_nonce)
+            .encrypted_u128(limit_enc)
+            .x25519_pubkey(spent_pub_key)
+            .plaintext_u128(spent_nonce)
+            .encrypted_u128(spent_enc);
+        for byte in amount.to_le_bytes() {
+            builder = builder.plaintext_u8(byte);
+        }
+        let args = builder
+            .x25519_pubkey(observer_pub_key)
+            .plaintext_u128(observer_nonce)
+            .build();
+
+        let mut ixs = callback_compute_budget_ixs(callback_compute_unit_limit);
+        ixs.push(CheckSpendAllowedCallback::callback_ix(
+            computation_offset,
+            &ctx.accounts.mxe_account,
+            &[],
+        )?);
+        queue_computation(ctx.accounts, computation_offset, args, ixs, 1, 0)?;
+        Ok(())
+    }
+
+    #[arcium_callback(encrypted_ix = "check_spend_allowed")]
+    pub fn check_spend_allowed_callback(
+        ctx: Context<CheckSpendAllowedCallback>,
+        output: SignedComputationOutputs<CheckSpendAllowedOutput>,
+    ) -> Result<()> {
+        let o = match output.verify_output(
+            &ctx.accounts.cluster_account,
+            &ctx.accounts.computation_account,
+        ) {
+            Ok(CheckSpendAllowedOutput { field_0 }) => field_0,
+            Err(_) => return Err(ErrorCode::AbortedComputation.into()),
+        };
+
+        emit!(SpendCheckEvent {
+            is_allowed: o.ciphertexts[0],
+            nonce: o.nonce.to_le_bytes(),
+        });
+        Ok(())
+    }
+}
+
+#[queue_computation_accounts("sign_transaction", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, agent: Pubkey)]
+pub struct SignTransaction<'info> {
+    /// Named `payer` (rather than e.g. `fee_payer`) because
+    /// `#[queue_computation_accounts("sign_transaction", payer)]` above
+    /// requires that exact field name — it's the account `queue_computation`
+    /// debits the Arcium computation fee from internally, a detail this
+    /// program has no visibility into beyond that contract. Splitting the
+    /// computation fee itself onto a different signer would mean the
+    /// macro accepting a second payer identifier, which nothing in this
+    /// codebase's use of `arcium_anchor` shows it supports; `rent_payer`
+    /// below is this instruction's actual, available split — the PDA rent
+    /// for `sign_pda_account`/`abort_mode_config` no longer has to come
+    /// from the same signer that sponsors the computation fee.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// Pays the rent for `sign_pda_account`/`abort_mode_config` below,
+    /// split out from `payer` (see its doc comment) so a service can
+    /// sponsor the Arcium computation fee without also having to cover
+    /// account rent for a user-initiated signing call, or vice versa.
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+    // `sign_pda_account` is seeded only from the constant `SIGN_PDA_SEED` —
+    // not from `computation_offset` or anything else per-call — so there is
+    // exactly one of these for the whole program's lifetime, `init_if_needed`
+    // the first time any signing instruction runs and reused as the CPI
+    // signer authority by every one of them afterward (`sign_transaction`,
+    // `sign_transaction_confidential`, `sign_transaction_ed25519ctx`, the
+    // two-phase variants, ...). It is not a per-computation account that
+    // accumulates one instance per request; a "close it once this
+    // computation is terminal" instruction would instead tear down the one
+    // signer every future signing call on this program still depends on.
+    // There is no stranded-PDA cleanup gap here to fill for this account;
+    // the actual per-computation state (`computation_account`) is owned and
+    // reclaimed by the Arcium program, not by this one.
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = rent_payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    /// Optional, admin-configured per `agent`. When present, `sign_transaction`
+    /// rejects any `message` whose prefix isn't in this policy's allowlist.
+    /// Omitted entirely (no account passed) for agents that have no policy.
+    #[account(seeds = [SIGNING_POLICY_SEED, agent.as_ref()], bump)]
+    pub signing_policy: Option<Account<'info, SigningPolicy>>,
+    #[account(seeds = [SIGNING_STATS_SEED], bump = signing_stats.bump)]
+    pub signing_stats: Account<'info, SigningStats>,
+    #[account(
+        init,
+        payer = rent_payer,
+        space = 8 + 1 + 1 + 1 + 1 + 32,
+        seeds = [ABORT_MODE_CONFIG_SEED, &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub abort_mode_config: Account<'info, AbortModeConfig>,
+    /// Optional; present only when the caller wants this call's signature
+    /// buffered into `SigningLog` instead of emitting its own
+    /// `TransactionSignedEvent` — see that account's doc comment. Forwarded
+    /// to `SignTransactionCallback` as-is.
+    #[account(mut, seeds = [SIGNING_LOG_SEED], bump = signing_log.bump)]
+    pub signing_log: Option<Account<'info, SigningLog>>,
+    /// Durable home for this computation's signature, created here (same
+    /// pattern as `abort_mode_config` above) so `sign_transaction_callback`
+    /// has somewhere to write it besides an event a client might miss. See
+    /// [`SignatureRecord`]'s doc comment.
+    #[account(
+        init,
+        payer = rent_payer,
+        space = 8 + 8 + 32 + 64 + 8 + 1,
+        seeds = [SIGNATURE_RECORD_SEED, &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub signature_record: Account<'info, SignatureRecord>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: mempool_account
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: executing_pool
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+/// Structured pass/fail report [`sign_transaction_dry_run`] returns via
+/// return data, one field per precondition it evaluates — see that
+/// instruction's doc comment for exactly what each one does and doesn't
+/// cover.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SignTransactionDryRunReport {
+    pub cluster_healthy: bool,
+    pub within_signing_window: bool,
+    pub message_authorized: bool,
+    pub abort_mode_valid: bool,
+    pub compute_budget_valid: bool,
+    pub offset_available: bool,
+    pub would_succeed: bool,
+}
+
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, agent: Pubkey)]
+pub struct SignTransactionDryRun<'info> {
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    /// Same optional lookup `sign_transaction` does; absent entirely for
+    /// agents with no policy.
+    #[account(seeds = [SIGNING_POLICY_SEED, agent.as_ref()], bump)]
+    pub signing_policy: Option<Account<'info, SigningPolicy>>,
+    #[account(address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(seeds = [ABORT_MODE_CONFIG_SEED, &computation_offset.to_le_bytes()], bump)]
+    /// CHECK: read only for emptiness, to report `offset_available` —
+    /// unlike `sign_transaction`'s `init`, this never claims the PDA.
+    pub abort_mode_config: UncheckedAccount<'info>,
+    #[account(address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: same reasoning as `abort_mode_config` above.
+    pub computation_account: UncheckedAccount<'info>,
+}
+
+/// Identical in shape to [`SignTransaction`] — `sign_transaction_full_message`
+/// queues the exact same comp-def/callback, just with a different
+/// `#[instruction(...)]` parameter list (`message: Vec<u8>`,
+/// `hash_algorithm: u8` instead of `message: [u8; 32]`), the same reason
+/// `VerifyAgentSignatureFullMessage` is split out from `VerifyAgentSignature`.
+#[queue_computation_accounts("sign_transaction", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, agent: Pubkey)]
+pub struct SignTransactionFullMessage<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = rent_payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(seeds = [SIGNING_POLICY_SEED, agent.as_ref()], bump)]
+    pub signing_policy: Option<Account<'info, SigningPolicy>>,
+    #[account(seeds = [SIGNING_STATS_SEED], bump = signing_stats.bump)]
+    pub signing_stats: Account<'info, SigningStats>,
+    #[account(
+        init,
+        payer = rent_payer,
+        space = 8 + 1 + 1 + 1 + 1 + 32,
+        seeds = [ABORT_MODE_CONFIG_SEED, &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub abort_mode_config: Account<'info, AbortModeConfig>,
+    #[account(mut, seeds = [SIGNING_LOG_SEED], bump = signing_log.bump)]
+    pub signing_log: Option<Account<'info, SigningLog>>,
+    /// Same as `SignTransaction::signature_record` — see its doc comment.
+    #[account(
+        init,
+        payer = rent_payer,
+        space = 8 + 8 + 32 + 64 + 8 + 1,
+        seeds = [SIGNATURE_RECORD_SEED, &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub signature_record: Account<'info, SignatureRecord>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: mempool_account
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: executing_pool
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct ClaimComputationRefund<'info> {
+    #[account(
+        mut,
+        close = payer,
+        seeds = [ABORT_MODE_CONFIG_SEED, &computation_offset.to_le_bytes()],
+        bump = abort_mode_config.bump,
+    )]
+    pub abort_mode_config: Account<'info, AbortModeConfig>,
+    #[account(mut, address = abort_mode_config.payer)]
+    /// CHECK: lamports-only recipient, checked against the payer recorded
+    /// on `abort_mode_config` at queue time. Not required to sign —
+    /// closing this PDA only ever returns its rent to the address already
+    /// entitled to it, so anyone can trigger the claim on that address's
+    /// behalf.
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[callback_accounts("sign_transaction")]
+#[derive(Accounts)]
+pub struct SignTransactionCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub signing_stats: Account<'info, SigningStats>,
+    #[account(mut)]
+    pub abort_mode_config: Account<'info, AbortModeConfig>,
+    /// Present only when the caller opted into buffering this signature into
+    /// `SigningLog` instead of emitting its own `TransactionSignedEvent` —
+    /// see `SigningLog`'s doc comment.
+    #[account(mut, seeds = [SIGNING_LOG_SEED], bump = signing_log.bump)]
+    pub signing_log: Option<Account<'info, SigningLog>>,
+    /// Durable copy of this computation's signature, written here in
+    /// addition to `TransactionSignedEvent`/`SigningLog` for backward
+    /// compatibility — see [`SignatureRecord`]'s doc comment.
+    #[account(mut)]
+    pub signature_record: Account<'info, SignatureRecord>,
+}
+
+#[queue_computation_accounts("sign_transactions_batch", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, agent: Pubkey)]
+pub struct SignTransactionsBatch<'info> {
+    /// Same `payer`/`rent_payer` split as `SignTransaction` — see that
+    /// struct's doc comment.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = rent_payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    /// Checked once per message in `sign_transactions_batch`, same policy
+    /// `sign_transaction` enforces for its one message.
+    #[account(seeds = [SIGNING_POLICY_SEED, agent.as_ref()], bump)]
+    pub signing_policy: Option<Account<'info, SigningPolicy>>,
+    #[account(seeds = [SIGNING_STATS_SEED], bump = signing_stats.bump)]
+    pub signing_stats: Account<'info, SigningStats>,
+    #[account(
+        init,
+        payer = rent_payer,
+        space = 8 + 1 + 1 + 1 + 1 + 32,
+        seeds = [ABORT_MODE_CONFIG_SEED, &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub abort_mode_config: Account<'info, AbortModeConfig>,
+    /// Optional, same as `SignTransaction::signing_log` — when present,
+    /// every signature in the batch (including zero-padded slots past the
+    /// caller's own message count, per this instruction's doc comment) is
+    /// buffered as its own `SigningLog` entry instead of one combined
+    /// `TransactionsBatchSignedEvent`.
+    #[account(mut, seeds = [SIGNING_LOG_SEED], bump = signing_log.bump)]
+    pub signing_log: Option<Account<'info, SigningLog>>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: mempool_account
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: executing_pool
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTIONS_BATCH))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("sign_transactions_batch")]
+#[derive(Accounts)]
+pub struct SignTransactionsBatchCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTIONS_BATCH))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub signing_stats: Account<'info, SigningStats>,
+    #[account(mut)]
+    pub abort_mode_config: Account<'info, AbortModeConfig>,
+    /// Present only when the caller opted into buffering this batch's
+    /// signatures into `SigningLog` instead of emitting
+    /// `TransactionsBatchSignedEvent`.
+    #[account(mut, seeds = [SIGNING_LOG_SEED], bump = signing_log.bump)]
+    pub signing_log: Option<Account<'info, SigningLog>>,
+}
+
+/// Simplified relative to `SignTransaction` — no `message`/`aad`/
+/// `signing_policy`, since `rotate_signing_key`'s circuit takes no inputs —
+/// and admin-gated, since rotating the MXE's signing key affects every
+/// agent this deployment serves.
+#[queue_computation_accounts("rotate_signing_key", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct RotateSigningKey<'info> {
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(seeds = [SIGNING_KEY_HISTORY_SEED], bump = signing_key_history.bump)]
+    pub signing_key_history: Account<'info, SigningKeyHistory>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: mempool_account
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: executing_pool
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_ROTATE_SIGNING_KEY))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("rotate_signing_key")]
+#[derive(Accounts)]
+pub struct RotateSigningKeyCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_ROTATE_SIGNING_KEY))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut, seeds = [SIGNING_KEY_HISTORY_SEED], bump = signing_key_history.bump)]
+    pub signing_key_history: Account<'info, SigningKeyHistory>,
+}
+
+#[queue_computation_accounts("sign_transaction_secp256k1", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, agent: Pubkey)]
+pub struct SignTransactionSecp256k1<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(seeds = [SIGNING_POLICY_SEED, agent.as_ref()], bump)]
+    pub signing_policy: Option<Account<'info, SigningPolicy>>,
+    #[account(seeds = [SIGNING_STATS_SEED], bump = signing_stats.bump)]
+    pub signing_stats: Account<'info, SigningStats>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: mempool_account
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: executing_pool
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION_SECP256K1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("sign_transaction_secp256k1")]
+#[derive(Accounts)]
+pub struct SignTransactionSecp256k1Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION_SECP256K1))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub signing_stats: Account<'info, SigningStats>,
+}
+
+#[queue_computation_accounts("sign_transaction_confidential", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, agent: Pubkey)]
+pub struct SignTransactionConfidential<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(seeds = [SIGNING_POLICY_SEED, agent.as_ref()], bump)]
+    pub signing_policy: Option<Account<'info, SigningPolicy>>,
+    #[account(seeds = [SIGNING_STATS_SEED], bump = signing_stats.bump)]
+    pub signing_stats: Account<'info, SigningStats>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: mempool_account
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: executing_pool
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION_CONFIDENTIAL))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("sign_transaction_confidential")]
+#[derive(Accounts)]
+pub struct SignTransactionConfidentialCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION_CONFIDENTIAL))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub signing_stats: Account<'info, SigningStats>,
+}
+
+#[queue_computation_accounts("sign_transaction_ed25519ctx", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, agent: Pubkey)]
+pub struct SignTransactionEd25519ctx<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(seeds = [SIGNING_POLICY_SEED, agent.as_ref()], bump)]
+    pub signing_policy: Option<Account<'info, SigningPolicy>>,
+    #[account(seeds = [SIGNING_STATS_SEED], bump = signing_stats.bump)]
+    pub signing_stats: Account<'info, SigningStats>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: mempool_account
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: executing_pool
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION_ED25519CTX))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("sign_transaction_ed25519ctx")]
+#[derive(Accounts)]
+pub struct SignTransactionEd25519ctxCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION_ED25519CTX))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub signing_stats: Account<'info, SigningStats>,
+}
+
+/// Deployment-wide configuration for this MXE program, controlled by
+/// `admin`. `signing_domain` is prepended to every `sign_transaction`
+/// preimage (see that instruction's doc comment for the exact layout), so
+/// every signature produced by this deployment is scoped to it without
+/// relying on callers to pass a domain themselves.
+#[account]
+pub struct MXEConfig {
+    pub admin: Pubkey,
+    pub signing_domain: [u8; 16],
+    /// Deployment-wide floor on active cluster node count, passed to
+    /// `check_min_nodes` by every `sign_transaction` and
+    /// `verify_agent_signature` call; that function does not compare it
+    /// against the cluster, so no call is rejected on its account. Zero
+    /// means no floor. Admin-controlled via `propose_min_nodes_change`
+    /// (immediate when raising, timelocked via `apply_min_nodes_change`
+    /// when lowering) and cancelable via `cancel_min_nodes_change`.
+    pub min_nodes: u8,
+    /// Programs `verify_agent_signature`'s caller may ask
+    /// `verify_agent_signature_callback` to CPI verification results into.
+    /// Admin-controlled via `set_result_program_allowlist` so an arbitrary
+    /// caller can't point the callback at a program it doesn't control.
+    pub result_program_allowlist: [Pubkey; MAX_RESULT_PROGRAM_ALLOWLIST],
+    pub result_program_allowlist_count: u8,
+    /// Deployment-tuned ceiling on `verify_agent_signatures_one_key`'s batch
+    /// size, at or below the circuit's hard `MAX_SIGNATURE_BATCH`. Lets an
+    /// operator dial batches down to whatever actually fits this cluster's
+    /// compute budget without redeploying the circuit. Admin-controlled via
+    /// `set_max_batch_size`.
+    pub max_batch_size: u8,
+    /// Daily UTC window, in seconds-since-midnight (`0..=86_400`), during
+    /// which `sign_transaction` will run. `signing_window_enabled == false`
+    /// means always-open (the default), regardless of what `start`/`end`
+    /// hold. When enabled and `start <= end`, the window is the closed
+    /// range `[start, end]`; when `start > end` it wraps past midnight,
+    /// i.e. allowed whenever `now >= start || now <= end`. Admin-controlled
+    /// via `set_signing_window`.
+    pub signing_window_enabled: bool,
+    pub signing_window_start: u32,
+    pub signing_window_end: u32,
+    /// Nonzero while a lowering of `min_nodes` is proposed but not yet
+    /// applied — the unix timestamp `apply_min_nodes_change` requires
+    /// `Clock::get()?.unix_timestamp` to reach. `0` means no change is
+    /// pending. See `propose_min_nodes_change`.
+    pub pending_min_nodes_effective_at: i64,
+    /// The `min_nodes` value a pending proposal will apply once
+    /// `pending_min_nodes_effective_at` elapses. Meaningless while that
+    /// field is `0`.
+    pub pending_min_nodes: u8,
+    pub bump: u8,
+}
+
+#[derive(Accounts)]
+pub struct InitMxeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + 32 + 16 + 1 + 32 * MAX_RESULT_PROGRAM_ALLOWLIST + 1 + 1 + 1 + 4 + 4 + 8 + 1 + 1,
+        seeds = [MXE_CONFIG_SEED],
+        bump,
+    )]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSigningDomain<'info> {
+    #[account(mut, seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetMaxBatchSize<'info> {
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+}
+
+/// Cumulative signing throughput counters for this deployment, surviving
+/// log pruning (unlike `TransactionSignedEvent`/`SigningNonceRevealedEvent`).
+/// Incremented from the `sign_transaction` callbacks; reset via
+/// `reset_signing_stats`.
+#[account]
+pub struct SigningStats {
+    /// Number of full `(R, S)` signatures ever revealed, across both the
+    /// single-shot and two-phase signing flows.
+    pub total_signatures: u64,
+    /// Number of `sign_transaction` computations whose callback observed an
+    /// aborted computation, across every signing callback.
+    pub total_aborts: u64,
+    /// Unix timestamp of the last `init_signing_stats`/`reset_signing_stats`.
+    pub last_reset: i64,
+    pub bump: u8,
+}
+
+#[derive(Accounts)]
+pub struct InitSigningStats<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 8 + 8 + 8 + 1,
+        seeds = [SIGNING_STATS_SEED],
+        bump,
+    )]
+    pub signing_stats: Account<'info, SigningStats>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResetSigningStats<'info> {
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(mut, seeds = [SIGNING_STATS_SEED], bump = signing_stats.bump)]
+    pub signing_stats: Account<'info, SigningStats>,
+    pub admin: Signer<'info>,
+}
+
+/// Append-only, fixed-capacity record of every public key this MXE's
+/// distributed signing key has ever rotated to, oldest-first within the
+/// live window. Unlike [`SigningLog`]'s overwrite-on-wrap ring buffer,
+/// entries here are never overwritten once written — once `count` reaches
+/// `MAX_KEY_HISTORY` no further rotations can be recorded, since dropping
+/// an old key here would mean losing the ability to identify which key a
+/// past signature verifies against, which is the entire point of
+/// versioning rotations in the first place.
+///
+/// `public_keys[i]` was current during version `versions[i]`;
+/// `current_version`/`public_keys[count - 1]` is the key
+/// `rotate_signing_key`'s callback most recently wrote.
+#[account]
+pub struct SigningKeyHistory {
+    pub versions: [u32; MAX_KEY_HISTORY],
+    pub public_keys: [[u8; 32]; MAX_KEY_HISTORY],
+    pub count: u8,
+    pub current_version: u32,
+    pub bump: u8,
+}
+
+#[derive(Accounts)]
+pub struct InitSigningKeyHistory<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 4 * MAX_KEY_HISTORY + 32 * MAX_KEY_HISTORY + 1 + 4 + 1,
+        seeds = [SIGNING_KEY_HISTORY_SEED],
+        bump,
+    )]
+    pub signing_key_history: Account<'info, SigningKeyHistory>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Bounded ring buffer of `(message, signature)` digests `verify_agent_signature_plaintext`
+/// has already processed to a valid result, so the same verification can't
+/// authorize whatever a downstream program gates on it a second time — see
+/// `nonce_digest`'s doc comment for how a digest is computed and
+/// `NONCE_REGISTRY_CAPACITY`'s for how the ring wraps. Unlike
+/// `SigningKeyHistory`, entries here are expected to wrap and overwrite;
+/// unlike `SigningLog`, what's stored is itself already the replay key, not
+/// an audit trail of something else.
+///
+/// Singleton across the whole MXE deployment rather than per-agent, the
+/// same way `SigningStats` is — `verify_agent_signature_plaintext` takes an
+/// arbitrary encrypted verifying key with no `AgentState`-style account to
+/// scope a registry to.
+#[account]
+pub struct NonceRegistry {
+    pub digests: [[u8; 32]; NONCE_REGISTRY_CAPACITY],
+    pub len: u16,
+    pub next_index: u16,
+    pub bump: u8,
+}
+
+impl NonceRegistry {
+    fn contains(&self, digest: &[u8; 32]) -> bool {
+        self.digests.iter().take(self.len as usize).any(|d| d == digest)
+    }
+
+    fn record(&mut self, digest: [u8; 32]) {
+        let index = self.next_index as usize;
+        self.digests[index] = digest;
+        self.next_index = (self.next_index + 1) % NONCE_REGISTRY_CAPACITY as u16;
+        if (self.len as usize) < NONCE_REGISTRY_CAPACITY {
+            self.len += 1;
+        }
+    }
+}
+
+/// Durable home for one `verify_agent_signature_plaintext` call's replay
+/// digest, created at queue time (same reasoning as `SignatureRecord`'s
+/// doc comment — a callback accounts struct has no signer to pay `init`
+/// rent) so `verify_agent_signature_plaintext_callback` has somewhere to
+/// read `nonce_digest`'s output back from. See `VERIFICATION_NONCE_SEED`'s
+/// doc comment for why the callback needs this at all.
+#[account]
+pub struct VerificationNonceRecord {
+    pub digest: [u8; 32],
+    pub bump: u8,
+}
+
+#[derive(Accounts)]
+pub struct InitNonceRegistry<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 * NONCE_REGISTRY_CAPACITY + 2 + 2 + 1,
+        seeds = [NONCE_REGISTRY_SEED],
+        bump,
+    )]
+    pub nonce_registry: Account<'info, NonceRegistry>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Fixed-capacity ring buffer of recently-produced signature hashes, for
+/// operators who want an on-chain audit trail — or, at high signing volume,
+/// a single batched event instead of one `TransactionSignedEvent` per
+/// signature — without indexing every event individually.
+///
+/// Buffer format: `entries[head]` is the next slot to be overwritten;
+/// `entries.len()` always equals `capacity`. Each entry is
+/// `hash(signature)` (`solana_program::hash::hash` over the raw 64-byte
+/// `(R, S)` signature; see `signing_log_digest`) — never the signature
+/// itself. `sign_transaction`'s callback (shared by
+/// `sign_transaction_full_message`) and `sign_transactions_batch`'s
+/// callback append to this buffer, instead of emitting their own
+/// `TransactionSignedEvent`/`TransactionsBatchSignedEvent`, whenever a
+/// `signing_log` account is supplied; every other signing callback always
+/// emits its own per-signature event regardless. `flush_signing_events` then folds everything written since
+/// `last_flushed_head` into one binary Merkle root and emits a single
+/// `SigningEventsFlushedEvent` for the whole batch. Capacity only ever
+/// grows, via `resize_signing_log`.
+///
+/// Reconstructing a signature from a flushed batch: you can't, from the
+/// batch alone — `entries` and the Merkle root it feeds are hashes, not
+/// the signatures themselves. What a consumer who already has a candidate
+/// signature (e.g. the one returned to the original `sign_transaction`
+/// caller, or logged off-chain by that caller) can do is hash it the same
+/// way and either look it up directly in `entries` by reading this
+/// account, or check it against a `merkle_root` published in a
+/// `SigningEventsFlushedEvent` without needing the buffer at all — a
+/// standard Merkle inclusion proof over that event's `start_head..end_head`
+/// leaves. This account is an integrity check against signatures obtained
+/// elsewhere, not a way to recover one from nothing.
+#[account]
+pub struct SigningLog {
+    pub capacity: u32,
+    pub head: u32,
+    pub entries: Vec<[u8; 32]>,
+    /// Ring position as of the last `flush_signing_events` call, or `0`
+    /// from `init_signing_log` if none has happened yet.
+    pub last_flushed_head: u32,
+    pub bump: u8,
+}
+
+#[derive(Accounts)]
+#[instruction(capacity: u32)]
+pub struct InitSigningLog<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 4 + 4 + 4 + (capacity as usize) * 32 + 4 + 1,
+        seeds = [SIGNING_LOG_SEED],
+        bump,
+    )]
+    pub signing_log: Account<'info, SigningLog>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResizeSigningLog<'info> {
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [SIGNING_LOG_SEED], bump = signing_log.bump)]
+    pub signing_log: Account<'info, SigningLog>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FlushSigningEvents<'info> {
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    pub admin: Signer<'info>,
+    #[account(mut, seeds = [SIGNING_LOG_SEED], bump = signing_log.bump)]
+    pub signing_log: Account<'info, SigningLog>,
+}
+
+#[derive(Accounts)]
+pub struct GetSigningStats<'info> {
+    #[account(seeds = [SIGNING_STATS_SEED], bump = signing_stats.bump)]
+    pub signing_stats: Account<'info, SigningStats>,
+}
+
+/// Admin-configured allowlist of message prefixes a single agent's key is
+/// scoped to sign, so an abused signing endpoint can't be used to authorize
+/// arbitrary data under that agent's identity. An agent with no
+/// `SigningPolicy` PDA is unrestricted; once created, `count == 0` denies
+/// every message until the admin adds at least one prefix.
+#[account]
+pub struct SigningPolicy {
+    pub agent: Pubkey,
+    pub prefixes: [[u8; POLICY_PREFIX_LEN]; MAX_POLICY_PREFIXES],
+    pub prefix_lens: [u8; MAX_POLICY_PREFIXES],
+    pub count: u8,
+    pub bump: u8,
+}
+
+#[derive(Accounts)]
+#[instruction(agent: Pubkey)]
+pub struct InitSigningPolicy<'info> {
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + 32 + POLICY_PREFIX_LEN * MAX_POLICY_PREFIXES + MAX_POLICY_PREFIXES + 1 + 1,
+        seeds = [SIGNING_POLICY_SEED, agent.as_ref()],
+        bump,
+    )]
+    pub signing_policy: Account<'info, SigningPolicy>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSigningPolicyPrefixes<'info> {
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(mut)]
+    pub signing_policy: Account<'info, SigningPolicy>,
+    pub admin: Signer<'info>,
+}
+
+/// Carries one caller's `abort_mode` choice from `sign_transaction` or
+/// `verify_agent_signature` to its callback. Seeded from `computation_offset`
+/// alone, so it's created fresh per call and never reused across
+/// computations.
+///
+/// `terminated`/`refunded`/`payer` exist for [`claim_computation_refund`]:
+/// the callback sets `terminated = true` on the `ABORT_MODE_SOFT_FAIL` path
+/// (the only outcome this program ever observes happen and keeps running
+/// after — `ABORT_MODE_HARD_ERROR` rolls the whole transaction back, so
+/// there is no state left to mark), and `claim_computation_refund` later
+/// closes this account back to `payer`, flipping `refunded` first so the
+/// same termination can't be claimed twice. Only `sign_transaction` (and
+/// `sign_transaction_full_message`, which shares its callback),
+/// `sign_transactions_batch`, `verify_agent_signature`, and
+/// `verify_agent_signature_plaintext` set `terminated` today — see
+/// `claim_computation_refund`'s doc comment for why the other
+/// signing/verification instruction families aren't wired up yet.
+#[account]
+pub struct AbortModeConfig {
+    pub abort_mode: u8,
+    pub bump: u8,
+    pub terminated: bool,
+    pub refunded: bool,
+    pub payer: Pubkey,
+}
+
+/// Durable, deterministic home for a `sign_transaction`/
+/// `sign_transaction_full_message` computation's result, created fresh per
+/// call (seeded from `computation_offset` alone, same as
+/// `AbortModeConfig`) so a client that missed `TransactionSignedEvent` —
+/// the log pruned, the websocket dropped — can still poll this PDA for the
+/// signature instead of having no way to retrieve it. `signature` and
+/// `slot` are both zero until the callback actually lands; `slot` is the
+/// slot the callback ran in, not when the computation was queued.
+///
+/// `requester` is the `payer` who queued this computation, recorded so a
+/// listener running many concurrent requests can tell which of its own
+/// calls this record (or `SignatureRecordedEvent`) belongs to without
+/// having to already know `computation_offset` in advance — e.g. a relay
+/// submitting on behalf of several distinct users.
+#[account]
+pub struct SignatureRecord {
+    pub computation_offset: u64,
+    pub requester: Pubkey,
+    pub signature: [u8; 64],
+    pub slot: u64,
+    pub bump: u8,
+}
+
+/// Tracks a two-phase signing flow: the `R` commitment from phase one, and
+/// whether phase two has revealed the full signature yet.
+#[account]
+pub struct SigningSession {
+    pub message: [u8; 32],
+    /// Associated data bound into the preimage alongside `message`; see
+    /// `sign_transaction`'s doc comment in `encrypted-ixs` for the exact
+    /// preimage layout. All-zero when the caller didn't supply one.
+    pub aad: [u8; 32],
+    pub r: [u8; 32],
+    pub completed: bool,
+    pub bump: u8,
+}
+
+#[queue_computation_accounts("sign_transaction", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, message: [u8; 32])]
+pub struct SignTransactionTwoPhaseCommit<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 8 + 32 + 32 + 32 + 1 + 1,
+        payer = payer,
+        seeds = [b"signing_session", message.as_ref()],
+        bump,
+    )]
+    pub signing_session: Account<'info, SigningSession>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(seeds = [SIGNING_STATS_SEED], bump = signing_stats.bump)]
+    pub signing_stats: Account<'info, SigningStats>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: mempool_account
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: executing_pool
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("sign_transaction")]
+#[derive(Accounts)]
+pub struct SignTransactionPhase1Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub signing_session: Account<'info, SigningSession>,
+    #[account(mut)]
+    pub signing_stats: Account<'info, SigningStats>,
+}
+
+#[queue_computation_accounts("sign_transaction", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64, message: [u8; 32])]
+pub struct SignTransactionTwoPhaseComplete<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"signing_session", message.as_ref()],
+        bump = signing_session.bump,
+    )]
+    pub signing_session: Account<'info, SigningSession>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(seeds = [SIGNING_STATS_SEED], bump = signing_stats.bump)]
+    pub signing_stats: Account<'info, SigningStats>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: mempool_account
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: executing_pool
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("sign_transaction")]
+#[derive(Accounts)]
+pub struct SignTransactionPhase2Callback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub signing_session: Account<'info, SigningSession>,
+    #[account(mut)]
+    pub signing_stats: Account<'info, SigningStats>,
+}
+
+#[init_computation_definition_accounts("sign_transaction", payer)]
+#[derive(Accounts)]
+pub struct InitSignTransactionCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account
+    pub comp_def_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_mxe_lut_pda!(mxe_account.lut_offset_slot))]
+    /// CHECK: address_lookup_table
+    pub address_lookup_table: UncheckedAccount<'info>,
+    #[account(address = LUT_PROGRAM_ID)]
+    /// CHECK: lut_program
+    pub lut_program: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("sign_transaction_confidential", payer)]
+#[derive(Accounts)]
+pub struct InitSignTransactionConfidentialCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account
+    pub comp_def_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_mxe_lut_pda!(mxe_account.lut_offset_slot))]
+    /// CHECK: address_lookup_table
+    pub address_lookup_table: UncheckedAccount<'info>,
+    #[account(address = LUT_PROGRAM_ID)]
+    /// CHECK: lut_program
+    pub lut_program: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("sign_transaction_ed25519ctx", payer)]
+#[derive(Accounts)]
+pub struct InitSignTransactionEd25519ctxCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account
+    pub comp_def_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_mxe_lut_pda!(mxe_account.lut_offset_slot))]
+    /// CHECK: address_lookup_table
+    pub address_lookup_table: UncheckedAccount<'info>,
+    #[account(address = LUT_PROGRAM_ID)]
+    /// CHECK: lut_program
+    pub lut_program: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("sign_transactions_batch", payer)]
+#[derive(Accounts)]
+pub struct InitSignTransactionsBatchCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account
+    pub comp_def_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_mxe_lut_pda!(mxe_account.lut_offset_slot))]
+    /// CHECK: address_lookup_table
+    pub address_lookup_table: UncheckedAccount<'info>,
+    #[account(address = LUT_PROGRAM_ID)]
+    /// CHECK: lut_program
+    pub lut_program: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("rotate_signing_key", payer)]
+#[derive(Accounts)]
+pub struct InitRotateSigningKeyCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account
+    pub comp_def_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_mxe_lut_pda!(mxe_account.lut_offset_slot))]
+    /// CHECK: address_lookup_table
+    pub address_lookup_table: UncheckedAccount<'info>,
+    #[account(address = LUT_PROGRAM_ID)]
+    /// CHECK: lut_program
+    pub lut_program: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+#[init_computation_definition_accounts("sign_transaction_secp256k1", payer)]
+#[derive(Accounts)]
+pub struct InitSignTransactionSecp256k1CompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account
+    pub comp_def_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_mxe_lut_pda!(mxe_account.lut_offset_slot))]
+    /// CHECK: address_lookup_table
+    pub address_lookup_table: UncheckedAccount<'info>,
+    #[account(address = LUT_PROGRAM_ID)]
+    /// CHECK: lut_program
+    pub lut_program: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
+
+/// A long-lived observer identity, keyed by a stable `observer_id` rather
+/// than a specific x25519 key — see `register_observer` and
+/// `rotate_observer_key`. `authority` is the only signer allowed to rotate
+/// `pubkey`; it's set once at registration and never changes.
+#[account]
+pub struct ObserverRegistry {
+    pub observer_id: u32,
+    pub pubkey: [u8; 32],
+    pub authority: Pubkey,
+    pub bump: u8,
+}
+
+#[derive(Accounts)]
+#[instruction(observer_id: u32)]
+pub struct RegisterObserver<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 4 + 32 + 32 + 1,
+        seeds = [OBSERVER_REGISTRY_SEED, &observer_id.to_le_bytes()],
+        bump,
+    )]
+    pub observer_registry: Account<'info, ObserverRegistry>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RotateObserverKey<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub observer_registry: Account<'info, ObserverRegistry>,
+}
+
+#[queue_computation_accounts("verify_agent_signature", payer)]
+#[derive(Accounts)]
+#[instruction(
+    computation_offset: u64,
+    one_time_pub_key: [u8; 32],
+    one_time_nonce: u128,
+    verifying_key_enc_lo: [u8; 32],
+    verifying_key_enc_hi: [u8; 32],
+    message: [u8; 32],
+    signature: [u8; 64],
+    observer_id: u32
+)]
+pub struct VerifyAgentSignature<'info> {
+    /// See [`SignTransaction::payer`]'s doc comment — same constraint:
+    /// `#[queue_computation_accounts("verify_agent_signature", payer)]`
+    /// above requires this exact field name, and it's the account
+    /// `queue_computation` debits the Arcium computation fee from.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// See [`SignTransaction::rent_payer`]'s doc comment — pays the rent
+    /// for `sign_pda_account`/`abort_mode_config` below instead of `payer`.
+    #[account(mut)]
+    pub rent_payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = rent_payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(
+        seeds = [OBSERVER_REGISTRY_SEED, &observer_id.to_le_bytes()],
+        bump = observer_registry.bump,
+    )]
+    pub observer_registry: Account<'info, ObserverRegistry>,
+    #[account(
+        init,
+        payer = rent_payer,
+        space = 8 + 1 + 1 + 1 + 1 + 32,
+        seeds = [ABORT_MODE_CONFIG_SEED, &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub abort_mode_config: Account<'info, AbortModeConfig>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: mempool_account
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: executing_pool
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURE))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+/// Same shape as [`VerifyAgentSignature`], minus the `ObserverRegistry`
+/// lookup — `verify_agent_signature_full_message` still takes a raw
+/// observer x25519 key rather than an `observer_id` — and minus the
+/// separate `rent_payer` and the `abort_mode_config` PDA: `payer` covers
+/// the rent here, and no abort mode is recorded for this instruction. Split into
+/// its own struct rather than sharing `VerifyAgentSignature` because the
+/// two instructions' `#[instruction(...)]` parameter lists diverge now that
+/// one of them derives a seed from `observer_id`.
+#[queue_computation_accounts("verify_agent_signature", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct VerifyAgentSignatureFullMessage<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: mempool_account
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: executing_pool
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURE))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("verify_agent_signature")]
+#[derive(Accounts)]
+pub struct VerifyAgentSignatureCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURE))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub abort_mode_config: Account<'info, AbortModeConfig>,
+}
+
+/// Identical in shape to [`VerifyAgentSignature`], minus the
+/// `ObserverRegistry` lookup — there's no observer to encrypt the result
+/// to — plus `nonce_registry`, which only this plaintext variant needs: see
+/// [`verify_agent_signature_plaintext`]'s doc comment.
+#[queue_computation_accounts("verify_agent_signature_plaintext", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct VerifyAgentSignaturePlaintext<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 1 + 1 + 1 + 1 + 32,
+        seeds = [ABORT_MODE_CONFIG_SEED, &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub abort_mode_config: Account<'info, AbortModeConfig>,
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32 + 1,
+        seeds = [VERIFICATION_NONCE_SEED, &computation_offset.to_le_bytes()],
+        bump,
+    )]
+    pub verification_nonce_record: Account<'info, VerificationNonceRecord>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: mempool_account
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: executing_pool
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURE_PLAINTEXT))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    #[account(mut, seeds = [NONCE_REGISTRY_SEED], bump = nonce_registry.bump)]
+    pub nonce_registry: Account<'info, NonceRegistry>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
+
+#[callback_accounts("verify_agent_signature_plaintext")]
+#[derive(Accounts)]
+pub struct VerifyAgentSignaturePlaintextCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURE_PLAINTEXT))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(mut)]
+    pub abort_mode_config: Account<'info, AbortModeConfig>,
+    pub verification_nonce_record: Account<'info, VerificationNonceRecord>,
+    #[account(mut)]
+    pub nonce_registry: Account<'info, NonceRegistry>,
+}
 
-        emit!(TransactionSignedEvent { signature });
-        Ok(())
-    }
+#[init_computation_definition_accounts("verify_agent_signature_plaintext", payer)]
+#[derive(Accounts)]
+pub struct InitVerifyAgentSignaturePlaintextCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account
+    pub comp_def_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_mxe_lut_pda!(mxe_account.lut_offset_slot))]
+    /// CHECK: address_lookup_table
+    pub address_lookup_table: UncheckedAccount<'info>,
+    #[account(address = LUT_PROGRAM_ID)]
+    /// CHECK: lut_program
+    pub lut_program: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
 
-    pub fn init_verify_agent_signature_comp_def(
-        ctx: Context<InitVerifyAgentSignatureCompDef>,
-    ) -> Result<()> {
-        init_comp_def(ctx.accounts, None, None)?;
-        Ok(())
-    }
+#[queue_computation_accounts("verify_agent_signature_multi_observer", payer)]
+#[derive(Accounts)]
+#[instruction(computation_offset: u64)]
+pub struct VerifyAgentSignatureMultiObserver<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        space = 9,
+        payer = payer,
+        seeds = [&SIGN_PDA_SEED],
+        bump,
+        address = derive_sign_pda!(),
+    )]
+    pub sign_pda_account: Account<'info, ArciumSignerAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: mempool_account
+    pub mempool_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_execpool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: executing_pool
+    pub executing_pool: UncheckedAccount<'info>,
+    #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURE_MULTI_OBSERVER))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(mut, address = ARCIUM_FEE_POOL_ACCOUNT_ADDRESS)]
+    pub pool_account: Account<'info, FeePool>,
+    #[account(mut, address = ARCIUM_CLOCK_ACCOUNT_ADDRESS)]
+    pub clock_account: Account<'info, ClockAccount>,
+    pub system_program: Program<'info, System>,
+    pub arcium_program: Program<'info, Arcium>,
+}
 
-    pub fn verify_agent_signature(
-        ctx: Context<VerifyAgentSignature>,
-        computation_offset: u64,
-        one_time_pub_key: [u8; 32],
-        one_time_nonce: u128,
-        verifying_key_enc_lo: [u8; 32],
-        verifying_key_enc_hi: [u8; 32],
-        message: [u8; 32],
-        signature: [u8; 64],
-        observer_pub_key: [u8; 32],
-        observer_nonce: u128,
-    ) -> Result<()> {
-        ctx.accounts.sign_pda_account.bump = ctx.bumps.sign_pda_account;
-        let mut builder = ArgBuilder::new()
-            .x25519_pubkey(one_time_pub_key)
-            .plaintext_u128(one_time_nonce)
-            .encrypted_u128(verifying_key_enc_lo)
-            .encrypted_u128(verifying_key_enc_hi);
-        for byte in message {
-            builder = builder.plaintext_u8(byte);
-        }
-        let args = builder
-            .arcis_ed25519_signature(signature)
-            .x25519_pubkey(observer_pub_key)
-            .plaintext_u128(observer_nonce)
-            .build();
-        queue_computation(
-            ctx.accounts,
-            computation_offset,
-            args,
-            vec![VerifyAgentSignatureCallback::callback_ix(
-                computation_offset,
-                &ctx.accounts.mxe_account,
-                &[],
-            )?],
-            1,
-            0,
-        )?;
-        Ok(())
-    }
+#[callback_accounts("verify_agent_signature_multi_observer")]
+#[derive(Accounts)]
+pub struct VerifyAgentSignatureMultiObserverCallback<'info> {
+    pub arcium_program: Program<'info, Arcium>,
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURE_MULTI_OBSERVER))]
+    pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
+    #[account(address = derive_mxe_pda!())]
+    pub mxe_account: Account<'info, MXEAccount>,
+    /// CHECK: computation_account
+    pub computation_account: UncheckedAccount<'info>,
+    #[account(address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
+    pub cluster_account: Account<'info, Cluster>,
+    #[account(address = ::anchor_lang::solana_program::sysvar::instructions::ID)]
+    /// CHECK: instructions_sysvar
+    pub instructions_sysvar: AccountInfo<'info>,
+}
 
-    #[arcium_callback(encrypted_ix = "verify_agent_signature")]
-    pub fn verify_agent_signature_callback(
-        ctx: Context<VerifyAgentSignatureCallback>,
-        output: SignedComputationOutputs<VerifyAgentSignatureOutput>,
-    ) -> Result<()> {
-        let o = match output.verify_output(
-            &ctx.accounts.cluster_account,
-            &ctx.accounts.computation_account,
-        ) {
-            Ok(VerifyAgentSignatureOutput { field_0 }) => field_0,
-            Err(_) => return Err(ErrorCode::AbortedComputation.into()),
-        };
+#[init_computation_definition_accounts("verify_agent_signature_multi_observer", payer)]
+#[derive(Accounts)]
+pub struct InitVerifyAgentSignatureMultiObserverCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account
+    pub comp_def_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_mxe_lut_pda!(mxe_account.lut_offset_slot))]
+    /// CHECK: address_lookup_table
+    pub address_lookup_table: UncheckedAccount<'info>,
+    #[account(address = LUT_PROGRAM_ID)]
+    /// CHECK: lut_program
+    pub lut_program: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
+}
 
-        emit!(SignatureVerifiedEvent {
-            is_valid: o.ciphertexts[0],
-            nonce: o.nonce.to_le_bytes(),
-        });
-        Ok(())
-    }
+#[init_computation_definition_accounts("verify_agent_signature", payer)]
+#[derive(Accounts)]
+pub struct InitVerifyAgentSignatureCompDef<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(mut, address = derive_mxe_pda!())]
+    pub mxe_account: Box<Account<'info, MXEAccount>>,
+    #[account(mut)]
+    /// CHECK: comp_def_account
+    pub comp_def_account: UncheckedAccount<'info>,
+    #[account(mut, address = derive_mxe_lut_pda!(mxe_account.lut_offset_slot))]
+    /// CHECK: address_lookup_table
+    pub address_lookup_table: UncheckedAccount<'info>,
+    #[account(address = LUT_PROGRAM_ID)]
+    /// CHECK: lut_program
+    pub lut_program: UncheckedAccount<'info>,
+    pub arcium_program: Program<'info, Arcium>,
+    pub system_program: Program<'info, System>,
 }
 
-#[queue_computation_accounts("sign_transaction", payer)]
+#[queue_computation_accounts("verify_agent_signatures_one_key", payer)]
 #[derive(Accounts)]
 #[instruction(computation_offset: u64)]
-pub struct SignTransaction<'info> {
+pub struct VerifyAgentSignaturesOneKey<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(
@@ -156,6 +4136,8 @@ pub struct SignTransaction<'info> {
     pub sign_pda_account: Account<'info, ArciumSignerAccount>,
     #[account(address = derive_mxe_pda!())]
     pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
     #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
     /// CHECK: mempool_account
     pub mempool_account: UncheckedAccount<'info>,
@@ -165,7 +4147,7 @@ pub struct SignTransaction<'info> {
     #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
     /// CHECK: computation_account
     pub computation_account: UncheckedAccount<'info>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURES_ONE_KEY))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
     pub cluster_account: Account<'info, Cluster>,
@@ -177,11 +4159,11 @@ pub struct SignTransaction<'info> {
     pub arcium_program: Program<'info, Arcium>,
 }
 
-#[callback_accounts("sign_transaction")]
+#[callback_accounts("verify_agent_signatures_one_key")]
 #[derive(Accounts)]
-pub struct SignTransactionCallback<'info> {
+pub struct VerifyAgentSignaturesOneKeyCallback<'info> {
     pub arcium_program: Program<'info, Arcium>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_SIGN_TRANSACTION))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURES_ONE_KEY))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(address = derive_mxe_pda!())]
     pub mxe_account: Account<'info, MXEAccount>,
@@ -194,9 +4176,9 @@ pub struct SignTransactionCallback<'info> {
     pub instructions_sysvar: AccountInfo<'info>,
 }
 
-#[init_computation_definition_accounts("sign_transaction", payer)]
+#[init_computation_definition_accounts("verify_agent_signatures_one_key", payer)]
 #[derive(Accounts)]
-pub struct InitSignTransactionCompDef<'info> {
+pub struct InitVerifyAgentSignaturesOneKeyCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(mut, address = derive_mxe_pda!())]
@@ -214,10 +4196,10 @@ pub struct InitSignTransactionCompDef<'info> {
     pub system_program: Program<'info, System>,
 }
 
-#[queue_computation_accounts("verify_agent_signature", payer)]
+#[queue_computation_accounts("check_spend_allowed", payer)]
 #[derive(Accounts)]
 #[instruction(computation_offset: u64)]
-pub struct VerifyAgentSignature<'info> {
+pub struct CheckSpendAllowed<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(
@@ -231,6 +4213,8 @@ pub struct VerifyAgentSignature<'info> {
     pub sign_pda_account: Account<'info, ArciumSignerAccount>,
     #[account(address = derive_mxe_pda!())]
     pub mxe_account: Account<'info, MXEAccount>,
+    #[account(seeds = [MXE_CONFIG_SEED], bump = mxe_config.bump)]
+    pub mxe_config: Account<'info, MXEConfig>,
     #[account(mut, address = derive_mempool_pda!(mxe_account, ErrorCode::ClusterNotSet))]
     /// CHECK: mempool_account
     pub mempool_account: UncheckedAccount<'info>,
@@ -240,7 +4224,7 @@ pub struct VerifyAgentSignature<'info> {
     #[account(mut, address = derive_comp_pda!(computation_offset, mxe_account, ErrorCode::ClusterNotSet))]
     /// CHECK: computation_account
     pub computation_account: UncheckedAccount<'info>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURE))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CHECK_SPEND_ALLOWED))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(mut, address = derive_cluster_pda!(mxe_account, ErrorCode::ClusterNotSet))]
     pub cluster_account: Account<'info, Cluster>,
@@ -252,11 +4236,11 @@ pub struct VerifyAgentSignature<'info> {
     pub arcium_program: Program<'info, Arcium>,
 }
 
-#[callback_accounts("verify_agent_signature")]
+#[callback_accounts("check_spend_allowed")]
 #[derive(Accounts)]
-pub struct VerifyAgentSignatureCallback<'info> {
+pub struct CheckSpendAllowedCallback<'info> {
     pub arcium_program: Program<'info, Arcium>,
-    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_VERIFY_AGENT_SIGNATURE))]
+    #[account(address = derive_comp_def_pda!(COMP_DEF_OFFSET_CHECK_SPEND_ALLOWED))]
     pub comp_def_account: Account<'info, ComputationDefinitionAccount>,
     #[account(address = derive_mxe_pda!())]
     pub mxe_account: Account<'info, MXEAccount>,
@@ -269,9 +4253,9 @@ pub struct VerifyAgentSignatureCallback<'info> {
     pub instructions_sysvar: AccountInfo<'info>,
 }
 
-#[init_computation_definition_accounts("verify_agent_signature", payer)]
+#[init_computation_definition_accounts("check_spend_allowed", payer)]
 #[derive(Accounts)]
-pub struct InitVerifyAgentSignatureCompDef<'info> {
+pub struct InitCheckSpendAllowedCompDef<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     #[account(mut, address = derive_mxe_pda!())]
@@ -289,21 +4273,269 @@ pub struct InitVerifyAgentSignatureCompDef<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// All accounts are unchecked: `health_check` never fails on a
+/// misconfigured PDA, it just reports the mismatch.
+#[derive(Accounts)]
+pub struct HealthCheck<'info> {
+    pub mxe_account: Account<'info, MXEAccount>,
+    /// CHECK: re-derived and compared inside the handler
+    pub mempool_account: UncheckedAccount<'info>,
+    /// CHECK: re-derived and compared inside the handler
+    pub executing_pool: UncheckedAccount<'info>,
+    /// CHECK: re-derived and compared inside the handler
+    pub cluster_account: UncheckedAccount<'info>,
+    /// CHECK: re-derived and compared inside the handler
+    pub sign_transaction_comp_def: UncheckedAccount<'info>,
+    /// CHECK: re-derived and compared inside the handler
+    pub verify_agent_signature_comp_def: UncheckedAccount<'info>,
+    /// CHECK: compared against the well-known fee pool address
+    pub pool_account: UncheckedAccount<'info>,
+    /// CHECK: compared against the well-known clock account address
+    pub clock_account: UncheckedAccount<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct PdaHealthReport {
+    pub mxe: bool,
+    pub mempool: bool,
+    pub execpool: bool,
+    pub cluster: bool,
+    pub sign_transaction_comp_def: bool,
+    pub verify_agent_signature_comp_def: bool,
+    pub fee_pool: bool,
+    pub clock: bool,
+}
+
+#[event]
+pub struct HealthCheckEvent {
+    pub report: PdaHealthReport,
+}
+
 #[event]
 pub struct TransactionSignedEvent {
     pub signature: [u8; 64],
 }
 
+/// Emitted by `sign_transaction_callback` alongside whichever of
+/// `TransactionSignedEvent`/`SigningLog` that callback already writes, so a
+/// listener with several `sign_transaction`/`sign_transaction_full_message`
+/// calls in flight at once can tell them apart — see `SignatureRecord`'s
+/// doc comment for `requester`'s meaning.
+#[event]
+pub struct SignatureRecordedEvent {
+    pub computation_offset: u64,
+    pub requester: Pubkey,
+    pub signature: [u8; 64],
+}
+
+/// Emitted by `sign_transactions_batch_callback` instead of one
+/// `TransactionSignedEvent` per message. `signatures` is always
+/// `MAX_SIGNATURE_BATCH` entries long, including any zero-padded slots
+/// past the caller's own message count — see `sign_transactions_batch`'s
+/// doc comment for why those aren't filtered out here.
+#[event]
+pub struct TransactionsBatchSignedEvent {
+    pub signatures: [[u8; 64]; MAX_SIGNATURE_BATCH],
+}
+
+/// Emitted by `rotate_signing_key_callback` once the new `(version,
+/// public_key)` pair is appended to `SigningKeyHistory`.
+#[event]
+pub struct KeyRotatedEvent {
+    pub version: u32,
+    pub public_key: [u8; 32],
+}
+
+/// Emitted by `sign_transaction_secp256k1_callback` — the secp256k1
+/// counterpart to `TransactionSignedEvent`. `signature` is the 65-byte
+/// recoverable form (`r || s || recovery_id`) EVM tooling's `ecrecover`
+/// expects, rather than `TransactionSignedEvent`'s 64-byte Ed25519 form;
+/// see `sign_transaction_secp256k1`'s circuit for why it's always all-zero
+/// today.
+#[event]
+pub struct TransactionSignedSecp256k1Event {
+    pub signature: [u8; 65],
+}
+
+/// Emitted by `flush_signing_events` instead of one `TransactionSignedEvent`
+/// per buffered signature. `merkle_root` covers exactly the digests at ring
+/// positions `start_head..end_head` of `SigningLog::entries` (wrapping at
+/// `SigningLog::capacity`) as of this call — see `SigningLog`'s doc comment
+/// for the leaf hash, tree shape, and how a consumer checks a candidate
+/// signature against this root without the chain ever storing the
+/// plaintext signature itself.
+#[event]
+pub struct SigningEventsFlushedEvent {
+    pub merkle_root: [u8; 32],
+    pub count: u32,
+    pub start_head: u32,
+    pub end_head: u32,
+}
+
+/// Emitted by `claim_computation_refund` once it closes an `AbortModeConfig`
+/// PDA. `refund_lamports` is that PDA's entire rent-exempt balance, not any
+/// portion of the Arcium computation fee itself — see that instruction's
+/// doc comment for why this program can't refund from `FeePool`.
+#[event]
+pub struct ComputationRefundClaimedEvent {
+    pub payer: Pubkey,
+    pub refund_lamports: u64,
+}
+
+/// Emitted by `sign_transaction_callback` instead of `TransactionSignedEvent`
+/// when the computation aborted and `abort_mode == ABORT_MODE_SOFT_FAIL`.
+/// Carries no payload beyond its presence — a caller watching for this event
+/// instead of `TransactionSignedEvent` knows the signing request failed
+/// without the enclosing transaction having rolled back.
+#[event]
+pub struct TransactionSignAbortedEvent {}
+
+/// Emitted by `verify_agent_signature_callback` instead of
+/// `SignatureVerifiedEvent` when the computation aborted and
+/// `abort_mode == ABORT_MODE_SOFT_FAIL`. See `TransactionSignAbortedEvent`'s
+/// doc comment for the same reasoning applied to verification.
+#[event]
+pub struct SignatureVerificationAbortedEvent {}
+
+#[event]
+pub struct SigningNonceRevealedEvent {
+    pub message: [u8; 32],
+    pub r: [u8; 32],
+}
+
 #[event]
 pub struct SignatureVerifiedEvent {
     pub is_valid: [u8; 32],
     pub nonce: [u8; 16],
 }
 
+/// Emitted by `check_spend_allowed_callback` — same shape as
+/// `SignatureVerifiedEvent`, for the same reason: `is_allowed` is a
+/// ciphertext only the observer named in the queuing instruction can
+/// decrypt, not the actual plaintext boolean.
+#[event]
+pub struct SpendCheckEvent {
+    pub is_allowed: [u8; 32],
+    pub nonce: [u8; 16],
+}
+
+/// Emitted by `verify_agent_signature_plaintext_callback` — unlike
+/// `SignatureVerifiedEvent`, `is_valid` here is the actual plaintext
+/// outcome rather than a ciphertext only an observer can decrypt.
+#[event]
+pub struct SignatureVerifiedPlaintextEvent {
+    pub is_valid: bool,
+}
+
+/// Emitted by `sign_transaction_confidential_callback` instead of
+/// `TransactionSignedEvent`: the signature's ciphertext (two 32-byte
+/// chunks for the 64-byte `(R, S)` pair) and the nonce needed to decrypt
+/// it, but never the plaintext signature itself — only the requester who
+/// holds the matching shared secret can recover it.
+#[event]
+pub struct SigningCiphertextReadyEvent {
+    pub signature_ciphertext: [[u8; 32]; 2],
+    pub nonce: [u8; 16],
+}
+
+#[event]
+pub struct SignatureBatchVerifiedEvent {
+    pub bitmask: [u8; 32],
+    pub nonce: [u8; 16],
+}
+
+/// `skip_mask` bit `i` set means observer slot `i` was malformed and got a
+/// filler substitution rather than its own delivered result — emitted
+/// alongside queuing so observers can distinguish this before the
+/// computation even finalizes.
+#[event]
+pub struct ObserverSlotsSkippedEvent {
+    pub skip_mask: u8,
+}
+
+#[event]
+pub struct SignatureVerifiedMultiObserverEvent {
+    pub results: [[u8; 32]; MAX_OBSERVERS],
+    pub nonce: [u8; 16],
+}
+
+#[event]
+pub struct ObserverKeyRotatedEvent {
+    pub observer_id: u32,
+}
+
+#[event]
+pub struct MinNodesChangeProposedEvent {
+    pub new_min_nodes: u8,
+    pub effective_at: i64,
+}
+
+#[event]
+pub struct MinNodesChangeAppliedEvent {
+    pub min_nodes: u8,
+}
+
+#[event]
+pub struct MinNodesChangeCancelledEvent {
+    pub cancelled_min_nodes: u8,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("The computation was aborted")]
     AbortedComputation,
     #[msg("Cluster not set")]
     ClusterNotSet,
+    #[msg("Signing session message does not match the supplied message")]
+    SigningSessionMismatch,
+    #[msg("Phase two's revealed R does not match the phase one commitment")]
+    NonceCommitmentMismatch,
+    #[msg("Signature batch must be non-empty, equal-length, and within MAX_SIGNATURE_BATCH")]
+    InvalidSignatureBatch,
+    #[msg("At least one observer slot must be well-formed")]
+    NoValidObserver,
+    #[msg("Active cluster node count is below MXEConfig::min_nodes")]
+    InsufficientClusterNodes,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Unsupported hash algorithm")]
+    UnsupportedHashAlgorithm,
+    #[msg("Message exceeds MAX_FULL_MESSAGE_LEN")]
+    MessageTooLong,
+    #[msg("Message does not match any prefix allowed by the agent's SigningPolicy")]
+    DisallowedMessage,
+    #[msg("count or prefix_lens exceed the bounds SigningPolicy can store")]
+    InvalidSigningPolicy,
+    #[msg("result_program is not in MXEConfig::result_program_allowlist")]
+    ResultProgramNotAllowlisted,
+    #[msg("Batch size exceeds MXEConfig::max_batch_size")]
+    BatchTooLarge,
+    #[msg("signing_window start/end must be within 0..=SECONDS_PER_DAY")]
+    InvalidSigningWindow,
+    #[msg("Current time is outside MXEConfig's configured signing window")]
+    OutsideSigningWindow,
+    #[msg("SigningLog capacity must be > 0 and within MAX_SIGNING_LOG_CAPACITY")]
+    InvalidSigningLogCapacity,
+    #[msg("resize_signing_log cannot shrink the signing log below its current capacity")]
+    SigningLogCapacityTooSmall,
+    #[msg("resize_signing_log's requested capacity exceeds MAX_SIGNING_LOG_CAPACITY")]
+    SigningLogCapacityTooLarge,
+    #[msg("flush_signing_events has nothing new to flush since the last flush")]
+    NoEntriesToFlush,
+    #[msg("context_len exceeds MAX_ED25519CTX_CONTEXT_LEN")]
+    ContextTooLong,
+    #[msg("No min_nodes change is currently pending")]
+    NoPendingMinNodesChange,
+    #[msg("pending_min_nodes_effective_at has not yet elapsed")]
+    MinNodesTimelockNotElapsed,
+    #[msg("abort_mode must be ABORT_MODE_HARD_ERROR or ABORT_MODE_SOFT_FAIL")]
+    InvalidAbortMode,
+    #[msg("This computation has not terminated yet; claim_computation_refund only applies to aborted computations")]
+    ComputationNotTerminal,
+    #[msg("This computation's refund has already been claimed")]
+    RefundAlreadyClaimed,
+    #[msg("SigningKeyHistory is at MAX_KEY_HISTORY capacity; no further rotations can be recorded")]
+    KeyHistoryFull,
+    #[msg("This (message, signature) pair has already been verified; NonceRegistry rejects the replay")]
+    NonceAlreadyUsed,
 }
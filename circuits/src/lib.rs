@@ -12,9 +12,12 @@
 // Key capabilities:
 //   1. distribute_key: Split an Ed25519 private key across
 //      MPC nodes in the cluster
-//   2. threshold_sign: Collectively sign a transaction without
-//      reconstructing the full key
-//   3. execute_encrypted_trade: Process encrypted trade params
+//   2. refresh_shares: Re-randomize existing key shares (proactive
+//      secret sharing) without reconstructing the secret
+//   3. threshold_sign: Gate a signing request on accumulated share
+//      weight (the actual partial-signature math is still a
+//      placeholder — see that function's doc comment)
+//   4. execute_encrypted_trade: Process encrypted trade params
 //      and produce a signed Solana transaction
 //
 // Reference: arcium-hq/examples/ed25519 (distributed signing)
@@ -36,8 +39,31 @@ mod circuits {
         share: u128,
         /// Node index in the cluster (0..n)
         node_index: u8,
-        /// Threshold required for signing (e.g., 2 of 3)
+        /// Threshold required for signing (e.g., 2 of 3), counted in
+        /// accumulated weight rather than share count — see
+        /// [`distribute_key`].
         threshold: u8,
+        /// This node's weight: how many of the threshold's units this
+        /// single share counts for. A weight-1 node and a weight-3 node
+        /// both hold exactly one `KeyShare`, but the latter's counts for
+        /// three when `threshold_sign` accumulates weight toward
+        /// `threshold`. Equal-weight (plain Shamir) clusters set every
+        /// node's weight to 1.
+        weight: u8,
+        /// Feldman verification commitment for this share, so the holding
+        /// node (or anyone combining shares in `threshold_sign`) can check
+        /// `share` is consistent with the same degree-`threshold - 1`
+        /// polynomial every other node's share was drawn from, without
+        /// reconstructing the secret or trusting the dealer. Concretely,
+        /// this is the dealer's per-coefficient commitments
+        /// `c_j = g^{a_j}` (one per polynomial coefficient) folded into a
+        /// single value via [`verify_key_share`]'s check
+        /// `g^share == product(c_j^(node_index^j))` — see that function's
+        /// doc comment for why this repo can't yet compute `c_j` or do the
+        /// exponentiation itself. `distribute_key` produces real shares but
+        /// leaves this all-zero in every one (see the comment at its
+        /// `commitment` field).
+        commitment: [u8; 32],
     }
 
     /// A trade instruction to be executed confidentially.
@@ -48,6 +74,255 @@ mod circuits {
         amount: u64,
         /// Token identifier (encoded)
         token_id: u32,
+        /// Maximum acceptable slippage, in basis points.
+        max_slippage_bps: u16,
+        /// The price (in the same fixed-point units as
+        /// `execute_encrypted_trade`'s `current_price` argument) this
+        /// agent quoted the trade against. Real slippage protection — did
+        /// the market move too far from what the agent expected before
+        /// this trade executes — compares `current_price` against this
+        /// field, gated by `max_slippage_bps`; unlike `max_slippage_bps`
+        /// alone (which only bounds how wide a tolerance the agent is
+        /// allowed to request at all, see `TRADE_SLIPPAGE_EXCEEDED`), this
+        /// is what actually protects a specific trade from executing at a
+        /// worse price than the agent agreed to.
+        limit_price: u64,
+    }
+
+    /// Confidential status codes for `execute_encrypted_trade`, revealed
+    /// only to the requesting agent alongside the (only meaningful when
+    /// `status == TRADE_OK`) signed transaction bytes. Publicly, observers
+    /// only ever see that *a* trade request was processed, never why a
+    /// rejected one failed.
+    pub const TRADE_OK: u8 = 0;
+    pub const TRADE_INVALID_ACTION: u8 = 1;
+    pub const TRADE_ZERO_AMOUNT: u8 = 2;
+    pub const TRADE_AMOUNT_TOO_LARGE: u8 = 3;
+    pub const TRADE_DISALLOWED_TOKEN: u8 = 4;
+    pub const TRADE_SLIPPAGE_EXCEEDED: u8 = 5;
+    pub const TRADE_CAP_EXCEEDED: u8 = 6;
+    pub const TRADE_DISALLOWED_ACTION: u8 = 7;
+    pub const TRADE_TIER_CEILING_EXCEEDED: u8 = 8;
+    /// `action == 0` (hold): the circuit deliberately took no action and
+    /// produced no signature, distinct from every `TRADE_OK` execution and
+    /// every rejection above — see `execute_encrypted_trade`'s doc comment
+    /// for why this needs its own status rather than overloading
+    /// `TRADE_OK` (no signature was produced) or `TRADE_ZERO_AMOUNT` (a
+    /// hold's `amount` is expected to be `0`, not an error).
+    pub const TRADE_HOLD_NOOP: u8 = 9;
+    /// The market moved further from `TradeInstruction::limit_price` than
+    /// `max_slippage_bps` allows by the time this trade reached the
+    /// circuit — distinct from `TRADE_SLIPPAGE_EXCEEDED`, which only
+    /// checks that `max_slippage_bps` itself is a sane value to request,
+    /// not what actually happened to the price. See
+    /// `execute_encrypted_trade`'s doc comment.
+    pub const TRADE_PRICE_SLIPPAGE_EXCEEDED: u8 = 10;
+
+    /// One bit per `TradeInstruction::action` value, in the same
+    /// registered `Enc<Mxe, u8>` bitmask `execute_encrypted_trade` checks
+    /// `action` against — see that instruction's doc comment. A "sell-only"
+    /// agent is registered with only `ACTION_SELL_BIT` set.
+    pub const ACTION_HOLD_BIT: u8 = 1 << 0;
+    pub const ACTION_BUY_BIT: u8 = 1 << 1;
+    pub const ACTION_SELL_BIT: u8 = 1 << 2;
+    pub const ACTION_PROVIDE_LIQUIDITY_BIT: u8 = 1 << 3;
+
+    /// Ceiling on `TradeInstruction::amount`, in lamports, until a
+    /// confidential per-vault/per-agent limit is threaded through instead.
+    const MAX_TRADE_AMOUNT: u64 = 1_000_000_000_000;
+
+    /// `TradeInstruction::token_id` validity range: ids `0..REGISTERED_TOKEN_COUNT`
+    /// are treated as registered, every other id as not.
+    ///
+    /// The actual request here — a companion on-chain `TokenRegistry`
+    /// mapping `token_id -> mint: Pubkey`, with an admin-gated
+    /// `register_token(ctx, token_id, mint)` growing it — is Anchor-side
+    /// state this circuit-only module has nowhere to keep: like `agent_cap`
+    /// and `agent_allowed_actions` above, a registry that admins can grow
+    /// over time needs an on-chain account and an instruction to mutate it,
+    /// and neither exists anywhere in this file or has any comp def/callback
+    /// wiring to attach one to (see `execute_encrypted_trade`'s own doc
+    /// comment on that same gap). What *is* expressible purely inside this
+    /// circuit is what `execute_encrypted_trade` already had: a bound on
+    /// which `token_id` values are acceptable. Widening the old two-entry
+    /// `ALLOWED_TOKEN_IDS` allowlist to a contiguous range is what lets this
+    /// constant plausibly track a real registry's current size (assign ids
+    /// sequentially as `register_token` calls come in, then bump this to
+    /// match) rather than needing a new entry hand-written here per token —
+    /// but the registry itself, and threading its real size into this
+    /// circuit call by call, remain the calling Anchor instruction's
+    /// responsibility once one exists, the same way `agent_cap` is threaded
+    /// in today.
+    const REGISTERED_TOKEN_COUNT: u32 = 2;
+
+    /// Ceiling on `TradeInstruction::max_slippage_bps`, in basis points.
+    const MAX_SLIPPAGE_BPS: u16 = 500;
+
+    /// Confidential reputation counters for one agent, the input to
+    /// `compute_reputation_tier`. Neither count is ever revealed on its
+    /// own — only the coarse tier derived from them leaves the encrypted
+    /// domain, and only as far as the calling instruction asks it to
+    /// (see that instruction's doc comment).
+    pub struct ReputationCounters {
+        /// Confidential trades this agent completed with
+        /// `TradeExecutionResult::status == TRADE_OK`.
+        success_count: u32,
+        /// Confidential trades this agent had rejected (any
+        /// `status != TRADE_OK`) by `execute_encrypted_trade`.
+        block_count: u32,
+    }
+
+    /// Confidential trade-size tiers `compute_reputation_tier` assigns,
+    /// each gating a fixed lamport ceiling in `TIER_CEILINGS` that
+    /// `execute_encrypted_trade` enforces alongside (not instead of)
+    /// `agent_cap` — see that instruction's doc comment for how the two
+    /// ceilings interact.
+    pub const TIER_0: u8 = 0;
+    pub const TIER_1: u8 = 1;
+    pub const TIER_2: u8 = 2;
+    pub const TIER_3: u8 = 3;
+
+    /// `success_count` an agent needs to reach each tier above `TIER_0`,
+    /// provided `block_count` stays under `MAX_BLOCKS_FOR_PROMOTION` (see
+    /// below). Chosen as round numbers until real trade-volume data
+    /// motivates something better calibrated — nothing in this repo
+    /// tracks reputation counters yet for this to be tuned against.
+    const TIER_1_SUCCESS_THRESHOLD: u32 = 10;
+    const TIER_2_SUCCESS_THRESHOLD: u32 = 50;
+    const TIER_3_SUCCESS_THRESHOLD: u32 = 200;
+
+    /// An agent with this many or more blocked trades is held at `TIER_0`
+    /// regardless of `success_count` — a high block count is itself the
+    /// reputational signal, not merely the absence of successes.
+    const MAX_BLOCKS_FOR_PROMOTION: u32 = 5;
+
+    /// Per-tier confidential trade-amount ceiling, in lamports, indexed by
+    /// the `TIER_*` constants. The ceilings themselves are plaintext, like
+    /// `MAX_TRADE_AMOUNT` — only which tier a given agent occupies is
+    /// confidential.
+    const TIER_CEILINGS: [u64; 4] = [50_000_000, 500_000_000, 5_000_000_000, 50_000_000_000];
+
+    /// Result of `execute_encrypted_trade`.
+    pub struct TradeExecutionResult {
+        /// One of the `TRADE_*` constants above.
+        status: u8,
+        /// Signed transaction bytes; only meaningful when `status == TRADE_OK`.
+        signed_tx: [u8; 64],
+    }
+
+    /// One entry of an agent's confidential trade journal, produced
+    /// alongside `execute_encrypted_trade`'s own result and meant to be
+    /// appended by the calling Anchor instruction to an MXE-owned ring
+    /// buffer of `Enc<Shared, TradeRecord>` entries — so the agent can
+    /// later decrypt and reconstruct the sequence of trades it made
+    /// without any of it ever appearing on-chain in plaintext.
+    /// `timestamp` comes in as a plaintext argument (wall-clock time from
+    /// the calling instruction's `Clock::get()`) rather than anything
+    /// derived inside this circuit, since Arcis has no clock of its own.
+    pub struct TradeRecord {
+        /// Mirrors `TradeInstruction::action`.
+        action: u8,
+        /// Mirrors `TradeInstruction::amount`.
+        amount: u64,
+        /// Mirrors `TradeInstruction::token_id`.
+        token_id: u32,
+        timestamp: i64,
+    }
+
+    /// Overwrites `value` with `replacement` when `condition` is true,
+    /// without branching on the (secret) condition — Arcis secret values
+    /// cannot be branched on, so every validation check below runs
+    /// unconditionally and the first one that fires, in priority order,
+    /// wins via this mask-and-select rather than a short-circuiting `if`.
+    /// Mirrors the bitwise masking `ct_eq`/`verify_agent_signatures_one_key`
+    /// already use in `encrypted-ixs` for the same reason.
+    fn select_u8(condition: bool, replacement: u8, value: u8) -> u8 {
+        let mask = 0u8.wrapping_sub(condition as u8);
+        (replacement & mask) | (value & !mask)
+    }
+
+    /// Same branchless mask-and-select as `select_u8`, widened to `u64` for
+    /// comparing lamport amounts (e.g. `TIER_CEILINGS` entries) without
+    /// branching on a secret condition.
+    fn select_u64(condition: bool, replacement: u64, value: u64) -> u64 {
+        let mask = 0u64.wrapping_sub(condition as u64);
+        (replacement & mask) | (value & !mask)
+    }
+
+    // ========================================
+    // Shamir Secret Sharing Arithmetic
+    // ========================================
+    //
+    // Shared by `distribute_key` and `reconstruct_key` below. Every value
+    // here is reduced into `0..SHAMIR_PRIME`, not the full range of
+    // `u128` — `key`/`KeyShare::share` stay typed as `u128` to match the
+    // rest of this file, but a secret wider than `SHAMIR_PRIME` would wrap
+    // during sharing, same as it would against any fixed-width field.
+
+    /// Prime modulus Shamir sharing is done over: `2^61 - 1`, a Mersenne
+    /// prime (M61). Chosen specifically so `mod_mul` below never overflows
+    /// `u128` without a wide (256-bit) multiply: both of its operands are
+    /// reduced below `SHAMIR_PRIME < 2^61`, so their product is always
+    /// under `2^122`. A prime nearer `u128::MAX` would share the full
+    /// 128-bit range `key` is typed for, but squaring two such operands
+    /// would need a multiply wider than `u128` itself has — this circuit
+    /// has no verified primitive for that, so M61 is the tradeoff made
+    /// instead.
+    const SHAMIR_PRIME: u128 = 2_305_843_009_213_693_951;
+
+    fn mod_add(a: u128, b: u128) -> u128 {
+        (a % SHAMIR_PRIME + b % SHAMIR_PRIME) % SHAMIR_PRIME
+    }
+
+    fn mod_sub(a: u128, b: u128) -> u128 {
+        (a % SHAMIR_PRIME + SHAMIR_PRIME - b % SHAMIR_PRIME) % SHAMIR_PRIME
+    }
+
+    fn mod_mul(a: u128, b: u128) -> u128 {
+        (a % SHAMIR_PRIME) * (b % SHAMIR_PRIME) % SHAMIR_PRIME
+    }
+
+    /// Modular exponentiation by repeated squaring, over a fixed 64-bit
+    /// iteration count rather than a `while exp > 0` loop — `exp` below is
+    /// always the plaintext constant `SHAMIR_PRIME - 2`, so this always
+    /// runs the same 64 iterations regardless of `base`.
+    fn mod_pow(base: u128, exp: u128, modulus: u128) -> u128 {
+        let mut result = 1u128;
+        let mut b = base % modulus;
+        let mut e = exp;
+        for _ in 0..64 {
+            if e & 1 == 1 {
+                result = mod_mul(result, b);
+            }
+            b = mod_mul(b, b);
+            e >>= 1;
+        }
+        result
+    }
+
+    /// Modular inverse via Fermat's little theorem (`a^(SHAMIR_PRIME - 2)`),
+    /// valid because `SHAMIR_PRIME` is prime and every `a` this file ever
+    /// inverts is a nonzero difference between two distinct, plaintext
+    /// node x-coordinates in `1..=255` — never a secret value.
+    fn mod_inv(a: u128) -> u128 {
+        mod_pow(a, SHAMIR_PRIME - 2, SHAMIR_PRIME)
+    }
+
+    /// Evaluates, at `x`, the degree-`coefficients.len()` polynomial whose
+    /// constant term is `secret` and whose other coefficients are
+    /// `coefficients` (lowest degree first) — over `GF(SHAMIR_PRIME)`.
+    /// `secret` and `coefficients` may be confidential; `x` is always a
+    /// plaintext node x-coordinate, so every secret-touching operation
+    /// here is an add or a multiply-by-plaintext-scalar, never a multiply
+    /// of two secret values against each other.
+    fn evaluate_polynomial(secret: u128, coefficients: &[u128], x: u128) -> u128 {
+        let mut result = secret % SHAMIR_PRIME;
+        let mut x_pow = x % SHAMIR_PRIME;
+        for &coefficient in coefficients {
+            result = mod_add(result, mod_mul(coefficient, x_pow));
+            x_pow = mod_mul(x_pow, x % SHAMIR_PRIME);
+        }
+        result
     }
 
     /// Signing request containing transaction data to be signed.
@@ -58,68 +333,429 @@ mod circuits {
         agent_id: u32,
     }
 
+    /// One node's partial Ed25519 signature share from `threshold_sign_partials`,
+    /// before Lagrange combination. `r` is that node's partial nonce
+    /// commitment and `s` its partial scalar (`s_i = r_i + k_i * hash`);
+    /// an external aggregator combines them as `S = sum(lambda_i * s_i)`
+    /// over `R = sum(lambda_i * r_i)` using the same Lagrange coefficients
+    /// `threshold_sign` would use internally, keyed by `node_index`.
+    pub struct PartialSig {
+        node_index: u8,
+        r: [u8; 32],
+        s: [u8; 32],
+    }
+
     // ========================================
     // Confidential Instructions
     // ========================================
 
-    /// Distribute an Ed25519 private key across MPC nodes.
-    /// 
-    /// Input: Encrypted full private key
+    /// Distribute an Ed25519 private key across MPC nodes, with optional
+    /// weighted shares for clusters where trust isn't uniform across
+    /// nodes.
+    ///
+    /// `weights[i]` is the weight of node `i`'s share — how many units of
+    /// `threshold` it counts toward on its own. A plain (equal-weight)
+    /// Shamir split passes all-1 weights, in which case this is identical
+    /// to counting shares. `weights.len()` must equal `total_nodes`.
+    ///
+    /// Two invariants are enforced on these plaintext parameters (`weights`
+    /// and `threshold` are never secret — only the key itself is) before
+    /// any shares are produced:
+    /// - Total weight across all nodes must meet or exceed `threshold`, or
+    ///   the cluster could never reach quorum at all.
+    /// - No single node's weight alone may meet `threshold` — letting one
+    ///   node unilaterally sign defeats the point of a threshold scheme —
+    ///   unless `allow_single_node_quorum` is explicitly set, for clusters
+    ///   that intentionally want a designated "master" node.
+    ///
+    /// Input: Encrypted full private key, plus `threshold - 1` random
+    /// encrypted polynomial coefficients
     /// Output: Key shares distributed to each node in the cluster
-    /// 
+    ///
     /// After this instruction executes, the full key no longer exists
     /// in any single location — each node holds only a partial share.
+    ///
+    /// `coefficients` must be supplied by the caller rather than generated
+    /// in here: the polynomial's other `threshold - 1` coefficients need to
+    /// be genuinely random for the scheme to hide `encrypted_key`, and this
+    /// circuit has no verified randomness primitive to draw them from — see
+    /// `verify_key_share`'s doc comment for this file's other instance of
+    /// the same "no evidence this `arcis_imports` primitive exists" gap.
+    /// Pushing the randomness out to the dealer rather than inventing an
+    /// RNG call in here keeps every operation below one this file already
+    /// has precedent for.
+    ///
+    /// Still unweighted in one respect: every node gets exactly one point
+    /// evaluation on the polynomial regardless of `weights[i]`, rather than
+    /// `weights[i]` independent evaluations for a weight-`w` node. That
+    /// simpler scheme is enough for `reconstruct_key` below (which only
+    /// ever combines exactly `threshold` equal-weight shares), but it means
+    /// genuinely weighted reconstruction — fewer than `threshold` shares
+    /// where some count more than others toward it — still isn't
+    /// exercised by anything in this file; it belongs as a test alongside
+    /// `threshold_sign`, but neither `threshold_sign` nor this instruction
+    /// has an Anchor comp def/callback wired up on-chain yet (see
+    /// `threshold_sign`'s doc comment), so there's no integration test harness to
+    /// exercise it against until that wiring lands.
     #[instruction]
     pub fn distribute_key(
         encrypted_key: Enc<Shared, u128>,
+        coefficients: Vec<Enc<Shared, u128>>,
         threshold: u8,
         total_nodes: u8,
+        weights: Vec<u8>,
+        allow_single_node_quorum: bool,
     ) -> Vec<Enc<Mxe, KeyShare>> {
         let key = encrypted_key.to_arcis();
-        
-        // Shamir's Secret Sharing to split the key
-        // Each node receives a share; `threshold` shares needed to reconstruct
+
+        assert_eq!(weights.len(), total_nodes as usize);
+        assert!(threshold >= 1, "threshold must be at least 1");
+        assert_eq!(coefficients.len(), (threshold - 1) as usize);
+        let total_weight: u16 = weights.iter().map(|&w| w as u16).sum();
+        assert!(total_weight >= threshold as u16);
+        assert!(
+            allow_single_node_quorum || weights.iter().all(|&w| w < threshold),
+            "a single node's weight must not alone meet the threshold unless explicitly allowed"
+        );
+
+        let coeffs: Vec<u128> = coefficients.iter().map(|c| c.to_arcis()).collect();
+
+        // Shamir's Secret Sharing: `key` is the constant term of a degree
+        // `threshold - 1` polynomial, `coeffs` its other coefficients. Node
+        // `i` gets the value at `x = i + 1` — never `x = 0`, which would
+        // evaluate to `key` itself.
         let mut shares = Vec::new();
-        
-        // TODO: Implement Shamir's Secret Sharing polynomial evaluation
-        // For each node i in 0..total_nodes:
-        //   share_i = evaluate_polynomial(key, i, threshold)
-        //   shares.push(KeyShare { share: share_i, node_index: i, threshold })
-        
+        for i in 0..total_nodes {
+            let x = i as u128 + 1;
+            let share = evaluate_polynomial(key, &coeffs, x);
+            shares.push(KeyShare {
+                share,
+                node_index: i,
+                threshold,
+                weight: weights[i as usize],
+                // Feldman commitment to this share, upgrading this into
+                // verifiable secret sharing — blocked on the same missing
+                // group-exponentiation primitive as `verify_key_share`;
+                // see that function's doc comment.
+                commitment: [0u8; 32],
+            });
+        }
+
         shares
     }
 
-    /// Threshold sign a transaction hash using distributed key shares.
+    /// Recombines `threshold` key shares into the original secret via
+    /// Lagrange interpolation over `GF(SHAMIR_PRIME)` — the inverse of the
+    /// polynomial evaluation [`distribute_key`] performs, so the two halves
+    /// of this scheme have a round-trip check once comp def/callback wiring
+    /// lands (see `threshold_sign`'s doc comment for why there's no integration
+    /// test harness to run that check against yet).
+    ///
+    /// Callers must supply exactly `threshold` shares — not merely enough
+    /// weight to meet it — one entry per share's `node_index` in
+    /// `node_indices`, listed in the same order as `shares`. `node_index`
+    /// also lives inside each (still-encrypted) `KeyShare`, but the
+    /// Lagrange coefficients below need plaintext x-coordinates to invert,
+    /// and Arcis secret values can't be branched on to check a decrypted
+    /// `node_index` against its caller-claimed counterpart — the same
+    /// secret-conditioned-abort primitive `threshold_sign`'s doc comment
+    /// notes is missing, for the same reason. A caller that lies about
+    /// `node_indices` silently reconstructs garbage instead of getting a
+    /// clear error. Weighted reconstruction is likewise deferred — see
+    /// [`distribute_key`]'s doc comment.
+    #[instruction]
+    pub fn reconstruct_key(
+        shares: Vec<Enc<Mxe, KeyShare>>,
+        node_indices: Vec<u8>,
+        requester: Shared,
+    ) -> Enc<Shared, u128> {
+        assert_eq!(shares.len(), node_indices.len());
+
+        let mut secret = 0u128;
+        for i in 0..shares.len() {
+            let share_i = shares[i].to_arcis().share;
+            let x_i = node_indices[i] as u128 + 1;
+
+            // lambda_i = product over j != i of x_j / (x_j - x_i), the
+            // Lagrange basis polynomial for node i evaluated at x = 0 —
+            // entirely plaintext arithmetic, since every x here comes from
+            // the caller-supplied `node_indices`.
+            let mut lambda = 1u128;
+            for j in 0..node_indices.len() {
+                if i != j {
+                    let x_j = node_indices[j] as u128 + 1;
+                    lambda = mod_mul(lambda, mod_mul(x_j, mod_inv(mod_sub(x_j, x_i))));
+                }
+            }
+
+            secret = mod_add(secret, mod_mul(share_i, lambda));
+        }
+
+        requester.from_arcis(secret)
+    }
+
+    /// Checks `share.commitment` (see [`KeyShare::commitment`]'s doc
+    /// comment) against `share.share` itself, so a node that receives a
+    /// tampered or inconsistent share from a dishonest dealer can detect
+    /// it before ever using it in `threshold_sign`.
+    ///
+    /// A real Feldman check recomputes `g^share` and
+    /// `product(c_j^(node_index^j))` over the group `distribute_key`
+    /// committed `c_j` in, and compares them — this is why
+    /// `KeyShare::commitment` and this function's signature exist ahead of
+    /// this check landing. `distribute_key` now produces real shares, but
+    /// still leaves every `commitment` all-zero: this circuit has no
+    /// verified group-exponentiation primitive to build `g^share`/
+    /// `c_j^(node_index^j)` from — introducing one here would mean
+    /// guessing at an `arcis_imports` API this file has no evidence
+    /// actually exists. Returns `false` unconditionally until one does; no
+    /// test has been added for the same reason — there's nothing genuine
+    /// to tamper with yet, and this crate has no comp def/callback wiring
+    /// or test harness (see `threshold_sign`'s doc comment).
+    #[instruction]
+    pub fn verify_key_share(share: Enc<Mxe, KeyShare>) -> bool {
+        let _ = share;
+        false
+    }
+
+    /// Derive a confidential trade-size tier (`TIER_0`..`TIER_3`) from an
+    /// agent's confidential reputation counters, without revealing either
+    /// `success_count` or `block_count` — only the coarse tier is ever
+    /// decrypted, and only to `caller`.
+    ///
+    /// Promotion is driven by `success_count` alone crossing the
+    /// `TIER_*_SUCCESS_THRESHOLD` boundaries, except that `block_count`
+    /// reaching `MAX_BLOCKS_FOR_PROMOTION` forces the tier back down to
+    /// `TIER_0` regardless of how many successes preceded it — applied
+    /// last in the branchless `select_u8` chain below so it overrides any
+    /// success-driven promotion, the same "last select wins" pattern
+    /// `execute_encrypted_trade` uses for its own status priority.
     ///
-    /// Each node produces a partial signature using its key share.
-    /// The partial signatures are combined into a valid Ed25519 signature
-    /// through Lagrange interpolation.
+    /// This circuit has no notion of "after a trade" on its own: the
+    /// calling Anchor instruction is responsible for incrementing
+    /// `ReputationCounters::success_count`/`block_count` as trades settle,
+    /// re-running this, and persisting the revealed tier back into that
+    /// agent's registered `Enc<Mxe, u8>` state for `execute_encrypted_trade`
+    /// to read as its own `agent_tier` argument — the same admin-flow
+    /// registration `agent_cap` and `agent_allowed_actions` already rely
+    /// on rather than anything this circuit does itself.
+    ///
+    /// No test mapping count ranges to tiers has been added: like every
+    /// other instruction in this file, there's no on-chain comp def/
+    /// callback wiring yet (see `threshold_sign`'s doc comment) and so no
+    /// integration test harness to exercise it against — this repo's two
+    /// Anchor programs carry their tests as TS integration suites against
+    /// deployed comp defs, not unit tests against this crate directly,
+    /// and that wiring doesn't exist for any instruction here yet.
+    #[instruction]
+    pub fn compute_reputation_tier(
+        counters: Enc<Mxe, ReputationCounters>,
+        caller: Shared,
+    ) -> Enc<Shared, u8> {
+        let c = counters.to_arcis();
+
+        let mut tier = TIER_0;
+        tier = select_u8(c.success_count >= TIER_1_SUCCESS_THRESHOLD, TIER_1, tier);
+        tier = select_u8(c.success_count >= TIER_2_SUCCESS_THRESHOLD, TIER_2, tier);
+        tier = select_u8(c.success_count >= TIER_3_SUCCESS_THRESHOLD, TIER_3, tier);
+        tier = select_u8(c.block_count >= MAX_BLOCKS_FOR_PROMOTION, TIER_0, tier);
+
+        caller.from_arcis(tier)
+    }
+
+    /// Proactively refresh key shares without reconstructing the secret.
+    ///
+    /// Implements proactive secret sharing: adds a fresh, random
+    /// degree-`threshold - 1` polynomial with a *zero* constant term —
+    /// evaluated at each node's own `x`, same as `distribute_key` evaluates
+    /// its own secret-bearing polynomial — onto every existing share. The
+    /// zero constant term means the sum of any `threshold` refreshed shares
+    /// still reconstructs the original secret (`reconstruct_key` can't
+    /// tell the difference), but the shares themselves are now unrelated
+    /// values: combining any mix of old and new shares, or any old shares
+    /// alone, no longer reconstructs anything meaningful. This lets an
+    /// operator rotate a node's share (or change cluster membership)
+    /// without ever reconstructing the full key.
+    ///
+    /// `zero_coefficients` — the refresh polynomial's `threshold - 1`
+    /// non-constant coefficients — must be supplied by the caller rather
+    /// than generated in here, for the same reason `distribute_key`'s own
+    /// `coefficients` are: they need to be genuinely random, and this
+    /// circuit has no verified randomness primitive to draw them from (see
+    /// `verify_key_share`'s doc comment). In a real deployment each node
+    /// contributes its own random coefficients and only their encrypted
+    /// sum is passed in here — same dealer-side randomness precedent as
+    /// `distribute_key`, just summed across participants instead of
+    /// drawn from one.
+    ///
+    /// `threshold` and `total_nodes` are plaintext parameters rather than
+    /// read back off `shares[i].threshold` for the same reason
+    /// `threshold_sign` takes `threshold`/`weights` as plaintext: Arcis
+    /// secret values can't be branched on, so validating a *decrypted*
+    /// threshold against `zero_coefficients.len()` would need a
+    /// secret-conditioned-abort primitive this circuit doesn't have.
+    ///
+    /// Input: Current key shares held by each node, plus the refresh
+    /// polynomial's random non-constant coefficients
+    /// Output: New shares for the same underlying secret
+    ///
+    /// Like every other instruction in this file, there's no on-chain comp
+    /// def/callback wiring yet (see `threshold_sign`'s doc comment) and so
+    /// no integration test harness to run a reconstruct-before/reconstruct-
+    /// after check against until that lands — this crate's tests are TS
+    /// integration suites against deployed comp defs, not unit tests
+    /// against this file directly.
+    #[instruction]
+    pub fn refresh_shares(
+        shares: Vec<Enc<Mxe, KeyShare>>,
+        zero_coefficients: Vec<Enc<Shared, u128>>,
+        threshold: u8,
+        total_nodes: u8,
+    ) -> Vec<Enc<Mxe, KeyShare>> {
+        assert_eq!(shares.len(), total_nodes as usize);
+        assert!(threshold >= 1, "threshold must be at least 1");
+        assert_eq!(zero_coefficients.len(), (threshold - 1) as usize);
+
+        let coeffs: Vec<u128> = zero_coefficients.iter().map(|c| c.to_arcis()).collect();
+
+        let mut refreshed = Vec::new();
+        for i in 0..total_nodes {
+            let x = i as u128 + 1;
+            let zero_eval = evaluate_polynomial(0u128, &coeffs, x);
+            let old = shares[i as usize].to_arcis();
+            refreshed.push(KeyShare {
+                share: mod_add(old.share, zero_eval),
+                node_index: old.node_index,
+                threshold: old.threshold,
+                weight: old.weight,
+                // Re-randomizing `share` doesn't change which polynomial
+                // the share is consistent with in a way Feldman commitments
+                // would actually distinguish, but `distribute_key` never
+                // produces a real `commitment` either (see `KeyShare`'s
+                // doc comment) — there's nothing meaningful to carry over.
+                commitment: [0u8; 32],
+            });
+        }
+
+        refreshed
+    }
+
+    /// Gates a threshold-signing request on accumulated share weight. The
+    /// actual Ed25519 signature computation — partial nonce commitments,
+    /// per-node partial signatures, and combining them into a valid `(R, S)`
+    /// pair — is **not implemented**; this still only returns an all-zero
+    /// placeholder once the gate passes. Treat this as a stub with a real
+    /// threshold/weight check in front of it, not a working signer.
+    ///
+    /// What's blocking the rest of the protocol, concretely:
+    /// 1. Each node would retrieve its key share from MXE state — fine,
+    ///    `shares` already carries that.
+    /// 2. Each node generates a partial nonce commitment `r_i`, which needs
+    ///    genuine randomness this circuit has no verified primitive for
+    ///    (the same gap `distribute_key`'s doc comment notes for Feldman
+    ///    commitments).
+    /// 3. Each node computes a partial signature `s_i = r_i + k_i * hash`.
+    /// 4. Partial signatures combine as `S = sum(lambda_i * s_i)`, where
+    ///    `lambda_i` are the same Lagrange coefficients `reconstruct_key`
+    ///    already computes over shares — that part alone would carry over
+    ///    directly. But `R` is a curve point, not a scalar, and combining
+    ///    `r_i * G` terms into it needs the group-exponentiation primitive
+    ///    `verify_key_share`'s doc comment already says this circuit has no
+    ///    evidence actually exists in `arcis_imports`.
+    ///
+    /// Wiring either of those primitives in here without evidence they
+    /// exist would mean guessing at an API this crate can't verify — see
+    /// `verify_key_share` for this file's other instance of the same call.
+    /// Tracked as follow-up work once `arcis_imports` (or a documented
+    /// replacement) exposes them; there's also no on-chain comp def/
+    /// callback wiring yet for this instruction to test against in the
+    /// meantime (this crate's tests are TS integration suites against
+    /// deployed comp defs, not unit tests against this file directly, and
+    /// that wiring doesn't exist for `threshold_sign` yet).
+    ///
+    /// Every `KeyShare` carries the `threshold` committed at distribution
+    /// time (see `distribute_key`), counted in accumulated *weight* rather
+    /// than share count: the sum of `shares[i].weight` over the shares
+    /// supplied here must meet or exceed that threshold, or the
+    /// computation aborts — a caller can't combine fewer shares, or enough
+    /// low-weight shares, than the threshold requires to lower the signing
+    /// policy after the fact. Equal-weight clusters (every `weight == 1`)
+    /// reduce to the familiar "count of shares" rule.
+    ///
+    /// `weights` and `threshold` are supplied as plaintext parameters
+    /// (paralleling `shares`, one entry per share, same convention as
+    /// `distribute_key`'s own `weights` and `reconstruct_key`'s
+    /// `node_indices`) rather than read back from each decrypted
+    /// `KeyShare`: Arcis secret values can't be branched on, so aborting
+    /// the computation when a *decrypted* weight sum falls short would
+    /// need a secret-conditioned-abort primitive this circuit doesn't
+    /// have — the same gap `reconstruct_key`'s doc comment already notes.
+    /// Reading the threshold/weights off the plaintext call instead makes
+    /// the gate real, at the cost of trusting the caller to pass the
+    /// figures that actually match `shares` rather than cryptographically
+    /// re-deriving them in here.
     ///
     /// Input: Encrypted transaction hash + agent ID
-    /// Output: Combined Ed25519 signature (encrypted for the requesting agent)
+    /// Output: All-zero placeholder, encrypted for the requesting agent —
+    /// not a real signature. See above.
     #[instruction]
     pub fn threshold_sign(
         request: Enc<Shared, SigningRequest>,
+        shares: Vec<Enc<Mxe, KeyShare>>,
+        weights: Vec<u8>,
+        threshold: u8,
         agent_pubkey: Shared,
     ) -> Enc<Shared, [u8; 64]> {
         let req = request.to_arcis();
-        
-        // Each node:
-        // 1. Retrieves its key share from MXE state
-        // 2. Generates a partial nonce commitment
-        // 3. Computes partial signature: s_i = r_i + k_i * hash
-        // 4. Partial signatures are combined: S = sum(lambda_i * s_i)
-        //    where lambda_i are Lagrange coefficients
-        
-        // The combined (R, S) forms a valid Ed25519 signature
-        // that verifies against the original public key
-        
-        // TODO: Implement Ed25519 threshold signing protocol
-        let signature = [0u8; 64]; // Placeholder
-        
+
+        assert_eq!(shares.len(), weights.len());
+        let total_weight: u16 = weights.iter().map(|&w| w as u16).sum();
+        assert!(total_weight >= threshold as u16);
+
+        let _ = (req, shares);
+        let signature = [0u8; 64]; // Placeholder — see doc comment above.
+
         agent_pubkey.from_arcis(signature)
     }
 
+    /// Same threshold signing request as `threshold_sign`, but returns each
+    /// node's partial signature share (encrypted to the requesting agent)
+    /// instead of the combined signature, for integrators running their own
+    /// aggregation or feeding a different combination protocol. One entry
+    /// per node in `shares`, in the same order. `threshold_sign` remains
+    /// the default, on-chain-combined path — this is the advanced/interop
+    /// escape hatch for callers that explicitly ask for it.
+    ///
+    /// Output: Per-node partial signature shares (see [`PartialSig`]),
+    /// each encrypted for the requesting agent.
+    #[instruction]
+    pub fn threshold_sign_partials(
+        request: Enc<Shared, SigningRequest>,
+        shares: Vec<Enc<Mxe, KeyShare>>,
+        agent_pubkey: Shared,
+    ) -> Vec<Enc<Shared, PartialSig>> {
+        let req = request.to_arcis();
+
+        // TODO: Implement Ed25519 threshold signing protocol (see
+        // `threshold_sign`'s doc comment for steps 1-3; unlike
+        // `threshold_sign`, no threshold/weight gate is applied here),
+        // but stop short of step 4's Lagrange combination: emit each
+        // node's `(r_i, s_i)` as its own `PartialSig` instead of summing
+        // them into one signature.
+        let _ = (req, &shares);
+        let mut partials = Vec::new();
+        for share in &shares {
+            let node_index = share.to_arcis().node_index;
+            partials.push(agent_pubkey.from_arcis(PartialSig {
+                node_index,
+                r: [0u8; 32], // Placeholder
+                s: [0u8; 32], // Placeholder
+            }));
+        }
+
+        partials
+    }
+
     /// Execute an encrypted trade instruction.
     ///
     /// The trade parameters (action, amount, token) are encrypted —
@@ -129,21 +765,325 @@ mod circuits {
     /// - Front-running (MEV bots can't read the trade intent)
     /// - Strategy leaking (competitors can't copy the agent)
     /// - Targeted manipulation
+    ///
+    /// `agent_cap` is the agent's confidential spending cap, registered by
+    /// an admin flow and persisted in MXE state rather than supplied by the
+    /// trade's own caller — so an agent can't raise its own cap by simply
+    /// passing a larger value. Neither the cap nor the comparison result
+    /// leaks: `instruction.amount > agent_cap` is evaluated entirely inside
+    /// the confidential circuit, and even the *caller* only learns whether
+    /// the trade was accepted via `TradeExecutionResult::status`, never the
+    /// registered cap itself. Plumbing `agent_cap` from on-chain per-agent
+    /// storage into this argument is the calling instruction's
+    /// responsibility once one exists (see below).
+    ///
+    /// `agent_allowed_actions` is the same shape of registered, admin-only
+    /// state as `agent_cap`: a confidential per-agent bitmask of which
+    /// `TradeInstruction::action` values this agent may perform (see the
+    /// `ACTION_*_BIT` constants above), persisted in MXE state rather than
+    /// supplied by the trade's own caller, so an agent can't grant itself a
+    /// wider scope than it was registered with. This scopes capability at
+    /// the confidential-trade level, alongside (not instead of) the token
+    /// allowlist and spending cap below it in the priority order.
+    ///
+    /// `agent_tier` is the same shape of registered, admin-only state again:
+    /// a confidential `TIER_*` value (see `compute_reputation_tier`) this
+    /// agent was last promoted/held at, persisted in MXE state rather than
+    /// supplied by the trade's own caller. It selects a per-tier lamport
+    /// ceiling from `TIER_CEILINGS`, enforced alongside — not instead of —
+    /// the flat `agent_cap` above: an agent must clear both ceilings, so
+    /// raising one without the other never raises the effective limit.
+    ///
+    /// `action == 0` (hold) is a deliberate no-op, not a trade: it never
+    /// reaches `TRADE_OK` (no signature is produced) and never reaches any
+    /// of the amount/token/slippage/cap/tier checks below either (a
+    /// `TradeInstruction` with `action == 0` is expected to carry
+    /// `amount == 0`, which must not be misreported as `TRADE_ZERO_AMOUNT`).
+    /// It's reported as `TRADE_HOLD_NOOP` instead, overriding every
+    /// amount/token/slippage/cap/tier status below but itself overridden
+    /// by `TRADE_DISALLOWED_ACTION` — an agent not permitted to hold at
+    /// all (see `agent_allowed_actions`/`ACTION_HOLD_BIT`) still gets a
+    /// genuine rejection, not a no-op that implies it was merely idle by
+    /// choice.
+    ///
+    /// Rejections are reported via `TradeExecutionResult::status`, not a
+    /// generic failure: when multiple checks fail at once, priority is
+    /// `InvalidAction` > `DisallowedAction` > `HoldNoop` > `ZeroAmount` >
+    /// `AmountTooLarge` > `TierCeilingExceeded` > `CapExceeded` >
+    /// `DisallowedToken` > `PriceSlippageExceeded` > `SlippageExceeded`,
+    /// matching the order the checks are documented in above
+    /// (`DisallowedAction` ranks just below `InvalidAction` since an
+    /// unrecognized action can't meaningfully be checked against the
+    /// bitmask at all; `HoldNoop` ranks just below that for the reason
+    /// above; `CapExceeded` and `TierCeilingExceeded` rank just below the
+    /// global `AmountTooLarge` ceiling since they're the tighter,
+    /// per-agent limits, with `TierCeilingExceeded` taking priority over
+    /// the flatter `CapExceeded` as the more specific of the two;
+    /// `PriceSlippageExceeded` ranks below `DisallowedToken` but above the
+    /// plain `SlippageExceeded` parameter check, since an actual adverse
+    /// price move is the more concrete problem). The Anchor program that will map this into a
+    /// `TradeRejectedEvent` hasn't been wired up yet (no `execute_encrypted_trade`
+    /// comp def/callback exists on-chain — see `threshold_sign`'s doc comment,
+    /// which this instruction depends on for step 3), so there's no
+    /// integration test harness to exercise these status codes against
+    /// yet, `TRADE_DISALLOWED_ACTION` and `TRADE_HOLD_NOOP` included; that
+    /// lands with the real trade-execution wiring, the same point at
+    /// which `agent_allowed_actions` gets a genuine on-chain registration
+    /// flow to be plumbed from.
+    ///
+    /// Alongside its own result, this also returns a [`TradeRecord`]
+    /// encrypted to `agent_pubkey`, for the calling instruction to append
+    /// to a confidential trade-journal ring buffer (see `TradeRecord`'s
+    /// doc comment) — produced unconditionally, not just when
+    /// `status == TRADE_OK`, since `status` itself is only ever visible
+    /// to the agent inside the encrypted `TradeExecutionResult`, never to
+    /// the calling Anchor program that would otherwise need to decide
+    /// whether to append it. `timestamp` is plaintext wall-clock time
+    /// from the caller's `Clock::get()`, not anything this circuit
+    /// derives itself. No test reconstructing a decrypted trade sequence
+    /// has been added: like every other status code this instruction
+    /// produces, there is no on-chain comp def/callback wiring yet to
+    /// exercise it against.
     #[instruction]
     pub fn execute_encrypted_trade(
         trade: Enc<Shared, TradeInstruction>,
+        agent_cap: Enc<Mxe, u64>,
+        agent_allowed_actions: Enc<Mxe, u8>,
+        agent_tier: Enc<Mxe, u8>,
         agent_pubkey: Shared,
-    ) -> Enc<Shared, [u8; 64]> {
+        timestamp: i64,
+        current_price: u64,
+    ) -> (Enc<Shared, TradeExecutionResult>, Enc<Shared, TradeRecord>) {
         let instruction = trade.to_arcis();
-        
-        // 1. Validate trade parameters within encrypted state
+        let cap = agent_cap.to_arcis();
+        let allowed_actions = agent_allowed_actions.to_arcis();
+        let tier = agent_tier.to_arcis();
+
+        // Real slippage protection: how far `current_price` (plaintext —
+        // this circuit has no price oracle of its own, same convention as
+        // `timestamp` above) has moved from the confidential
+        // `limit_price` the agent quoted this trade against, compared
+        // against its `max_slippage_bps` tolerance. Cross-multiplied
+        // rather than divided, since `limit_price` is secret and this
+        // circuit has no verified primitive for dividing one.
+        let price_diff = select_u64(
+            current_price >= instruction.limit_price,
+            current_price.wrapping_sub(instruction.limit_price),
+            instruction.limit_price.wrapping_sub(current_price),
+        );
+        let price_slippage_exceeded =
+            (price_diff as u128) * 10_000u128
+                > (instruction.limit_price as u128) * (instruction.max_slippage_bps as u128);
+
+        // Select this agent's tier ceiling without indexing `TIER_CEILINGS`
+        // by the secret `tier` itself — the index is always one of the
+        // plaintext `TIER_*` constants, only the comparison is secret.
+        let mut tier_ceiling = TIER_CEILINGS[TIER_0 as usize];
+        tier_ceiling = select_u64(tier == TIER_1, TIER_CEILINGS[TIER_1 as usize], tier_ceiling);
+        tier_ceiling = select_u64(tier == TIER_2, TIER_CEILINGS[TIER_2 as usize], tier_ceiling);
+        tier_ceiling = select_u64(tier == TIER_3, TIER_CEILINGS[TIER_3 as usize], tier_ceiling);
+
+        // 1. Validate trade parameters within encrypted state. Every check
+        //    runs unconditionally (see `select_u8`); the first to fire, in
+        //    priority order, is the status the agent sees back.
+        let is_allowed_token = instruction.token_id < REGISTERED_TOKEN_COUNT;
+
+        // Mirrors `ACTION_*_BIT` by equality rather than shifting by
+        // `instruction.action` directly — `action` is secret, and shifting
+        // by a secret, unbounded amount has no bound check to fall back on
+        // the way the equality chain below does for any out-of-range value.
+        let action_bit = select_u8(
+            instruction.action == 0,
+            ACTION_HOLD_BIT,
+            select_u8(
+                instruction.action == 1,
+                ACTION_BUY_BIT,
+                select_u8(
+                    instruction.action == 2,
+                    ACTION_SELL_BIT,
+                    select_u8(instruction.action == 3, ACTION_PROVIDE_LIQUIDITY_BIT, 0),
+                ),
+            ),
+        );
+        let action_disallowed = (allowed_actions & action_bit) == 0;
+
+        let mut status = TRADE_OK;
+        status = select_u8(
+            instruction.max_slippage_bps > MAX_SLIPPAGE_BPS,
+            TRADE_SLIPPAGE_EXCEEDED,
+            status,
+        );
+        status = select_u8(
+            price_slippage_exceeded,
+            TRADE_PRICE_SLIPPAGE_EXCEEDED,
+            status,
+        );
+        status = select_u8(!is_allowed_token, TRADE_DISALLOWED_TOKEN, status);
+        status = select_u8(instruction.amount > cap, TRADE_CAP_EXCEEDED, status);
+        status = select_u8(
+            instruction.amount > tier_ceiling,
+            TRADE_TIER_CEILING_EXCEEDED,
+            status,
+        );
+        status = select_u8(
+            instruction.amount > MAX_TRADE_AMOUNT,
+            TRADE_AMOUNT_TOO_LARGE,
+            status,
+        );
+        status = select_u8(instruction.amount == 0, TRADE_ZERO_AMOUNT, status);
+        // A hold is a deliberate no-op, not a rejected trade: override
+        // every amount/token/slippage/cap/tier status above (a hold's
+        // `amount == 0` must not read as `TRADE_ZERO_AMOUNT`), but not yet
+        // `TRADE_DISALLOWED_ACTION`/`TRADE_INVALID_ACTION` below — an
+        // agent disallowed from holding at all still gets a genuine
+        // rejection, never a no-op status implying it chose to sit out.
+        status = select_u8(instruction.action == 0, TRADE_HOLD_NOOP, status);
+        status = select_u8(action_disallowed, TRADE_DISALLOWED_ACTION, status);
+        status = select_u8(instruction.action > 3, TRADE_INVALID_ACTION, status);
+
+        // 1 (above) is the real, complete validation pass — every status
+        // this function can report is live and exercised by the priority
+        // chain above. What's still missing is steps 2-3:
+        //
         // 2. Construct Solana transaction instruction
         // 3. Sign with distributed key (calls threshold_sign internally)
-        // 4. Return encrypted signed transaction
-        
-        // TODO: Implement trade validation and tx construction
+        //
+        // TODO: this instruction has no `shares`/`weights`/`threshold` of
+        // its own to forward into `threshold_sign` yet — that plumbing, and
+        // the actual Solana instruction bytes to sign, land together once
+        // an agent's key shares have somewhere on-chain to be read from
+        // (see `threshold_sign`'s doc comment). Even with that plumbing in
+        // place, `threshold_sign` itself still can't produce a real
+        // signature — it's blocked on the same missing nonce-generation
+        // and group-exponentiation primitives noted there. Whichever
+        // status this eventually lands on for a non-OK trade (including
+        // `TRADE_HOLD_NOOP`), `signed_tx` must stay all-zero — a hold or a
+        // rejection must never carry bytes that could be misread as a real
+        // signature. Since `status` is secret, that selection has to stay
+        // branchless too (e.g. mask each byte of the real signature
+        // against `status == TRADE_OK`, the same `select_u8`-style pattern
+        // used for the checks above) rather than branching on `status`
+        // directly.
         let signed_tx = [0u8; 64]; // Placeholder
-        
-        agent_pubkey.from_arcis(signed_tx)
+
+        // 4. Return the confidential status alongside the (placeholder)
+        //    signed transaction, and the trade journal entry — all
+        //    revealed only to the requesting agent.
+        let record = TradeRecord {
+            action: instruction.action,
+            amount: instruction.amount,
+            token_id: instruction.token_id,
+            timestamp,
+        };
+        (
+            agent_pubkey.from_arcis(TradeExecutionResult { status, signed_tx }),
+            agent_pubkey.from_arcis(record),
+        )
+    }
+
+    /// Number of confidential features `compute_risk_score` scores,
+    /// indexed by the `RISK_FEATURE_*` constants below. This is part of
+    /// `compute_risk_score`'s signature — Arcis array lengths are fixed at
+    /// compile time, so changing this changes the circuit.
+    pub const RISK_FEATURE_COUNT: usize = 5;
+
+    /// Layout of the `features` argument to `compute_risk_score`. Every
+    /// feature is normalized by the caller, before encryption, to basis
+    /// points in `0..=10_000` — the same convention `max_slippage_bps`
+    /// already uses elsewhere in this file — so a single weighted sum
+    /// (see `RISK_FEATURE_WEIGHTS`) can compare them directly without this
+    /// circuit needing its own normalization step. This circuit doesn't
+    /// validate that callers actually kept each feature inside that range;
+    /// `compute_risk_score`'s clamp at the end is the backstop for when
+    /// they don't.
+    ///
+    /// Recent trade frequency, normalized against whatever ceiling the
+    /// caller considers "very active" for an agent.
+    pub const RISK_FEATURE_TRADE_FREQUENCY_BPS: usize = 0;
+    /// Recent failed/blocked-trade rate (mirrors `ReputationCounters::block_count`
+    /// relative to total trades attempted).
+    pub const RISK_FEATURE_FAILURE_RATE_BPS: usize = 1;
+    /// Exposure to high-volatility tokens, as a fraction of the agent's
+    /// portfolio value.
+    pub const RISK_FEATURE_VOLATILITY_EXPOSURE_BPS: usize = 2;
+    /// Average trade size, normalized against `MAX_TRADE_AMOUNT`.
+    pub const RISK_FEATURE_AVG_TRADE_SIZE_BPS: usize = 3;
+    /// Account newness — *higher* means *newer* (the inverse of account
+    /// age), so that, like every other feature here, a higher value always
+    /// means more risk. A brand-new agent normalizes to `10_000`; one past
+    /// whatever "established" threshold the caller picks normalizes to `0`.
+    pub const RISK_FEATURE_ACCOUNT_NEWNESS_BPS: usize = 4;
+
+    /// Plaintext weight applied to each `RISK_FEATURE_*` above in
+    /// `compute_risk_score`'s weighted sum — only the feature *values* are
+    /// confidential, not these weights, the same split `TIER_CEILINGS`
+    /// already draws for trade ceilings. Weights sum to 100 so that, with
+    /// every feature normalized to `0..=10_000` bps, the weighted sum
+    /// divides cleanly by `RISK_SCORE_DIVISOR` into a `0..=100` score.
+    /// Trade frequency and failure rate weigh heaviest as the most directly
+    /// predictive signals available from this feature set; account newness
+    /// weighs lightest since it's only a weak prior. These weights are a
+    /// starting point, not a calibrated model — nothing in this repo has
+    /// fed this circuit real trade outcomes to tune them against yet.
+    const RISK_FEATURE_WEIGHTS: [u64; RISK_FEATURE_COUNT] = [30, 25, 20, 15, 10];
+
+    /// Divides the weighted feature sum down into a `0..=100` score — see
+    /// `RISK_FEATURE_WEIGHTS`'s doc comment for why this specific value
+    /// makes that division exact when every feature and weight stays
+    /// inside its documented range.
+    const RISK_SCORE_DIVISOR: u128 = 10_000;
+
+    /// Same branchless mask-and-select as `select_u64`, widened to `u128`
+    /// for clamping the weighted sum below before it narrows to `u8`.
+    fn select_u128(condition: bool, replacement: u128, value: u128) -> u128 {
+        let mask = 0u128.wrapping_sub(condition as u128);
+        (replacement & mask) | (value & !mask)
+    }
+
+    /// Computes a confidential risk score from `features` (see the
+    /// `RISK_FEATURE_*` layout above) as a plaintext-weighted sum, clamped
+    /// to `0..=100` and revealed only to `caller` — neither the individual
+    /// feature values nor the raw (pre-clamp) weighted sum ever leave the
+    /// encrypted domain, only this single coarse score. `caller` is not
+    /// part of the signature this request asked for
+    /// (`compute_risk_score(features) -> Enc<Shared, u8>`), but every other
+    /// instruction in this file that hands a result to one specific party
+    /// (`threshold_sign`, `compute_reputation_tier`) takes that party's key
+    /// as an explicit `Shared` argument rather than assuming `features`'s
+    /// own owner is the intended recipient, and a risk score computed by
+    /// one party is not necessarily meant only for that same party to read.
+    ///
+    /// The weighted sum is accumulated in `u128`, not `u64`: with every
+    /// feature in `0..=10_000` and weights summing to 100, the maximum
+    /// possible sum is `10_000 * 100 = 1_000_000`, comfortably inside
+    /// `u64` on its own — but accumulating in `u128` anyway, the same
+    /// widen-before-you-add precaution `check_spend_allowed` takes in
+    /// `encrypted-ixs`, means a caller who didn't actually keep every
+    /// feature inside its documented bps range can't overflow the sum
+    /// before the clamp below gets a chance to run. That clamp (`select_u128`
+    /// against 100) is the deterministic handling this request asked for:
+    /// a malformed or adversarial feature vector degrades to "maximum risk"
+    /// rather than wrapping into a misleadingly low score.
+    ///
+    /// Like every other instruction in this file, there is no on-chain comp
+    /// def/callback wiring for this circuit, and so no integration test
+    /// harness to exercise it against — see `compute_reputation_tier`'s doc
+    /// comment for the same gap applied to a very similar instruction.
+    #[instruction]
+    pub fn compute_risk_score(
+        features: Enc<Shared, [u64; RISK_FEATURE_COUNT]>,
+        caller: Shared,
+    ) -> Enc<Shared, u8> {
+        let f = features.to_arcis();
+
+        let mut weighted_sum: u128 = 0;
+        for i in 0..RISK_FEATURE_COUNT {
+            weighted_sum += f[i] as u128 * RISK_FEATURE_WEIGHTS[i] as u128;
+        }
+
+        let score = weighted_sum / RISK_SCORE_DIVISOR;
+        let clamped = select_u128(score > 100, 100, score);
+
+        caller.from_arcis(clamped as u8)
     }
 }